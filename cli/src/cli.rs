@@ -17,8 +17,17 @@ use hdp::{
     preprocessor::module_registry::ModuleRegistry,
     primitives::task::{
         datalake::{
-            block_sampled::BlockSampledDatalake, compute::Computation, envelope::DatalakeEnvelope,
-            transactions::TransactionsInBlockDatalake, DatalakeCompute,
+            block_sampled::{
+                BlockSampledCollection, BlockSampledDatalake, RandomSampling, StorageLayout,
+                TimestampRange,
+            },
+            compute::Computation,
+            envelope::DatalakeEnvelope,
+            transactions::{
+                TransactionsByHashDatalake, TransactionsInBlockDatalake,
+                TransactionsInRangeDatalake, TxIndexRange,
+            },
+            DatalakeCompute,
         },
         TaskEnvelope,
     },
@@ -133,13 +142,77 @@ pub async fn datalake_entry_run(args: RunDatalakeArgs) -> Result<()> {
             block_range_end,
             sampled_property,
             increment,
-        } => DatalakeEnvelope::BlockSampled(BlockSampledDatalake::new(
+            storage_layout,
+            sample_size,
+            seed,
+            seed_block,
+        } => {
+            let sampled_property = match storage_layout {
+                Some(path) => {
+                    let layout: StorageLayout = fs::read_to_string(path)?.parse()?;
+                    BlockSampledCollection::from_str_with_layout(&sampled_property, &layout)?
+                }
+                None => sampled_property.parse()?,
+            };
+            let block_sampled_datalake = match (sample_size, seed) {
+                (Some(sample_size), Some(seed)) => BlockSampledDatalake::from_random_sampling(
+                    chain_id,
+                    block_range_start,
+                    block_range_end,
+                    increment,
+                    RandomSampling::new(seed, sample_size, seed_block),
+                    sampled_property,
+                )?,
+                _ => BlockSampledDatalake::new(
+                    chain_id,
+                    block_range_start,
+                    block_range_end,
+                    increment,
+                    sampled_property,
+                ),
+            };
+            DatalakeEnvelope::BlockSampled(block_sampled_datalake)
+        }
+        DataLakeCommands::BlockList {
             chain_id,
-            block_range_start,
-            block_range_end,
-            increment,
+            block_list,
             sampled_property,
-        )),
+            storage_layout,
+        } => {
+            let sampled_property = match storage_layout {
+                Some(path) => {
+                    let layout: StorageLayout = fs::read_to_string(path)?.parse()?;
+                    BlockSampledCollection::from_str_with_layout(&sampled_property, &layout)?
+                }
+                None => sampled_property.parse()?,
+            };
+            DatalakeEnvelope::BlockSampled(BlockSampledDatalake::from_block_list(
+                chain_id,
+                block_list,
+                sampled_property,
+            )?)
+        }
+        DataLakeCommands::BlockTimestamps {
+            chain_id,
+            from_timestamp,
+            to_timestamp,
+            sampled_property,
+            time_step,
+            storage_layout,
+        } => {
+            let sampled_property = match storage_layout {
+                Some(path) => {
+                    let layout: StorageLayout = fs::read_to_string(path)?.parse()?;
+                    BlockSampledCollection::from_str_with_layout(&sampled_property, &layout)?
+                }
+                None => sampled_property.parse()?,
+            };
+            DatalakeEnvelope::BlockSampled(BlockSampledDatalake::from_timestamp_range(
+                chain_id,
+                TimestampRange::new(from_timestamp, to_timestamp, time_step)?,
+                sampled_property,
+            ))
+        }
         DataLakeCommands::TransactionsInBlock {
             chain_id,
             target_block,
@@ -148,19 +221,60 @@ pub async fn datalake_entry_run(args: RunDatalakeArgs) -> Result<()> {
             end_index,
             increment,
             included_types,
-        } => DatalakeEnvelope::TransactionsInBlock(TransactionsInBlockDatalake::new(
+            filter,
+        } => DatalakeEnvelope::TransactionsInBlock(
+            TransactionsInBlockDatalake::new(
+                chain_id,
+                target_block,
+                sampled_property,
+                0,
+                0,
+                increment,
+                included_types,
+            )
+            .with_index_range(TxIndexRange::new(start_index, end_index))
+            .with_filter(filter.unwrap_or_default()),
+        ),
+        DataLakeCommands::TransactionsInRange {
             chain_id,
-            target_block,
+            block_range_start,
+            block_range_end,
+            block_increment,
             sampled_property,
             start_index,
             end_index,
             increment,
             included_types,
-        )),
+            filter,
+        } => DatalakeEnvelope::TransactionsInRange(
+            TransactionsInRangeDatalake::new(
+                chain_id,
+                block_range_start,
+                block_range_end,
+                block_increment,
+                sampled_property,
+                start_index,
+                end_index,
+                increment,
+                included_types,
+            )
+            .with_filter(filter.unwrap_or_default()),
+        ),
+        DataLakeCommands::TransactionsByHash {
+            chain_id,
+            tx_hashes,
+            sampled_property,
+        } => DatalakeEnvelope::TransactionsByHash(TransactionsByHashDatalake::new(
+            chain_id,
+            tx_hashes,
+            sampled_property,
+        )?),
     };
-    let tasks = vec![TaskEnvelope::DatalakeCompute(DatalakeCompute::new(
-        parsed_datalake,
-        Computation::new(args.aggregate_fn_id, args.aggregate_fn_ctx),
+    let tasks = vec![TaskEnvelope::DatalakeCompute(Box::new(
+        DatalakeCompute::new(
+            parsed_datalake,
+            Computation::new(args.aggregate_fn_id, args.aggregate_fn_ctx),
+        ),
     ))];
 
     hdp::run(&config, tasks).await?;
@@ -186,7 +300,14 @@ pub async fn entry_run(args: RunArgs) -> Result<()> {
     for task in parsed.tasks {
         match task {
             Task::DatalakeCompute(task) => {
-                task_envelopes.push(TaskEnvelope::DatalakeCompute(task));
+                task_envelopes.push(TaskEnvelope::DatalakeCompute(Box::new(task)));
+            }
+            Task::MultiDatalakeCompute(task) => {
+                task_envelopes.extend(
+                    task.into_datalake_computes()
+                        .into_iter()
+                        .map(|task| TaskEnvelope::DatalakeCompute(Box::new(task))),
+                );
             }
             Task::Module(task) => {
                 let module = module_registry
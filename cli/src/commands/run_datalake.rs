@@ -1,12 +1,11 @@
 use std::path::PathBuf;
 
-use alloy::primitives::{BlockNumber, TxIndex};
+use alloy::primitives::{BlockNumber, TxHash, TxIndex, B256};
 use clap::{arg, command, Parser, Subcommand};
 use hdp::primitives::{
     aggregate_fn::{AggregationFunction, FunctionContext},
-    task::datalake::{
-        block_sampled::BlockSampledCollection,
-        transactions::{IncludedTypes, TransactionsCollection},
+    task::datalake::transactions::{
+        IncludedTypes, TransactionsCollection, TransactionsFilter, TxIndexBound,
     },
     ChainId,
 };
@@ -18,6 +17,15 @@ pub struct RunDatalakeArgs {
     /// Optional context for applying conditions on the aggregate function "count".
     /// Format: "{operator}.{value}" (e.g., "eq.100" for equality, "gt.100" for greater-than).
     /// Supported operators are in the [`Operator`] enum.
+    /// For "percentile", the format is "p{percentile}" (e.g., "p95" for the 95th percentile).
+    /// For "bloom", the format is "bits{size}" (e.g., "bits512" for a 512 bits filter, 2048 by default).
+    /// For "min", "max", "net_change" and "max_drawdown", "signed" takes the values as int256.
+    /// For "avg", the format is "d{decimals}" or "d{decimals}.{rounding}" for a fixed-point result,
+    /// with rounding "half_up" (default), "floor" or "ceil" (e.g., "d4.floor" for 7142 from 5/7).
+    /// For "histogram", the format is "buckets({boundary},..)" with strictly ascending boundaries
+    /// (e.g., "buckets(10,100)" for the buckets [0, 10), [10, 100) and [100, 2^256)).
+    /// Any context can be followed by "where {predicate}" to aggregate only the matching values
+    /// (e.g., "where and(gt.1000000000,lt.100000000000)"), see [`Predicate`] for the syntax.
     pub aggregate_fn_ctx: Option<FunctionContext>,
 
     #[command(subcommand)]
@@ -64,10 +72,62 @@ pub enum DataLakeCommands {
         /// Block number range end (inclusive)
         block_range_end: BlockNumber,
         /// Sampled property e.g. "header.number", "account.0xaccount.balance", "storage.0xcontract.0xstoragekey"
-        sampled_property: BlockSampledCollection,
+        ///
+        /// Packed value in a storage slot can be sampled with a byte slice e.g. "storage.0xcontract.0xstoragekey[20:3:signed]"
+        ///
+        /// With a storage layout, storage variables can be referred by name e.g. "storage.0xcontract._balances[0xholder]"
+        ///
+        /// Code of an account can be sampled e.g. "code.0xcontract.size", "code.0xcontract.word[32]"
+        sampled_property: String,
         /// Increment number of given range blocks
         #[arg(default_value_t = 1)]
         increment: u64,
+        /// Path to the storage layout json of the contract, output of `solc --storage-layout`
+        #[arg(long)]
+        storage_layout: Option<PathBuf>,
+        /// Number of blocks to sample pseudo-randomly from the range, instead of every block
+        #[arg(long, requires = "seed")]
+        sample_size: Option<u64>,
+        /// Seed of the random sampling, e.g. a future block hash or a nonce
+        #[arg(long, requires = "sample_size")]
+        seed: Option<B256>,
+        /// Block whose hash is the seed, to prove the seed along with the datalake
+        #[arg(long, requires = "seed")]
+        seed_block: Option<BlockNumber>,
+    },
+
+    #[command(arg_required_else_help = true)]
+    #[command(short_flag = 'l')]
+    BlockList {
+        /// Chain id
+        chain_id: ChainId,
+        /// Comma separated, strictly ascending block numbers e.g. "19000000,19216000,19432000"
+        #[arg(value_delimiter = ',')]
+        block_list: Vec<BlockNumber>,
+        /// Sampled property, same as for block sampled datalake
+        sampled_property: String,
+        /// Path to the storage layout json of the contract, output of `solc --storage-layout`
+        #[arg(long)]
+        storage_layout: Option<PathBuf>,
+    },
+
+    #[command(arg_required_else_help = true)]
+    #[command(short_flag = 'm')]
+    BlockTimestamps {
+        /// Chain id
+        chain_id: ChainId,
+        /// Timestamp range start in seconds (inclusive), resolved to the first block at or after it
+        from_timestamp: u64,
+        /// Timestamp range end in seconds (inclusive), resolved to the last block at or before it
+        to_timestamp: u64,
+        /// Sampled property, same as for block sampled datalake
+        sampled_property: String,
+        /// Time step in seconds, to sample the block of every step instead of every block in the range
+        #[arg(long)]
+        time_step: Option<u64>,
+        /// Path to the storage layout json of the contract, output of `solc --storage-layout`
+        #[arg(long)]
+        storage_layout: Option<PathBuf>,
     },
 
     #[command(arg_required_else_help = true)]
@@ -80,16 +140,69 @@ pub enum DataLakeCommands {
         /// Sampled property
         /// Fields from transaction: "chain_id", "gas_price"... etc
         /// Fields from transaction receipt: "cumulative_gas_used".. etc
+        /// Fee fields derived from transaction, receipt and header: "tx_fee.effective_gas_price".. etc
         sampled_property: TransactionsCollection,
         /// Start index of transactions range (inclusive)
-        start_index: TxIndex,
+        /// Negative index is relative to the end of the block e.g. "-10"
+        #[arg(allow_hyphen_values = true)]
+        start_index: TxIndexBound,
         /// End index of transactions range (exclusive)
-        end_index: TxIndex,
+        /// Negative index is relative to the end of the block, "last" is the end of the block
+        #[arg(allow_hyphen_values = true)]
+        end_index: TxIndexBound,
         /// Increment number of transaction indexes in the block
         increment: u64,
         /// Filter out the specific type of Txs
         /// Each byte represents a type of transaction to be included in the datalake
         /// e.g 1,0,1,0 -> include legacy, exclude eip2930, include eip1559, exclude eip4844
         included_types: IncludedTypes,
+        /// Filter on sender, recipient and function selector of Txs
+        /// e.g "to=0xcontract,selector=0xa9059cbb"
+        #[arg(long)]
+        filter: Option<TransactionsFilter>,
+    },
+
+    #[command(arg_required_else_help = true)]
+    #[command(short_flag = 'r')]
+    TransactionsInRange {
+        /// Chain id
+        chain_id: ChainId,
+        /// Block number range start (inclusive)
+        block_range_start: BlockNumber,
+        /// Block number range end (inclusive)
+        block_range_end: BlockNumber,
+        /// Increment number of blocks
+        block_increment: u64,
+        /// Sampled property
+        /// Fields from transaction: "chain_id", "gas_price"... etc
+        /// Fields from transaction receipt: "cumulative_gas_used".. etc
+        /// Fee fields derived from transaction, receipt and header: "tx_fee.effective_gas_price".. etc
+        sampled_property: TransactionsCollection,
+        /// Start index of transactions range in each block (inclusive)
+        start_index: TxIndex,
+        /// End index of transactions range in each block (exclusive), bounded by the number of transactions in the block
+        end_index: TxIndex,
+        /// Increment number of transaction indexes in each block
+        increment: u64,
+        /// Filter out the specific type of Txs
+        /// Each byte represents a type of transaction to be included in the datalake
+        /// e.g 1,0,1,0 -> include legacy, exclude eip2930, include eip1559, exclude eip4844
+        included_types: IncludedTypes,
+        /// Filter on sender, recipient and function selector of Txs
+        /// e.g "to=0xcontract,selector=0xa9059cbb"
+        #[arg(long)]
+        filter: Option<TransactionsFilter>,
+    },
+
+    #[command(arg_required_else_help = true)]
+    #[command(short_flag = 'x')]
+    TransactionsByHash {
+        /// Chain id
+        chain_id: ChainId,
+        /// Comma separated hashes of the transactions, located in their blocks before compilation
+        #[arg(value_delimiter = ',')]
+        tx_hashes: Vec<TxHash>,
+        /// Sampled property, same as for transactions in block datalake
+        sampled_property: TransactionsCollection,
     },
 }
@@ -1,22 +1,28 @@
-use alloy::primitives::U256;
+use alloy::primitives::{TxHash, B256, U256};
 use anyhow::bail;
 use hdp::hdp_run;
 use hdp::preprocessor::module_registry::ModuleRegistry;
 use hdp::primitives::ChainId;
 use hdp::primitives::{
-    aggregate_fn::{integer::Operator, FunctionContext},
+    aggregate_fn::{
+        integer::{Operator, Rounding},
+        FunctionContext,
+    },
     task::{
         datalake::{
             block_sampled::{
                 AccountField, BlockSampledCollection, BlockSampledCollectionType,
-                BlockSampledDatalake, HeaderField,
+                BlockSampledDatalake, CodeField, HeaderField, RandomSampling, StorageLayout,
+                TimestampRange,
             },
             compute::Computation,
             datalake_type::DatalakeType,
             envelope::DatalakeEnvelope,
             transactions::{
-                IncludedTypes, TransactionField, TransactionReceiptField, TransactionsCollection,
-                TransactionsCollectionType, TransactionsInBlockDatalake,
+                IncludedTypes, TransactionFeeField, TransactionField, TransactionReceiptField,
+                TransactionsByHashDatalake, TransactionsCollection, TransactionsCollectionType,
+                TransactionsFilter, TransactionsInBlockDatalake, TransactionsInRangeDatalake,
+                TxIndexRange,
             },
             DatalakeCompute,
         },
@@ -65,32 +71,117 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                                 .with_help_message("What is the chain ID? (Enter to set default)")
                                 .with_default("ETHEREUM_SEPOLIA")
                                 .prompt()?;
-                            // 1. Block range start
-                            let block_range_start: u64 = inquire::Text::new("Block range start")
-                                .with_help_message(
-                                    "What is the block range start? (Enter to set default)",
-                                )
-                                .with_default("4952200")
-                                .prompt()?
-                                .parse()?;
-                            // 2. Block range end
-                            let block_range_end: u64 = inquire::Text::new("Block range end")
-                                .with_help_message(
-                                    "What is the block range end? (Enter to set default)",
-                                )
-                                .with_default("4952229")
-                                .prompt()?
-                                .parse()?;
-                            // 3. Increment
-                            let increment: u64 = inquire::Text::new("Increment")
+                            // 1. Explicit block list, instead of a block range
+                            let block_list: Vec<u64> = inquire::Text::new("Block list")
                                 .with_help_message(
-                                    "How many blocks to skip in the range? (Enter to set default)",
+                                    "Comma separated blocks to sample instead of a range (Enter to skip)",
                                 )
-                                .with_default("1")
+                                .with_default("")
                                 .prompt()?
-                                .parse()?;
-                            // 4. Sampled Property
-                            // 4.1. Block Sampled Collection Type
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|block| !block.is_empty())
+                                .map(str::parse)
+                                .collect::<Result<_, _>>()?;
+                            // 1.1. Timestamp range, resolved to blocks before compilation
+                            let timestamp_range = if block_list.is_empty() {
+                                let from_timestamp = inquire::Text::new("From timestamp")
+                                    .with_help_message("Timestamp range start in seconds (Enter to use a block range)")
+                                    .with_default("")
+                                    .prompt()?;
+                                if from_timestamp.is_empty() {
+                                    None
+                                } else {
+                                    let to_timestamp: u64 = inquire::Text::new("To timestamp")
+                                        .with_help_message("Timestamp range end in seconds")
+                                        .prompt()?
+                                        .parse()?;
+                                    let time_step = inquire::Text::new("Time step")
+                                        .with_help_message("Seconds between sampled blocks (Enter to sample every block)")
+                                        .with_default("")
+                                        .prompt()?;
+                                    let time_step = if time_step.is_empty() {
+                                        None
+                                    } else {
+                                        Some(time_step.parse()?)
+                                    };
+                                    Some(TimestampRange::new(
+                                        from_timestamp.parse()?,
+                                        to_timestamp,
+                                        time_step,
+                                    )?)
+                                }
+                            } else {
+                                None
+                            };
+                            let (block_range_start, block_range_end, increment) = if block_list
+                                .is_empty()
+                                && timestamp_range.is_none()
+                            {
+                                // 2. Block range start
+                                let block_range_start: u64 =
+                                    inquire::Text::new("Block range start")
+                                        .with_help_message(
+                                            "What is the block range start? (Enter to set default)",
+                                        )
+                                        .with_default("4952200")
+                                        .prompt()?
+                                        .parse()?;
+                                // 3. Block range end
+                                let block_range_end: u64 = inquire::Text::new("Block range end")
+                                    .with_help_message(
+                                        "What is the block range end? (Enter to set default)",
+                                    )
+                                    .with_default("4952229")
+                                    .prompt()?
+                                    .parse()?;
+                                // 4. Increment
+                                let increment: u64 = inquire::Text::new("Increment")
+                                    .with_help_message(
+                                        "How many blocks to skip in the range? (Enter to set default)",
+                                    )
+                                    .with_default("1")
+                                    .prompt()?
+                                    .parse()?;
+                                (block_range_start, block_range_end, increment)
+                            } else {
+                                (0, 0, 0)
+                            };
+                            // 4.1. Random sampling of the range
+                            let random_sampling = if block_list.is_empty()
+                                && timestamp_range.is_none()
+                            {
+                                let sample_size = inquire::Text::new("Random sample size")
+                                    .with_help_message("How many blocks to sample pseudo-randomly from the range? (Enter to sample every block)")
+                                    .with_default("")
+                                    .prompt()?;
+                                if sample_size.is_empty() {
+                                    None
+                                } else {
+                                    let seed: B256 = inquire::Text::new("Random sampling seed")
+                                        .with_help_message("e.g. a future block hash or a nonce")
+                                        .prompt()?
+                                        .parse()?;
+                                    let seed_block = inquire::Text::new("Seed block")
+                                        .with_help_message("Block whose hash is the seed, to prove the seed (Enter to skip)")
+                                        .with_default("")
+                                        .prompt()?;
+                                    let seed_block = if seed_block.is_empty() {
+                                        None
+                                    } else {
+                                        Some(seed_block.parse()?)
+                                    };
+                                    Some(RandomSampling::new(
+                                        seed,
+                                        sample_size.parse()?,
+                                        seed_block,
+                                    ))
+                                }
+                            } else {
+                                None
+                            };
+                            // 5. Sampled Property
+                            // 5.1. Block Sampled Collection Type
                             let variants: Vec<String> = BlockSampledCollectionType::variants();
                             let collection_opts: Vec<&str> =
                                 variants.iter().map(AsRef::as_ref).collect();
@@ -102,7 +193,8 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                             .prompt()?;
                             let collection_type =
                                 BlockSampledCollectionType::from_str(collection_ans)?;
-                            // 4.2. Detail Sampled Property
+                            // 5.2. Detail Sampled Property
+                            let mut storage_layout: Option<StorageLayout> = None;
                             let sampled_property = match collection_type {
                                 BlockSampledCollectionType::Header => {
                                     let variants: Vec<String> = HeaderField::variants();
@@ -130,50 +222,156 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                                     let address = inquire::Text::new("Enter target address")
                                         .with_help_message("Enter target address")
                                         .prompt()?;
-                                    let storage_key =
-                                        inquire::Text::new("Enter target storage key")
-                                            .with_help_message("Enter the storage key")
+                                    let layout_path =
+                                        inquire::Text::new("Enter storage layout file path")
+                                            .with_help_message("Output of `solc --storage-layout` to refer to storage variables by name (Enter to skip)")
+                                            .with_default("")
+                                            .prompt()?;
+                                    let storage_key = if layout_path.is_empty() {
+                                        let storage_key =
+                                            inquire::Text::new("Enter target storage key")
+                                                .with_help_message("Enter the storage key")
+                                                .prompt()?;
+                                        let slice = inquire::Text::new("Enter packed value slice")
+                                            .with_help_message("[offset:width] or [offset:width:signed] in bytes, if the value is packed in the slot (Enter to skip)")
+                                            .with_default("")
                                             .prompt()?;
+                                        format!("{}{}", storage_key, slice)
+                                    } else {
+                                        storage_layout =
+                                            Some(std::fs::read_to_string(layout_path)?.parse()?);
+                                        inquire::Text::new("Enter target storage variable")
+                                            .with_help_message(
+                                                "e.g. totalSupply, _balances[0xholder]",
+                                            )
+                                            .prompt()?
+                                    };
                                     format!("storage.{}.{}", address, storage_key)
                                 }
+                                BlockSampledCollectionType::Code => {
+                                    let address = inquire::Text::new("Enter target address")
+                                        .with_help_message("Enter target address")
+                                        .prompt()?;
+                                    let variants: Vec<String> = CodeField::variants();
+                                    let code_opts: Vec<&str> =
+                                        variants.iter().map(AsRef::as_ref).collect();
+                                    let code_ans: &str =
+                                        Select::new("Select detail code property", code_opts)
+                                        .with_help_message("What code property do you want to sample? (code is checked against the proven code hash)")
+                                            .prompt()?;
+                                    let code_field = if code_ans == "SIZE" {
+                                        code_ans.to_string()
+                                    } else {
+                                        let offset: u32 = inquire::Text::new("Enter byte offset")
+                                            .with_help_message(
+                                                "Byte offset of the 32 bytes word in the code",
+                                            )
+                                            .prompt()?
+                                            .parse()?;
+                                        format!("WORD[{}]", offset)
+                                    };
+                                    format!("code.{}.{}", address, code_field)
+                                }
                             };
-                            let block_sampled_datalake = BlockSampledDatalake::new(
-                                ChainId::from_str(&chain_id)?,
-                                block_range_start,
-                                block_range_end,
-                                increment,
-                                BlockSampledCollection::from_str(&sampled_property)?,
-                            );
+                            let sampled_property = match &storage_layout {
+                                Some(layout) => BlockSampledCollection::from_str_with_layout(
+                                    &sampled_property,
+                                    layout,
+                                )?,
+                                None => BlockSampledCollection::from_str(&sampled_property)?,
+                            };
+                            let block_sampled_datalake =
+                                if let Some(timestamp_range) = timestamp_range {
+                                    BlockSampledDatalake::from_timestamp_range(
+                                        ChainId::from_str(&chain_id)?,
+                                        timestamp_range,
+                                        sampled_property,
+                                    )
+                                } else if let Some(random_sampling) = random_sampling {
+                                    BlockSampledDatalake::from_random_sampling(
+                                        ChainId::from_str(&chain_id)?,
+                                        block_range_start,
+                                        block_range_end,
+                                        increment,
+                                        random_sampling,
+                                        sampled_property,
+                                    )?
+                                } else if block_list.is_empty() {
+                                    BlockSampledDatalake::new(
+                                        ChainId::from_str(&chain_id)?,
+                                        block_range_start,
+                                        block_range_end,
+                                        increment,
+                                        sampled_property,
+                                    )
+                                } else {
+                                    BlockSampledDatalake::from_block_list(
+                                        ChainId::from_str(&chain_id)?,
+                                        block_list,
+                                        sampled_property,
+                                    )?
+                                };
                             DatalakeEnvelope::BlockSampled(block_sampled_datalake)
                         }
-                        DatalakeType::TransactionsInBlock => {
+                        DatalakeType::TransactionsInBlock | DatalakeType::TransactionsInRange => {
                             // 0. Chain ID
                             let chain_id: String = inquire::Text::new("Chain ID")
                                 .with_help_message("What is the chain ID? (Enter to set default)")
                                 .with_default("ETHEREUM_SEPOLIA")
                                 .prompt()?;
-                            let target_block: u64 = inquire::Text::new("Enter target block number")
-                                .with_help_message(
-                                    "What block you target to get transactions? (Enter to set default)",
-                                )
-                                .with_default("4952200")
-                                .prompt()?
-                                .parse()?;
-                            let start_index: u64 = inquire::Text::new("Start index")
+                            let (target_block, block_range_end, block_increment) = if datalake_type
+                                == DatalakeType::TransactionsInBlock
+                            {
+                                let target_block: u64 =
+                                        inquire::Text::new("Enter target block number")
+                                            .with_help_message(
+                                                "What block you target to get transactions? (Enter to set default)",
+                                            )
+                                            .with_default("4952200")
+                                            .prompt()?
+                                            .parse()?;
+                                (target_block, target_block, 1)
+                            } else {
+                                let block_range_start: u64 =
+                                    inquire::Text::new("Block range start")
+                                        .with_help_message(
+                                            "What is the block range start? (Enter to set default)",
+                                        )
+                                        .with_default("4952200")
+                                        .prompt()?
+                                        .parse()?;
+                                let block_range_end: u64 = inquire::Text::new("Block range end")
+                                    .with_help_message(
+                                        "What is the block range end? (Enter to set default)",
+                                    )
+                                    .with_default("4952210")
+                                    .prompt()?
+                                    .parse()?;
+                                let block_increment: u64 =
+                                        inquire::Text::new("Block increment")
+                                            .with_help_message(
+                                                "How many blocks to skip in the range? (Enter to set default)",
+                                            )
+                                            .with_default("1")
+                                            .prompt()?
+                                            .parse()?;
+                                (block_range_start, block_range_end, block_increment)
+                            };
+                            let start_index = inquire::Text::new("Start index")
                             .with_help_message(
-                                "What is the start index of transactions in the block? (Enter to set default)",
+                                "What is the start index of transactions in the block? Negative index is relative to the end of the block (Enter to set default)",
                             )
                             .with_default("0")
                             .prompt()?
                             .parse()?;
-                            // TODO: have end index dynamically fetch by block number
-                            let end_index: u64 = inquire::Text::new("End index")
+                            let end_index = inquire::Text::new("End index")
                             .with_help_message(
-                                "What is the end index of transactions in the block? (Enter to set default)",
+                                "What is the end index of transactions in the block? \"last\" is the end of the block (Enter to set default)",
                             )
                             .with_default("10")
                             .prompt()?
                             .parse()?;
+                            let index_range = TxIndexRange::new(start_index, end_index);
                             let increment: u64 = inquire::Text::new("Increment")
                                 .with_help_message(
                                     "How many transactions to skip in the range? (Enter to set default)",
@@ -190,6 +388,13 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                                 .split(',')
                                 .map(|s| s.parse().unwrap())
                                 .collect();
+                            let filter: TransactionsFilter = inquire::Text::new("Filter")
+                                .with_help_message(
+                                    "Filter on sender, to and function selector, e.g. to=0x...,selector=0xa9059cbb (Enter to skip)",
+                                )
+                                .with_default("")
+                                .prompt()?
+                                .parse()?;
                             let variants = TransactionsCollectionType::variants();
                             let collection_opts: Vec<&str> =
                                 variants.iter().map(AsRef::as_ref).collect();
@@ -222,17 +427,78 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                                             .prompt()?;
                                     format!("tx_receipt.{}", transaction_receipt_ans)
                                 }
+                                TransactionsCollectionType::TransactionFees => {
+                                    let variants = TransactionFeeField::variants();
+                                    let transaction_fee_opts: Vec<&str> =
+                                        variants.iter().map(AsRef::as_ref).collect();
+                                    let transaction_fee_ans: &str =
+                                        Select::new("Select detail transaction fee property", transaction_fee_opts)
+                                        .with_help_message("What transaction fee property do you want to sample? (derived from the transaction, its receipt and the block header)")
+                                            .prompt()?;
+                                    format!("tx_fee.{}", transaction_fee_ans)
+                                }
                             };
-                            let transactions_datalake = TransactionsInBlockDatalake::new(
+                            if datalake_type == DatalakeType::TransactionsInBlock {
+                                DatalakeEnvelope::TransactionsInBlock(
+                                    TransactionsInBlockDatalake::new(
+                                        ChainId::from_str(&chain_id)?,
+                                        target_block,
+                                        TransactionsCollection::from_str(&sampled_property)?,
+                                        0,
+                                        0,
+                                        increment,
+                                        IncludedTypes::from(&included_types),
+                                    )
+                                    .with_index_range(index_range)
+                                    .with_filter(filter),
+                                )
+                            } else {
+                                // end index is already bounded by the number of transactions of each block
+                                let (start_index, end_index) = index_range.absolute().ok_or_else(|| {
+                                    anyhow::anyhow!("Relative transaction indexes are only supported for a single block")
+                                })?;
+                                DatalakeEnvelope::TransactionsInRange(
+                                    TransactionsInRangeDatalake::new(
+                                        ChainId::from_str(&chain_id)?,
+                                        target_block,
+                                        block_range_end,
+                                        block_increment,
+                                        TransactionsCollection::from_str(&sampled_property)?,
+                                        start_index,
+                                        end_index,
+                                        increment,
+                                        IncludedTypes::from(&included_types),
+                                    )
+                                    .with_filter(filter),
+                                )
+                            }
+                        }
+                        DatalakeType::TransactionsByHash => {
+                            // 0. Chain ID
+                            let chain_id: String = inquire::Text::new("Chain ID")
+                                .with_help_message("What is the chain ID? (Enter to set default)")
+                                .with_default("ETHEREUM_SEPOLIA")
+                                .prompt()?;
+                            // 1. Transaction hashes
+                            let tx_hashes: Vec<TxHash> = inquire::Text::new("Transaction hashes")
+                                .with_help_message("Comma separated hashes of the transactions")
+                                .prompt()?
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|tx_hash| !tx_hash.is_empty())
+                                .map(str::parse)
+                                .collect::<Result<_, _>>()?;
+                            // 2. Sampled property
+                            let sampled_property = inquire::Text::new("Sampled property")
+                                .with_help_message(
+                                    "e.g. tx.value, tx_receipt.success, tx_fee.effective_gas_price",
+                                )
+                                .prompt()?;
+                            DatalakeEnvelope::TransactionsByHash(TransactionsByHashDatalake::new(
                                 ChainId::from_str(&chain_id)?,
-                                target_block,
+                                tx_hashes,
                                 TransactionsCollection::from_str(&sampled_property)?,
-                                start_index,
-                                end_index,
-                                increment,
-                                IncludedTypes::from(&included_types),
-                            );
-                            DatalakeEnvelope::TransactionsInBlock(transactions_datalake)
+                            )?)
                         }
                     }
                 }
@@ -242,7 +508,26 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                 }
             };
 
-            let task_opts: Vec<&str> = vec!["AVG", "SUM", "MIN", "MAX", "COUNT"];
+            let task_opts: Vec<&str> = vec![
+                "AVG",
+                "SUM",
+                "MIN",
+                "MAX",
+                "COUNT",
+                "MEDIAN",
+                "PERCENTILE",
+                "VARIANCE",
+                "STDDEV",
+                "BLOOM",
+                "MERKLE",
+                "TWAP",
+                "FIRST",
+                "LAST",
+                "NET_CHANGE",
+                "MAX_DRAWDOWN",
+                "COUNT_DISTINCT",
+                "HISTOGRAM",
+            ];
 
             let aggregate_fn_id = Select::new("Select the aggregation function", task_opts)
                 .with_help_message(
@@ -267,14 +552,81 @@ pub async fn run_interactive() -> anyhow::Result<()> {
                         U256::from_str(&value_to_compare)?,
                     ))
                 }
+                "PERCENTILE" => {
+                    let percentile: u8 = inquire::Text::new("Enter the percentile")
+                        .with_help_message("Between 0 and 100, e.g. 95")
+                        .prompt()?
+                        .parse()?;
+                    Some(FunctionContext::default().with_percentile(percentile))
+                }
+                "NET_CHANGE" | "MAX_DRAWDOWN" => {
+                    let signed = inquire::Confirm::new("Are the values signed?")
+                        .with_help_message("Two's complement int256 values, e.g. int256 storage")
+                        .with_default(false)
+                        .prompt()?;
+                    signed.then(|| FunctionContext::default().with_signed())
+                }
+                "AVG" => {
+                    let decimals: u8 = inquire::Text::new("Enter the decimals of the average")
+                        .with_help_message(
+                            "The average is a fixed-point number, e.g. 4 for 7142 from 5/7",
+                        )
+                        .with_default("0")
+                        .prompt()?
+                        .parse()?;
+                    let rounding: Rounding = Select::new(
+                        "Select the rounding of the average",
+                        vec!["half_up", "floor", "ceil"],
+                    )
+                    .prompt()?
+                    .parse()?;
+                    Some(FunctionContext::default().with_fixed_point(decimals, rounding))
+                }
+                "HISTOGRAM" => {
+                    let boundaries: String =
+                        inquire::Text::new("Enter the boundaries of the buckets")
+                            .with_help_message(
+                                "Strictly ascending and comma separated, e.g. 10,100 for [0, 10), [10, 100) and [100, 2^256)",
+                            )
+                            .prompt()?;
+                    Some(FunctionContext::from_str(&format!(
+                        "buckets({})",
+                        boundaries
+                    ))?)
+                }
+                "BLOOM" => {
+                    let bloom_bits: u16 = inquire::Text::new("Enter the bloom filter size in bits")
+                        .with_help_message("Power of two up to 2048, the Ethereum logs bloom size")
+                        .with_default("2048")
+                        .prompt()?
+                        .parse()?;
+                    Some(FunctionContext::default().with_bloom_bits(bloom_bits))
+                }
                 _ => None,
             };
 
+            let filter: String = inquire::Text::new("Enter the filter of the values")
+                .with_help_message(
+                    "e.g. and(gt.1000000000,lt.100000000000). (Enter to aggregate all values)",
+                )
+                .with_default("")
+                .prompt()?;
+            let aggregate_fn_ctx = match filter.trim() {
+                "" => aggregate_fn_ctx,
+                filter => Some(
+                    aggregate_fn_ctx
+                        .unwrap_or_default()
+                        .with_filter(filter.parse()?),
+                ),
+            };
+
             let target_datalake_compute = DatalakeCompute::new(
                 datalake_envelope,
                 Computation::new(aggregate_fn_id.parse()?, aggregate_fn_ctx),
             );
-            vec![TaskEnvelope::DatalakeCompute(target_datalake_compute)]
+            vec![TaskEnvelope::DatalakeCompute(Box::new(
+                target_datalake_compute,
+            ))]
         }
         "MODULE" => {
             println!("Let's try to generate module! ");
@@ -4,6 +4,8 @@ use std::{collections::HashMap, path::PathBuf};
 use crate::constant::DEFAULT_DRY_CAIRO_RUN_CAIRO_FILE;
 use crate::{primitives::ChainId, provider::config::ProviderConfig};
 
+use super::CompileError;
+
 pub struct CompilerConfig {
     // dry-run program path
     pub dry_run_program_path: PathBuf,
@@ -20,6 +22,13 @@ impl CompilerConfig {
             save_fetch_keys_file: self.save_fetch_keys_file,
         }
     }
+
+    /// Provider config of the chain, which the tasks of the chain are fetched with
+    pub fn provider_config_of(&self, chain_id: &ChainId) -> Result<&ProviderConfig, CompileError> {
+        self.provider_config
+            .get(chain_id)
+            .ok_or(CompileError::ChainNotConfigured(*chain_id))
+    }
 }
 
 // Default config for the compiler only for testing
@@ -1,54 +1,135 @@
 use crate::{
-    primitives::task::datalake::DatalakeCompute, provider::traits::new_provider_from_config,
+    primitives::aggregate_fn::integer::OverflowError,
+    primitives::solidity_types::traits::DatalakeComputeCodecs,
+    primitives::task::datalake::{
+        block_sampled::MultiBlockSampledDatalake, envelope::DatalakeEnvelope, DatalakeCompute,
+    },
+    provider::{traits::new_provider_from_config, types::FetchedDatalake},
 };
 use tracing::{debug, info};
 
 use super::{config::CompilerConfig, Compilable, CompilationResult, CompileError};
 
+impl DatalakeCompute {
+    /// Aggregate the fetched values of the datalake
+    fn aggregate(&self, fetched: FetchedDatalake) -> Result<CompilationResult, CompileError> {
+        debug!("values to aggregate : {:#?}", fetched.values);
+
+        // ========== compute ==============
+        let aggregation_fn = &self.compute.aggregate_fn_id;
+        let fn_context = &self.compute.aggregate_fn_ctx;
+        // overflows are reported with the task, other errors as they are
+        let task_error = |err: anyhow::Error| match err.downcast::<OverflowError>() {
+            Ok(source) => CompileError::AggregationOverflow {
+                task: self.commit(),
+                source,
+            },
+            Err(err) => CompileError::GeneralError(err),
+        };
+        let (aggregated_result, aggregation_output) = aggregation_fn
+            .operation_with_output(
+                &fetched.values,
+                &fetched.timestamps,
+                self.datalake.value_bytes(),
+                Some(fn_context.clone()),
+            )
+            .map_err(task_error)?;
+
+        let compiled = CompilationResult::new(
+            vec![aggregated_result],
+            fetched.headers,
+            fetched.accounts,
+            fetched.storages,
+            fetched.transactions,
+            fetched.transaction_receipts,
+            fetched.bytecodes,
+            fetched.mmr_metas,
+        )
+        .with_transaction_counts(fetched.transaction_counts);
+        Ok(match aggregation_output {
+            Some(output) => compiled.with_aggregation_output(self.commit(), output),
+            None => compiled,
+        })
+    }
+}
+
 impl Compilable for DatalakeCompute {
     async fn compile(
         &self,
         compile_config: &CompilerConfig,
     ) -> Result<CompilationResult, CompileError> {
         info!("target task: {:#?}", self);
+        self.validate()?;
         // ========== datalake ==============
-        let target_provider_config = compile_config
-            .provider_config
-            .get(&self.datalake.get_chain_id())
-            .expect("target task's chain had not been configured.");
+        let target_provider_config =
+            compile_config.provider_config_of(&self.datalake.get_chain_id())?;
         let provider = new_provider_from_config(target_provider_config);
         let compiled_block_sampled = provider.fetch_proofs(self).await?;
-        debug!("values to aggregate : {:#?}", compiled_block_sampled.values);
-
-        // ========== compute ==============
-        let aggregation_fn = &self.compute.aggregate_fn_id;
-        let fn_context = &self.compute.aggregate_fn_ctx;
-        let aggregated_result =
-            aggregation_fn.operation(&compiled_block_sampled.values, Some(fn_context.clone()))?;
-
-        Ok(CompilationResult::new(
-            vec![aggregated_result],
-            compiled_block_sampled.headers,
-            compiled_block_sampled.accounts,
-            compiled_block_sampled.storages,
-            compiled_block_sampled.transactions,
-            compiled_block_sampled.transaction_receipts,
-            compiled_block_sampled.mmr_metas,
-        ))
+        self.aggregate(compiled_block_sampled)
     }
 }
 
 pub type DatalakeComputeVec = Vec<DatalakeCompute>;
 
+/// Indexes of the tasks to fetch together, in order of first task.
+/// Block sampled tasks over the same blocks are grouped, so their shared proofs are fetched once.
+fn group_by_blocks(tasks: &[DatalakeCompute]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![];
+    for (index, task) in tasks.iter().enumerate() {
+        let group = match &task.datalake {
+            DatalakeEnvelope::BlockSampled(datalake) => groups.iter_mut().find(|group| {
+                matches!(
+                    &tasks[group[0]].datalake,
+                    DatalakeEnvelope::BlockSampled(other) if other.has_same_blocks(datalake)
+                )
+            }),
+            _ => None,
+        };
+        match group {
+            Some(group) => group.push(index),
+            None => groups.push(vec![index]),
+        }
+    }
+    groups
+}
+
 impl Compilable for DatalakeComputeVec {
     async fn compile(
         &self,
         compile_config: &CompilerConfig,
     ) -> Result<CompilationResult, CompileError> {
-        let mut final_results = CompilationResult::default();
+        for task in self {
+            task.validate()?;
+        }
+        let mut compiled: Vec<Option<CompilationResult>> = self.iter().map(|_| None).collect();
+
+        for group in group_by_blocks(self) {
+            if group.len() == 1 {
+                compiled[group[0]] = Some(self[group[0]].compile(compile_config).await?);
+                continue;
+            }
+
+            let datalakes = group
+                .iter()
+                .filter_map(|index| match &self[*index].datalake {
+                    DatalakeEnvelope::BlockSampled(datalake) => Some(datalake.clone()),
+                    _ => None,
+                })
+                .collect();
+            let datalake = MultiBlockSampledDatalake::from_datalakes(datalakes)?;
+            info!("target tasks over the same blocks: {:#?}", group);
+            let target_provider_config =
+                compile_config.provider_config_of(&datalake.datalakes()[0].chain_id)?;
+            let provider = new_provider_from_config(target_provider_config);
+            let fetched = provider.fetch_multi_proofs(&datalake).await?;
+            for (index, fetched) in group.into_iter().zip(fetched) {
+                compiled[index] = Some(self[index].aggregate(fetched)?);
+            }
+        }
 
-        for datalake_compute in self {
-            let current_results = datalake_compute.compile(compile_config).await?;
+        // results are extended in order of the tasks
+        let mut final_results = CompilationResult::default();
+        for current_results in compiled.into_iter().flatten() {
             final_results.extend(current_results);
         }
 
@@ -89,6 +170,79 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_group_by_blocks() {
+        let blocks = BlockSampledDatalake::new(
+            ChainId::EthereumSepolia,
+            6127485,
+            6127490,
+            1,
+            BlockSampledCollection::Header(HeaderField::GasUsed),
+        );
+        let task = |datalake: BlockSampledDatalake| DatalakeCompute {
+            compute: Computation::new(AggregationFunction::AVG, None),
+            datalake: DatalakeEnvelope::BlockSampled(datalake),
+        };
+        let tasks = vec![
+            task(blocks.clone()),
+            task(BlockSampledDatalake {
+                block_range_end: 6127495,
+                ..blocks.clone()
+            }),
+            task(BlockSampledDatalake {
+                sampled_property: BlockSampledCollection::Account(
+                    address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4"),
+                    AccountField::Balance,
+                ),
+                ..blocks.clone()
+            }),
+            task(BlockSampledDatalake {
+                sampled_property: BlockSampledCollection::Header(HeaderField::BaseFeePerGas),
+                ..blocks
+            }),
+        ];
+        assert_eq!(group_by_blocks(&tasks), vec![vec![0, 2, 3], vec![1]]);
+    }
+
+    #[test]
+    fn test_aggregate_overflow() {
+        let task = DatalakeCompute {
+            compute: Computation::new(AggregationFunction::SUM, None),
+            datalake: DatalakeEnvelope::BlockSampled(BlockSampledDatalake::new(
+                ChainId::EthereumSepolia,
+                6127485,
+                6127487,
+                1,
+                BlockSampledCollection::Header(HeaderField::GasUsed),
+            )),
+        };
+        let fetched = || FetchedDatalake {
+            values: vec![U256::from(1), U256::MAX, U256::from(2)],
+            ..Default::default()
+        };
+        match task.aggregate(fetched()) {
+            Err(CompileError::AggregationOverflow {
+                task: overflowed,
+                source: OverflowError::Accumulation { index, .. },
+            }) => {
+                assert_eq!(overflowed, task.commit());
+                assert_eq!(index, 1);
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the average of the same values is accumulated in u512
+        let task = DatalakeCompute {
+            compute: Computation::new(AggregationFunction::AVG, None),
+            ..task
+        };
+        let compiled = task.aggregate(fetched()).unwrap();
+        assert_eq!(
+            compiled.task_results,
+            vec![U256::MAX / U256::from(3) + U256::from(1)]
+        );
+    }
+
     #[tokio::test]
     async fn test_compile_block_sampled_datalake_compute_vec() {
         initialize();
@@ -103,6 +257,9 @@ mod tests {
                     block_range_end: 10005,
                     increment: 1,
                     sampled_property: BlockSampledCollection::Header(HeaderField::Number),
+                    block_list: vec![],
+                    random_sampling: None,
+                    timestamp_range: None,
                 }),
             },
             DatalakeCompute {
@@ -116,6 +273,9 @@ mod tests {
                         address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4"),
                         AccountField::Balance,
                     ),
+                    block_list: vec![],
+                    random_sampling: None,
+                    timestamp_range: None,
                 }),
             },
             DatalakeCompute {
@@ -128,7 +288,11 @@ mod tests {
                     sampled_property: BlockSampledCollection::Storage(
                         address!("75CeC1db9dCeb703200EAa6595f66885C962B920"),
                         B256::from(U256::from(1)),
+                        None,
                     ),
+                    block_list: vec![],
+                    random_sampling: None,
+                    timestamp_range: None,
                 }),
             },
         ];
@@ -168,6 +332,8 @@ mod tests {
                     sampled_property: TransactionsCollection::Transactions(
                         TransactionField::GasLimit,
                     ),
+                    filter: Default::default(),
+                    index_range: None,
                 }),
             },
             DatalakeCompute {
@@ -182,6 +348,8 @@ mod tests {
                     sampled_property: TransactionsCollection::TranasactionReceipts(
                         TransactionReceiptField::Success,
                     ),
+                    filter: Default::default(),
+                    index_range: None,
                 }),
             },
         ];
@@ -1,14 +1,17 @@
-use alloy::primitives::U256;
+use alloy::primitives::{B256, U256};
 
 use config::CompilerConfig;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
+use crate::primitives::aggregate_fn::{integer::OverflowError, output::AggregationOutput};
 use crate::primitives::processed_types::{
-    account::ProcessedAccount, header::ProcessedHeader, mmr::MMRMeta, receipt::ProcessedReceipt,
-    storage::ProcessedStorage, transaction::ProcessedTransaction,
+    account::ProcessedAccount, bytecode::ProcessedBytecode, header::ProcessedHeader, mmr::MMRMeta,
+    receipt::ProcessedReceipt, storage::ProcessedStorage, transaction::ProcessedTransaction,
+    transaction_count::ProcessedTransactionCount,
 };
+use crate::primitives::ChainId;
 
 use crate::provider::error::ProviderError;
 use crate::{cairo_runner, preprocessor::module_registry::ModuleRegistryError};
@@ -40,6 +43,18 @@ pub enum CompileError {
 
     #[error("Compilation failed")]
     CompilationFailed,
+
+    #[error("Chain {0} has not been configured")]
+    ChainNotConfigured(ChainId),
+
+    #[error("Multiple modules are not supported yet")]
+    MultipleModules,
+
+    #[error("Multiple chain ids are not supported yet")]
+    MultipleChains,
+
+    #[error("Aggregation of task {task} failed: {source}")]
+    AggregationOverflow { task: B256, source: OverflowError },
 }
 
 /// Compile vector of tasks into compilation results
@@ -64,11 +79,18 @@ pub struct CompilationResult {
     pub transactions: HashSet<ProcessedTransaction>,
     /// Transaction receipts related to the datalake
     pub transaction_receipts: HashSet<ProcessedReceipt>,
+    /// Bytecodes related to the datalake
+    pub bytecodes: HashSet<ProcessedBytecode>,
+    /// Transaction counts related to the datalake
+    pub transaction_counts: HashSet<ProcessedTransactionCount>,
     /// MMR meta data related to the headers
     pub mmr_metas: HashSet<MMRMeta>,
+    /// Outputs committed by the results of tasks, e.g. bloom filters, by task commitment
+    pub aggregation_outputs: HashMap<B256, AggregationOutput>,
 }
 
 impl CompilationResult {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         task_results: Vec<U256>,
         headers: HashSet<ProcessedHeader>,
@@ -76,6 +98,7 @@ impl CompilationResult {
         storages: HashSet<ProcessedStorage>,
         transactions: HashSet<ProcessedTransaction>,
         transaction_receipts: HashSet<ProcessedReceipt>,
+        bytecodes: HashSet<ProcessedBytecode>,
         mmr_metas: HashSet<MMRMeta>,
     ) -> Self {
         Self {
@@ -85,10 +108,31 @@ impl CompilationResult {
             storages,
             transactions,
             transaction_receipts,
+            bytecodes,
             mmr_metas,
+            ..Default::default()
         }
     }
 
+    /// Set the proven transaction counts of the compilation results
+    pub fn with_transaction_counts(
+        mut self,
+        transaction_counts: HashSet<ProcessedTransactionCount>,
+    ) -> Self {
+        self.transaction_counts = transaction_counts;
+        self
+    }
+
+    /// Set the output committed by the result of the task
+    pub fn with_aggregation_output(
+        mut self,
+        task_commitment: B256,
+        output: AggregationOutput,
+    ) -> Self {
+        self.aggregation_outputs.insert(task_commitment, output);
+        self
+    }
+
     /// Extend the current compilation results with another compilation results
     pub fn extend(&mut self, other: CompilationResult) {
         self.headers.extend(other.headers);
@@ -96,7 +140,10 @@ impl CompilationResult {
         self.storages.extend(other.storages);
         self.transactions.extend(other.transactions);
         self.transaction_receipts.extend(other.transaction_receipts);
+        self.bytecodes.extend(other.bytecodes);
+        self.transaction_counts.extend(other.transaction_counts);
         self.task_results.extend(other.task_results);
         self.mmr_metas.extend(other.mmr_metas);
+        self.aggregation_outputs.extend(other.aggregation_outputs);
     }
 }
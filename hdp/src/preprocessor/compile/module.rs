@@ -8,7 +8,6 @@ use crate::primitives::processed_types::cairo_format;
 use crate::primitives::task::ExtendedModule;
 use crate::provider::key::categorize_fetch_keys;
 use crate::provider::traits::new_provider_from_config;
-use core::panic;
 
 use std::collections::HashSet;
 use std::path::PathBuf;
@@ -28,8 +27,7 @@ impl Compilable for ModuleVec {
         let dry_run_program_path = compile_config.dry_run_program_path.clone();
 
         let input = generate_input(self.to_vec(), PathBuf::from(DRY_CAIRO_RUN_OUTPUT_FILE)).await?;
-        let input_string =
-            serde_json::to_string_pretty(&input).expect("Failed to serialize module class");
+        let input_string = serde_json::to_string_pretty(&input).map_err(anyhow::Error::from)?;
 
         // 2. run the dry run and get the fetch points
         info!("2. Running dry-run... ");
@@ -44,7 +42,7 @@ impl Compilable for ModuleVec {
         }
 
         if keys.len() != 1 {
-            panic!("Multiple Modules are not supported");
+            return Err(CompileError::MultipleModules);
         }
 
         let dry_runned_module = keys.into_iter().next().unwrap();
@@ -54,7 +52,7 @@ impl Compilable for ModuleVec {
         let keys_maps_chain = categorize_fetch_keys(dry_runned_module.fetch_keys);
         if keys_maps_chain.len() > 1 {
             // TODO: This is temporary solution. Need to handle multiple chain id in future
-            panic!("Multiple chain id is not supported yet");
+            return Err(CompileError::MultipleChains);
         }
 
         let mut headers = HashSet::new();
@@ -62,15 +60,13 @@ impl Compilable for ModuleVec {
         let mut storages = HashSet::new();
         let mut transactions = HashSet::new();
         let mut transaction_receipts = HashSet::new();
+        let mut bytecodes = HashSet::new();
         let mut mmr_metas = HashSet::new();
 
         info!("3. Fetching proofs from provider...");
         for (chain_id, keys) in keys_maps_chain {
             info!("target provider chain id: {}", chain_id);
-            let target_provider_config = compile_config
-                .provider_config
-                .get(&chain_id)
-                .expect("target task's chain had not been configured.");
+            let target_provider_config = compile_config.provider_config_of(&chain_id)?;
             let provider = new_provider_from_config(target_provider_config);
             let results = provider.fetch_proofs_from_keys(keys).await?;
 
@@ -80,6 +76,7 @@ impl Compilable for ModuleVec {
             storages.extend(results.storages.into_iter());
             transactions.extend(results.transactions.into_iter());
             transaction_receipts.extend(results.transaction_receipts.into_iter());
+            bytecodes.extend(results.bytecodes.into_iter());
             mmr_metas.extend(results.mmr_metas.into_iter());
         }
 
@@ -90,6 +87,7 @@ impl Compilable for ModuleVec {
             storages,
             transactions,
             transaction_receipts,
+            bytecodes,
             mmr_metas,
         );
         Ok(compiled_result)
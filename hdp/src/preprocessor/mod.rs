@@ -9,7 +9,10 @@ use crate::primitives::processed_types::module::ProcessedModule;
 use crate::primitives::processed_types::query::ProcessorInput;
 use crate::primitives::processed_types::task::ProcessedTask;
 use crate::primitives::solidity_types::traits::{DatalakeCodecs, DatalakeComputeCodecs};
+use crate::primitives::task::datalake::envelope::DatalakeEnvelope;
+use crate::primitives::task::datalake::DatalakeCompute;
 use crate::primitives::task::TaskEnvelope;
+use crate::provider::evm::provider::EvmProvider;
 use alloy::dyn_abi::DynSolValue;
 use alloy::primitives::{Bytes, B256};
 use compile::config::CompilerConfig;
@@ -48,6 +51,9 @@ impl PreProcessor {
         &self,
         tasks: Vec<TaskEnvelope>,
     ) -> Result<ProcessorInput, PreProcessorError> {
+        // 0. resolve the blocks of the tasks, as they are committed
+        let tasks = self.resolve_tasks(tasks).await?;
+
         // 1. compile the given tasks
         let compiled_results = tasks
             .compile(&self.compile_config)
@@ -89,6 +95,12 @@ impl PreProcessor {
                         Bytes::from(datalake_compute.datalake.encode()?),
                         datalake_type.into(),
                         property_type,
+                    )
+                    .with_aggregation_output(
+                        compiled_results
+                            .aggregation_outputs
+                            .get(&task_commitment)
+                            .cloned(),
                     );
 
                     let task = ProcessedTask::DatalakeCompute(datalake_compute);
@@ -130,6 +142,8 @@ impl PreProcessor {
             storages: Vec::from_iter(compiled_results.storages),
             transactions: Vec::from_iter(compiled_results.transactions),
             transaction_receipts: Vec::from_iter(compiled_results.transaction_receipts),
+            bytecodes: Vec::from_iter(compiled_results.bytecodes),
+            transaction_counts: Vec::from_iter(compiled_results.transaction_counts),
         };
         let processed_result = ProcessorInput::new(
             SOUND_CAIRO_RUN_OUTPUT_FILE.into(),
@@ -141,4 +155,71 @@ impl PreProcessor {
         info!("1️⃣  Preprocessor completed successfully");
         Ok(processed_result)
     }
+
+    /// Resolve the timestamp ranges of the block sampled datalakes to blocks,
+    /// the relative index ranges of the transactions datalakes to indexes,
+    /// and the hashes of the transactions by hash datalakes to positions
+    async fn resolve_tasks(
+        &self,
+        tasks: Vec<TaskEnvelope>,
+    ) -> Result<Vec<TaskEnvelope>, PreProcessorError> {
+        let mut resolved_tasks = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let task = match task {
+                TaskEnvelope::DatalakeCompute(task) => {
+                    TaskEnvelope::DatalakeCompute(Box::new(self.resolve_datalake(*task).await?))
+                }
+                task => task,
+            };
+            resolved_tasks.push(task);
+        }
+        Ok(resolved_tasks)
+    }
+
+    async fn resolve_datalake(
+        &self,
+        task: DatalakeCompute,
+    ) -> Result<DatalakeCompute, PreProcessorError> {
+        let DatalakeCompute { datalake, compute } = task;
+        let datalake = match datalake {
+            DatalakeEnvelope::BlockSampled(datalake) if !datalake.is_resolved() => {
+                let provider_config = self.compile_config.provider_config_of(&datalake.chain_id)?;
+                let resolved = EvmProvider::new(provider_config)
+                    .resolve_timestamp_range(&datalake)
+                    .await
+                    .map_err(CompileError::ProviderError)?;
+                info!(
+                    "resolved timestamp range of datalake: {}",
+                    serde_json::to_string(&resolved).map_err(anyhow::Error::from)?
+                );
+                DatalakeEnvelope::BlockSampled(resolved)
+            }
+            DatalakeEnvelope::TransactionsInBlock(datalake) if !datalake.is_resolved() => {
+                let provider_config = self.compile_config.provider_config_of(&datalake.chain_id)?;
+                let resolved = EvmProvider::new(provider_config)
+                    .resolve_tx_index_range(&datalake)
+                    .await
+                    .map_err(CompileError::ProviderError)?;
+                info!(
+                    "resolved transaction index range of datalake: {}",
+                    serde_json::to_string(&resolved).map_err(anyhow::Error::from)?
+                );
+                DatalakeEnvelope::TransactionsInBlock(resolved)
+            }
+            DatalakeEnvelope::TransactionsByHash(datalake) if !datalake.is_resolved() => {
+                let provider_config = self.compile_config.provider_config_of(&datalake.chain_id)?;
+                let resolved = EvmProvider::new(provider_config)
+                    .resolve_tx_hashes(&datalake)
+                    .await
+                    .map_err(CompileError::ProviderError)?;
+                info!(
+                    "resolved transaction positions of datalake: {}",
+                    serde_json::to_string(&resolved).map_err(anyhow::Error::from)?
+                );
+                DatalakeEnvelope::TransactionsByHash(resolved)
+            }
+            datalake => datalake,
+        };
+        Ok(DatalakeCompute::new(datalake, compute))
+    }
 }
@@ -1,25 +1,179 @@
 use std::str::FromStr;
 
-use alloy::primitives::U256;
+use alloy::primitives::{keccak256, Uint, I256, U256, U512};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::primitives::merkle_tree::build_values_merkle_tree;
 
 use super::FunctionContext;
 
+/// Overflow of an aggregation, which fails instead of wrapping around
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OverflowError {
+    /// Accumulating the value at the index, in the order of the values, overflowed
+    #[error("{operation} overflows at value {index} ({value})")]
+    Accumulation {
+        operation: &'static str,
+        index: usize,
+        value: U256,
+    },
+    /// The result does not fit in its type
+    #[error("{operation} result overflows {bound}")]
+    Result {
+        operation: &'static str,
+        bound: &'static str,
+    },
+}
+
 /// Returns the average of the values: [`AVG`](https://en.wikipedia.org/wiki/Average)
+///
+/// The values are summed in u512, so the average of any u256 values is exact.
+/// The result is rounded to the nearest integer, half up.
 pub fn average(values: &[U256]) -> Result<U256> {
+    fixed_point_average(values, 0, &Rounding::HalfUp)
+}
+
+/// Maximum number of decimals of a fixed-point result, as `10^77` is the largest power of 10 in u256
+pub const MAX_DECIMALS: u8 = 77;
+
+/// Returns the average of the values as a fixed-point number with the given decimals,
+/// i.e. `sum * 10^decimals / count`, rounded with the given rounding mode.
+/// Fails if the result does not fit in u256.
+pub fn fixed_point_average(values: &[U256], decimals: u8, rounding: &Rounding) -> Result<U256> {
     if values.is_empty() {
         bail!("No values found");
     }
+    if decimals > MAX_DECIMALS {
+        bail!(
+            "Decimals must be at most {}, got {}",
+            MAX_DECIMALS,
+            decimals
+        );
+    }
 
-    let sum = sum(values).unwrap();
+    let mut sum = U512::ZERO;
+    for (index, value) in values.iter().enumerate() {
+        sum = sum
+            .checked_add(U512::from(*value))
+            .ok_or(OverflowError::Accumulation {
+                operation: "Average",
+                index,
+                value: *value,
+            })?;
+    }
+    let overflow = OverflowError::Result {
+        operation: "Average",
+        bound: "u256",
+    };
+    // a scaled sum overflowing u512 is an average overflowing u256
+    let scaled = sum
+        .checked_mul(U512::from(10).pow(U512::from(decimals)))
+        .ok_or(overflow.clone())?;
+    let average = rounding.divide(scaled, U512::from(values.len()));
+    if average > U512::from(U256::MAX) {
+        return Err(overflow.into());
+    }
+    Ok(average.to::<U256>())
+}
+
+/// Returns the time weighted average of the values: [`TWAP`](https://en.wikipedia.org/wiki/Time-weighted_average_price)
+///
+/// Each value is weighted by the time to the next value, so the last value has no weight,
+/// and the sum is divided by the time from the first to the last value.
+/// The result is rounded to the nearest integer, half up, as [`average`].
+/// A single value is its own time weighted average.
+pub fn time_weighted_average(values: &[U256], timestamps: &[u64]) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+    if timestamps.len() != values.len() {
+        bail!(
+            "Expected {} timestamps, got {}",
+            values.len(),
+            timestamps.len()
+        );
+    }
+    if values.len() == 1 {
+        return Ok(values[0]);
+    }
+
+    let mut weighted_sum = U512::ZERO;
+    for (index, (value, window)) in values.iter().zip(timestamps.windows(2)).enumerate() {
+        if window[1] < window[0] {
+            bail!("Timestamps are not in ascending order");
+        }
+        weighted_sum = weighted_sum
+            .checked_add(U512::from(*value) * U512::from(window[1] - window[0]))
+            .ok_or(OverflowError::Accumulation {
+                operation: "Time weighted average",
+                index,
+                value: *value,
+            })?;
+    }
+    let duration = U512::from(timestamps[timestamps.len() - 1] - timestamps[0]);
+    if duration.is_zero() {
+        bail!("No time elapsed between the values");
+    }
+
+    // weighted average of u256 values fits in u256
+    Ok(((weighted_sum * U512::from(2) + duration) / (duration * U512::from(2))).to::<U256>())
+}
+
+/// Default size of a bloom filter in bits, the size of the Ethereum logs bloom
+pub const BLOOM_BITS: u16 = 2048;
+
+/// Returns the bloom filter of the values, with the Ethereum logs bloom construction.
+///
+/// Each value is hashed as its `value_bytes` low order big endian bytes, e.g. 20 bytes for addresses,
+/// so an address is hashed as in the logs bloom. For each of the first three pairs of bytes
+/// of the hash, the bit `pair mod bits` is set, bit `i` being in byte `bits / 8 - 1 - i / 8`.
+/// The size in bits must be a power of two between 8 and 2048.
+pub fn bloom_filter(values: &[U256], bits: u16, value_bytes: usize) -> Result<Vec<u8>> {
+    if !bits.is_power_of_two() || !(8..=BLOOM_BITS).contains(&bits) {
+        bail!("Invalid bloom filter size: {} bits", bits);
+    }
+    if !(1..=32).contains(&value_bytes) {
+        bail!("Invalid value size: {} bytes", value_bytes);
+    }
+
+    let bits = bits as usize;
+    let mut filter = vec![0u8; bits / 8];
+    for value in values {
+        let word = value.to_be_bytes::<32>();
+        let (high, low) = word.split_at(32 - value_bytes);
+        if high.iter().any(|byte| *byte != 0) {
+            bail!("Value {} does not fit in {} bytes", value, value_bytes);
+        }
+        let hash = keccak256(low);
+        for pair in hash[..6].chunks(2) {
+            let bit = (((pair[0] as usize) << 8) | pair[1] as usize) & (bits - 1);
+            filter[bits / 8 - 1 - bit / 8] |= 1 << (bit % 8);
+        }
+    }
+    Ok(filter)
+}
 
-    divide(sum, U256::from(values.len()))
+/// Returns the keccak256 hash of the bloom filter of the values: [`BLOOM`](https://en.wikipedia.org/wiki/Bloom_filter)
+///
+/// The filter itself is output along with the result, see [`bloom_filter`].
+pub fn bloom_filterize(values: &[U256], bits: u16, value_bytes: usize) -> Result<U256> {
+    let filter = bloom_filter(values, bits, value_bytes)?;
+    Ok(U256::from_be_bytes(keccak256(filter).0))
 }
 
-// TODO: Implement bloom_filterize
-pub fn bloom_filterize(_values: &[U256]) -> Result<U256> {
-    Ok(U256::from(0))
+/// Returns the root of the merkle tree of the values in order: [`MERKLE`](https://en.wikipedia.org/wiki/Merkle_tree)
+///
+/// The tree is a standard merkle tree of the leaves `keccak256(index ++ value)`,
+/// so each value can be proven at its position, see [`build_values_merkle_tree`].
+pub fn merkle_root(values: &[U256]) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+
+    let (tree, _) = build_values_merkle_tree(values);
+    Ok(U256::from_be_bytes(tree.root().0))
 }
 
 /// Find the maximum value: [`MAX`](https://en.wikipedia.org/wiki/Maxima_and_minima)
@@ -55,41 +209,215 @@ pub fn find_min(values: &[U256]) -> Result<U256> {
     Ok(min)
 }
 
-/// Standard deviation
-/// wip
-pub fn standard_deviation(values: &[U256]) -> Result<U256> {
+/// Order preserving key of a value, flipping the sign bit of signed values,
+/// so that signed values compare and subtract as unsigned ones.
+pub(super) fn order_key(value: &U256, signed: bool) -> U256 {
+    if signed {
+        value ^ (U256::from(1) << 255)
+    } else {
+        *value
+    }
+}
+
+/// Find the maximum value, with the values as two's complement int256
+pub fn find_signed_max(values: &[U256]) -> Result<U256> {
+    match values.iter().max_by_key(|value| order_key(value, true)) {
+        Some(max) => Ok(*max),
+        None => bail!("No values found"),
+    }
+}
+
+/// Find the minimum value, with the values as two's complement int256
+pub fn find_signed_min(values: &[U256]) -> Result<U256> {
+    match values.iter().min_by_key(|value| order_key(value, true)) {
+        Some(min) => Ok(*min),
+        None => bail!("No values found"),
+    }
+}
+
+/// Returns the first value, e.g. the value at the first sampled block
+pub fn first(values: &[U256]) -> Result<U256> {
+    match values.first() {
+        Some(first) => Ok(*first),
+        None => bail!("No values found"),
+    }
+}
+
+/// Returns the last value, e.g. the value at the last sampled block
+pub fn last(values: &[U256]) -> Result<U256> {
+    match values.last() {
+        Some(last) => Ok(*last),
+        None => bail!("No values found"),
+    }
+}
+
+/// Returns the change from the first to the last value, which is also the sum of the deltas
+/// between consecutive values.
+///
+/// The values are two's complement int256 if `signed`, and unsigned otherwise.
+/// The change is an int256, encoded as two's complement in the result.
+/// Fails if the change does not fit in int256.
+pub fn net_change(values: &[U256], signed: bool) -> Result<U256> {
+    let first = order_key(&first(values)?, signed);
+    let last = order_key(&last(values)?, signed);
+
+    let overflow = OverflowError::Result {
+        operation: "Net change",
+        bound: "int256",
+    };
+    let change = if last >= first {
+        let increase = last - first;
+        if increase > I256::MAX.into_raw() {
+            return Err(overflow.into());
+        }
+        I256::from_raw(increase)
+    } else {
+        let decrease = first - last;
+        if decrease > I256::MIN.into_raw() {
+            return Err(overflow.into());
+        }
+        I256::from_raw(decrease.wrapping_neg())
+    };
+    Ok(change.into_raw())
+}
+
+/// Returns the largest decline from a value to any later value: [`MAX_DRAWDOWN`](https://en.wikipedia.org/wiki/Drawdown_(economics))
+///
+/// The values are two's complement int256 if `signed`, and unsigned otherwise.
+/// The drawdown is never negative, and is 0 if the values never decline.
+pub fn max_drawdown(values: &[U256], signed: bool) -> Result<U256> {
     if values.is_empty() {
         bail!("No values found");
     }
 
-    let mut sum = U256::from(0);
-    let count = U256::from(values.len());
+    let mut peak = order_key(&values[0], signed);
+    let mut drawdown = U256::ZERO;
+    for value in values {
+        let value = order_key(value, signed);
+        if value > peak {
+            peak = value;
+        } else if peak - value > drawdown {
+            drawdown = peak - value;
+        }
+    }
+    Ok(drawdown)
+}
+
+/// Unsigned integer wide enough for the exact variance numerator of any u256 values
+type U768 = Uint<768, 12>;
 
+/// Exact numerator of the population variance, `n * sum(x^2) - sum(x)^2`, which is `n^2` times the variance
+fn variance_numerator(values: &[U256]) -> U768 {
+    let mut sum = U768::ZERO;
+    let mut sum_of_squares = U768::ZERO;
     for value in values {
+        let value = U768::from(*value);
         sum += value;
+        sum_of_squares += value * value;
     }
+    U768::from(values.len()) * sum_of_squares - sum * sum
+}
 
-    let avg = divide(sum, count)
-        .expect("Division have failed")
-        .to_string()
-        .parse::<f64>()
-        .unwrap();
+/// Floor of the square root, with Newton's method
+fn isqrt(value: U768) -> U768 {
+    if value < U768::from(2) {
+        return value;
+    }
+    // initial guess is a power of two greater than or equal to the root
+    let mut root = U768::from(1) << value.bit_len().div_ceil(2);
+    loop {
+        let next = (root + value / root) >> 1;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
 
-    let mut variance_sum = 0.0;
-    for value in values {
-        let value = value.to_string().parse::<f64>().unwrap();
-        variance_sum += (value - avg).powi(2);
+/// Returns the population variance of the values: [`VARIANCE`](https://en.wikipedia.org/wiki/Variance)
+///
+/// With `N = n * sum(x^2) - sum(x)^2`, computed exactly, the variance is `N / n^2`
+/// rounded to the nearest integer, half up: `floor((2N + n^2) / (2n^2))`.
+/// Fails if the variance does not fit in u256.
+pub fn variance(values: &[U256]) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+
+    let n = U768::from(values.len());
+    let numerator = variance_numerator(values);
+    let variance = (numerator * U768::from(2) + n * n) / (n * n * U768::from(2));
+    if variance > U768::from(U256::MAX) {
+        return Err(OverflowError::Result {
+            operation: "Variance",
+            bound: "u256",
+        }
+        .into());
+    }
+    Ok(variance.to::<U256>())
+}
+
+/// Returns the population standard deviation of the values: [`STDDEV`](https://en.wikipedia.org/wiki/Standard_deviation)
+///
+/// With `N = n * sum(x^2) - sum(x)^2`, computed exactly, the standard deviation is `sqrt(N) / n`
+/// rounded to the nearest integer, half up: `floor((isqrt(4N) + n) / (2n))`,
+/// where `isqrt` is the floor of the square root.
+pub fn standard_deviation(values: &[U256]) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
     }
 
-    let variance: f64 = divide(U256::from(variance_sum), U256::from(count))
-        .expect("Division have failed")
-        .to_string()
-        .parse::<f64>()
-        .unwrap();
-    Ok(U256::from(roundup(variance.sqrt().to_string())))
+    let n = U768::from(values.len());
+    let numerator = variance_numerator(values);
+    let standard_deviation: U768 = (isqrt(numerator << 2) + n) / (n << 1);
+    // at most half of the range of the values, so it always fits
+    Ok(standard_deviation.to::<U256>())
+}
+
+/// Returns the median of the values: [`MEDIAN`](https://en.wikipedia.org/wiki/Median)
+///
+/// Values are sorted in ascending order. For an odd number of values, the median is the middle value.
+/// For an even number of values, it is the average of the two middle values rounded down,
+/// computed as `low + (high - low) / 2` so it cannot overflow.
+pub fn median(values: &[U256]) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort();
+
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        return Ok(sorted[middle]);
+    }
+    let (low, high) = (sorted[middle - 1], sorted[middle]);
+    Ok(low + (high - low) / U256::from(2))
+}
+
+/// Returns the p-th percentile of the values, with the [nearest-rank method](https://en.wikipedia.org/wiki/Percentile#The_nearest-rank_method)
+///
+/// Values are sorted in ascending order, and the value at the 1-based rank `ceil(p * n / 100)` is returned.
+/// The rank is at least 1, so the 0th percentile is the minimum and the 100th is the maximum.
+/// The result is always one of the values, no interpolation is done.
+pub fn percentile(values: &[U256], percentile: u8) -> Result<U256> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+    if percentile > 100 {
+        bail!("Percentile must be between 0 and 100, got {}", percentile);
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort();
+
+    let rank = (percentile as usize * sorted.len()).div_ceil(100).max(1);
+    Ok(sorted[rank - 1])
 }
 
 /// Sum of values: [`SUM`](https://en.wikipedia.org/wiki/Summation)
+///
+/// Fails with the index of the value at which the sum overflows u256.
 pub fn sum(values: &[U256]) -> Result<U256> {
     if values.is_empty() {
         bail!("No values found");
@@ -97,8 +425,12 @@ pub fn sum(values: &[U256]) -> Result<U256> {
 
     let mut sum = U256::from(0);
 
-    for value in values {
-        sum += value;
+    for (index, value) in values.iter().enumerate() {
+        sum = sum.checked_add(*value).ok_or(OverflowError::Accumulation {
+            operation: "Sum",
+            index,
+            value: *value,
+        })?;
     }
 
     Ok(sum)
@@ -117,13 +449,16 @@ pub fn sum(values: &[U256]) -> Result<U256> {
 /// - 03: Greater than or equal (>=)
 /// - 04: Less than (<)
 /// - 05: Less than or equal (<=)
+///
+/// With `signed` in the context, the values and the value to compare are two's complement int256.
 pub fn count(values: &[U256], ctx: &FunctionContext) -> Result<U256> {
     let logical_operator = &ctx.operator;
-    let value_to_compare = ctx.value_to_compare;
+    let value_to_compare = order_key(&ctx.value_to_compare, ctx.signed);
 
     let mut condition_satisfiability_count = 0;
 
     for value in values {
+        let value = &order_key(value, ctx.signed);
         match logical_operator {
             Operator::Equal => {
                 if value == &value_to_compare {
@@ -256,33 +591,153 @@ impl Operator {
     }
 }
 
-// Handle division properly using U256 type
-fn divide(a: U256, b: U256) -> Result<U256> {
-    if b.is_zero() {
-        bail!("Division by zero error");
+/// Count number of distinct values, 0 if there are no values
+pub fn count_distinct(values: &[U256]) -> Result<U256> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    Ok(U256::from(sorted.len()))
+}
+
+/// Maximum number of boundaries of HISTOGRAM
+pub const MAX_HISTOGRAM_BOUNDARIES: usize = 255;
+
+/// Returns the number of values in each bucket delimited by the boundaries: [`HISTOGRAM`](https://en.wikipedia.org/wiki/Histogram)
+///
+/// The boundaries `b_1 < .. < b_k` delimit `k + 1` buckets `[0, b_1), [b_1, b_2), .., [b_k, 2^256)`,
+/// so a value is counted in the bucket `i` where `i` is the number of boundaries less than or equal to it.
+pub fn histogram(values: &[U256], boundaries: &[U256]) -> Result<Vec<U256>> {
+    if boundaries.is_empty() || boundaries.len() > MAX_HISTOGRAM_BOUNDARIES {
+        bail!(
+            "Histogram must have 1 to {} boundaries, got {}",
+            MAX_HISTOGRAM_BOUNDARIES,
+            boundaries.len()
+        );
+    }
+    if boundaries.windows(2).any(|window| window[0] >= window[1]) {
+        bail!("Histogram boundaries must be strictly ascending");
     }
 
-    let quotient = a / b;
-    let remainder = a % b;
-    let divisor_half = b / U256::from(2);
+    let mut counts = vec![U256::ZERO; boundaries.len() + 1];
+    for value in values {
+        let bucket = boundaries.partition_point(|boundary| boundary <= value);
+        counts[bucket] += U256::from(1);
+    }
+    Ok(counts)
+}
 
-    if remainder > divisor_half || (remainder == divisor_half && b % U256::from(2) == U256::from(0))
-    {
-        Ok(quotient + U256::from(1))
-    } else {
-        Ok(quotient)
+/// Returns the commitment to the bucket counts of the histogram,
+/// the `keccak256` of the counts as 32 bytes big endian words in order
+pub fn histogram_commitment(values: &[U256], boundaries: &[U256]) -> Result<U256> {
+    let counts = histogram(values, boundaries)?;
+    Ok(bucket_counts_commitment(&counts))
+}
+
+/// Returns the commitment to the bucket counts, see [`histogram_commitment`]
+pub fn bucket_counts_commitment(counts: &[U256]) -> U256 {
+    let encoded: Vec<u8> = counts
+        .iter()
+        .flat_map(|count| count.to_be_bytes::<32>())
+        .collect();
+    U256::from_be_bytes(keccak256(encoded).0)
+}
+
+/// Rounding mode of a division
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Rounding {
+    /// Round to the nearest integer, half up
+    #[default]
+    HalfUp,
+    /// Round down
+    Floor,
+    /// Round up
+    Ceil,
+}
+
+impl Rounding {
+    pub fn is_default(&self) -> bool {
+        *self == Rounding::default()
+    }
+
+    /// Divide with the rounding mode. The divisor must not be zero.
+    pub fn divide(&self, a: U512, b: U512) -> U512 {
+        let quotient = a / b;
+        let remainder = a % b;
+        let round_up = match self {
+            Rounding::Floor => false,
+            Rounding::Ceil => !remainder.is_zero(),
+            Rounding::HalfUp => remainder >= b - remainder,
+        };
+        if round_up {
+            quotient + U512::from(1)
+        } else {
+            quotient
+        }
+    }
+
+    pub fn to_index(rounding: &Self) -> u8 {
+        match rounding {
+            Rounding::HalfUp => 0,
+            Rounding::Floor => 1,
+            Rounding::Ceil => 2,
+        }
+    }
+
+    pub fn from_index(index: u8) -> Result<Self> {
+        match index {
+            0 => Ok(Rounding::HalfUp),
+            1 => Ok(Rounding::Floor),
+            2 => Ok(Rounding::Ceil),
+            _ => bail!("Unknown rounding mode"),
+        }
     }
 }
 
-fn roundup(value: String) -> u128 {
-    let result: f64 = value.parse().unwrap();
-    result.round() as u128
+impl FromStr for Rounding {
+    type Err = anyhow::Error;
+
+    fn from_str(rounding: &str) -> Result<Self> {
+        match rounding {
+            "half_up" => Ok(Self::HalfUp),
+            "floor" => Ok(Self::Floor),
+            "ceil" => Ok(Self::Ceil),
+            _ => bail!("Unknown rounding mode"),
+        }
+    }
+}
+
+impl TryFrom<String> for Rounding {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Rounding::from_str(&value)
+    }
+}
+
+impl From<Rounding> for String {
+    fn from(value: Rounding) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rounding = match self {
+            Rounding::HalfUp => "half_up",
+            Rounding::Floor => "floor",
+            Rounding::Ceil => "ceil",
+        };
+        write!(f, "{}", rounding)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use alloy::primitives::{address, Bloom, BloomInput};
+
     use super::*;
 
     #[test]
@@ -321,6 +776,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_overflow() {
+        let values = vec![U256::from(1), U256::MAX - U256::from(1), U256::from(2)];
+        let err = sum(&values).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OverflowError>(),
+            Some(&OverflowError::Accumulation {
+                operation: "Sum",
+                index: 2,
+                value: U256::from(2)
+            })
+        );
+        assert_eq!(err.to_string(), "Sum overflows at value 2 (2)");
+
+        // the average of near max values is accumulated in u512
+        let values = vec![U256::MAX, U256::MAX - U256::from(2)];
+        assert_eq!(average(&values).unwrap(), U256::MAX - U256::from(1));
+        let values = vec![U256::MAX; 3];
+        assert_eq!(average(&values).unwrap(), U256::MAX);
+
+        let err = fixed_point_average(&values, 77, &Rounding::Floor).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OverflowError>(),
+            Some(&OverflowError::Result {
+                operation: "Average",
+                bound: "u256"
+            })
+        );
+
+        let err = net_change(&[U256::ZERO, U256::MAX], false).unwrap_err();
+        assert_eq!(err.to_string(), "Net change result overflows int256");
+        let err = variance(&[U256::ZERO, U256::MAX]).unwrap_err();
+        assert!(err.downcast_ref::<OverflowError>().is_some());
+    }
+
     #[test]
     fn test_avg_multi() {
         let values = vec![
@@ -379,6 +869,364 @@ mod tests {
             U256::from(100),
         ];
         assert_eq!(standard_deviation(&values).unwrap(), U256::from(39));
+
+        // sqrt(0.25) = 0.5 rounds up, sqrt(0.16) = 0.4 rounds down
+        let values = vec![U256::from(0), U256::from(1)];
+        assert_eq!(standard_deviation(&values).unwrap(), U256::from(1));
+        let values = vec![
+            U256::from(0),
+            U256::from(0),
+            U256::from(0),
+            U256::from(0),
+            U256::from(1),
+        ];
+        assert_eq!(standard_deviation(&values).unwrap(), U256::from(0));
+
+        // exact for wei-denominated values, where f64 would lose precision
+        let base = U256::from(10).pow(U256::from(24));
+        let values = vec![base, base + U256::from(2), base + U256::from(4)];
+        // variance is 8 / 3
+        assert_eq!(standard_deviation(&values).unwrap(), U256::from(2));
+        assert_eq!(variance(&values).unwrap(), U256::from(3));
+
+        // largest spread of u256 values
+        let values = vec![U256::ZERO, U256::MAX];
+        assert_eq!(
+            standard_deviation(&values).unwrap(),
+            (U256::MAX >> 1) + U256::from(1)
+        );
+        assert!(variance(&values).is_err());
+
+        assert!(standard_deviation(&[]).is_err());
+    }
+
+    #[test]
+    fn test_variance() {
+        let values = vec![
+            U256::from(2),
+            U256::from(4),
+            U256::from(4),
+            U256::from(4),
+            U256::from(5),
+            U256::from(5),
+            U256::from(7),
+            U256::from(9),
+        ];
+        assert_eq!(variance(&values).unwrap(), U256::from(4));
+        assert_eq!(standard_deviation(&values).unwrap(), U256::from(2));
+
+        // variance is 0.25, which rounds down, and 0.5, which rounds up
+        let values = vec![U256::from(0), U256::from(0), U256::from(0), U256::from(2)];
+        assert_eq!(variance(&values).unwrap(), U256::from(1));
+        let values = vec![U256::from(1), U256::from(2)];
+        assert_eq!(variance(&values).unwrap(), U256::from(0));
+        let values = vec![U256::from(0), U256::from(0), U256::from(0), U256::from(1)];
+        assert_eq!(variance(&values).unwrap(), U256::from(0));
+
+        let values = vec![U256::from(6776); 11];
+        assert_eq!(variance(&values).unwrap(), U256::ZERO);
+
+        assert!(variance(&[]).is_err());
+    }
+
+    #[test]
+    fn test_time_weighted_average() {
+        // base fees over blocks 12s apart, then a 36s gap after a missed slot
+        let values = vec![
+            U256::from(10),
+            U256::from(20),
+            U256::from(40),
+            U256::from(1000),
+        ];
+        let timestamps = vec![1000, 1012, 1024, 1060];
+        // (10 * 12 + 20 * 12 + 40 * 36) / 60 = 30
+        assert_eq!(
+            time_weighted_average(&values, &timestamps).unwrap(),
+            U256::from(30)
+        );
+        // rounded half up: (1 * 1 + 2 * 1) / 2 = 1.5
+        assert_eq!(
+            time_weighted_average(&[U256::from(1), U256::from(2), U256::ZERO], &[0, 1, 2]).unwrap(),
+            U256::from(2)
+        );
+        // large values do not overflow
+        assert_eq!(
+            time_weighted_average(&[U256::MAX, U256::MAX, U256::ZERO], &[0, 12, 24]).unwrap(),
+            U256::MAX
+        );
+
+        assert_eq!(
+            time_weighted_average(&[U256::from(7)], &[1000]).unwrap(),
+            U256::from(7)
+        );
+        assert!(time_weighted_average(&values, &timestamps[..3]).is_err());
+        assert!(time_weighted_average(&values, &[1000, 1012, 1000, 1060]).is_err());
+        assert!(time_weighted_average(&values[..2], &[1000, 1000]).is_err());
+        assert!(time_weighted_average(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_delta_aggregations() {
+        // treasury balance over the sampled blocks
+        let values = vec![
+            U256::from(500),
+            U256::from(800),
+            U256::from(300),
+            U256::from(650),
+            U256::from(200),
+            U256::from(450),
+        ];
+        assert_eq!(first(&values).unwrap(), U256::from(500));
+        assert_eq!(last(&values).unwrap(), U256::from(450));
+        assert_eq!(
+            net_change(&values, false).unwrap(),
+            I256::try_from(-50).unwrap().into_raw()
+        );
+        assert_eq!(max_drawdown(&values, false).unwrap(), U256::from(600));
+
+        let increasing = vec![U256::from(1), U256::from(2), U256::from(2), U256::from(9)];
+        assert_eq!(net_change(&increasing, false).unwrap(), U256::from(8));
+        assert_eq!(max_drawdown(&increasing, false).unwrap(), U256::ZERO);
+        assert_eq!(max_drawdown(&[U256::from(5)], false).unwrap(), U256::ZERO);
+
+        // unsigned changes beyond int256 overflow
+        assert!(net_change(&[U256::ZERO, U256::MAX], false).is_err());
+        assert!(net_change(&[U256::MAX, U256::ZERO], false).is_err());
+        assert_eq!(
+            net_change(&[U256::from(1) << 255, U256::ZERO], false).unwrap(),
+            I256::MIN.into_raw()
+        );
+        assert_eq!(
+            max_drawdown(&[U256::MAX, U256::ZERO], false).unwrap(),
+            U256::MAX
+        );
+
+        // signed values, e.g. int256 storage
+        let signed: Vec<U256> = [-20, 40, -100, 10]
+            .into_iter()
+            .map(|value: i64| I256::try_from(value).unwrap().into_raw())
+            .collect();
+        assert_eq!(
+            net_change(&signed, true).unwrap(),
+            I256::try_from(30).unwrap().into_raw()
+        );
+        assert_eq!(max_drawdown(&signed, true).unwrap(), U256::from(140));
+        assert_eq!(find_signed_min(&signed).unwrap(), signed[2]);
+        assert_eq!(find_signed_max(&signed).unwrap(), signed[1]);
+        // as unsigned, negative values are the largest
+        assert_eq!(find_max(&signed).unwrap(), signed[0]);
+        assert_eq!(
+            max_drawdown(&[I256::MAX.into_raw(), I256::MIN.into_raw()], true).unwrap(),
+            U256::MAX
+        );
+        assert!(net_change(&[I256::MAX.into_raw(), I256::MIN.into_raw()], true).is_err());
+
+        assert!(first(&[]).is_err());
+        assert!(last(&[]).is_err());
+        assert!(net_change(&[], false).is_err());
+        assert!(max_drawdown(&[], true).is_err());
+        assert!(find_signed_min(&[]).is_err());
+    }
+
+    #[test]
+    fn test_fixed_point_average() {
+        // success of 7 receipts, a rate of 71.428...%
+        let values: Vec<U256> = [1, 1, 0, 1, 1, 0, 1].into_iter().map(U256::from).collect();
+        assert_eq!(average(&values).unwrap(), U256::from(1));
+        assert_eq!(
+            fixed_point_average(&values, 4, &Rounding::HalfUp).unwrap(),
+            U256::from(7143)
+        );
+        assert_eq!(
+            fixed_point_average(&values, 4, &Rounding::Floor).unwrap(),
+            U256::from(7142)
+        );
+        assert_eq!(
+            fixed_point_average(&values, 4, &Rounding::Ceil).unwrap(),
+            U256::from(7143)
+        );
+        assert_eq!(
+            fixed_point_average(&values, 18, &Rounding::Floor).unwrap(),
+            U256::from(714285714285714285u64)
+        );
+
+        // half up rounds exact halves up, and is the rounding of AVG
+        let values = vec![U256::from(1), U256::from(2)];
+        assert_eq!(
+            fixed_point_average(&values, 0, &Rounding::HalfUp).unwrap(),
+            average(&values).unwrap()
+        );
+        assert_eq!(
+            fixed_point_average(&values, 0, &Rounding::Floor).unwrap(),
+            U256::from(1)
+        );
+        // exact averages are not rounded
+        let values = vec![U256::from(2), U256::from(4)];
+        assert_eq!(
+            fixed_point_average(&values, 0, &Rounding::Ceil).unwrap(),
+            U256::from(3)
+        );
+
+        // the sum does not overflow, but the scaled result may
+        let values = vec![U256::MAX, U256::MAX];
+        assert_eq!(
+            fixed_point_average(&values, 0, &Rounding::Floor).unwrap(),
+            U256::MAX
+        );
+        assert!(fixed_point_average(&values, 1, &Rounding::Floor).is_err());
+        assert!(fixed_point_average(&[U256::from(1)], 78, &Rounding::Floor).is_err());
+        assert_eq!(
+            fixed_point_average(&[U256::from(1)], 77, &Rounding::Floor).unwrap(),
+            U256::from(10).pow(U256::from(77))
+        );
+        assert!(fixed_point_average(&[], 2, &Rounding::Floor).is_err());
+
+        // a scaled sum overflowing u512 is an error, not a wrapped average
+        let values = vec![U256::MAX - U256::from(1), U256::MAX];
+        let err = fixed_point_average(&values, 77, &Rounding::Floor).unwrap_err();
+        assert_eq!(err.to_string(), "Average result overflows u256");
+
+        assert_eq!("ceil".parse::<Rounding>().unwrap(), Rounding::Ceil);
+        assert_eq!(Rounding::HalfUp.to_string(), "half_up");
+        assert!("up".parse::<Rounding>().is_err());
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        let values: Vec<U256> = [5, 1, 5, 3, 1, 5].into_iter().map(U256::from).collect();
+        assert_eq!(count_distinct(&values).unwrap(), U256::from(3));
+        assert_eq!(count_distinct(&[U256::MAX]).unwrap(), U256::from(1));
+        assert_eq!(count_distinct(&[]).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let values: Vec<U256> = [0, 9, 10, 11, 99, 100, 1000]
+            .into_iter()
+            .map(U256::from)
+            .collect();
+        let boundaries = vec![U256::from(10), U256::from(100)];
+        // boundaries belong to the bucket they start
+        assert_eq!(
+            histogram(&values, &boundaries).unwrap(),
+            vec![U256::from(2), U256::from(3), U256::from(2)]
+        );
+        assert_eq!(histogram(&[], &boundaries).unwrap(), vec![U256::ZERO; 3]);
+
+        let mut encoded = Vec::new();
+        for count in [2u64, 3, 2] {
+            encoded.extend_from_slice(&U256::from(count).to_be_bytes::<32>());
+        }
+        assert_eq!(
+            histogram_commitment(&values, &boundaries).unwrap(),
+            U256::from_be_bytes(keccak256(encoded).0)
+        );
+
+        assert!(histogram(&values, &[]).is_err());
+        assert!(histogram(&values, &[U256::from(10), U256::from(10)]).is_err());
+        assert!(histogram(&values, &[U256::from(100), U256::from(10)]).is_err());
+        let boundaries: Vec<U256> = (1..=256u64).map(U256::from).collect();
+        assert!(histogram(&values, &boundaries).is_err());
+        assert!(histogram(&values, &boundaries[..255]).is_ok());
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let to =
+            U256::from_be_slice(address!("dAC17F958D2ee523a2206206994597C13D831ec7").as_slice());
+        let values = vec![to, U256::from(100)];
+
+        // same as the Ethereum logs bloom of the 32 bytes words
+        let filter = bloom_filter(&values, BLOOM_BITS, 32).unwrap();
+        let mut bloom = Bloom::default();
+        for value in &values {
+            bloom.accrue(BloomInput::Raw(&value.to_be_bytes::<32>()));
+        }
+        assert_eq!(filter, bloom.as_slice());
+        assert_eq!(
+            bloom_filterize(&values, BLOOM_BITS, 32).unwrap(),
+            U256::from_be_bytes(keccak256(bloom.as_slice()).0)
+        );
+
+        // addresses are hashed as in the logs bloom of their logs
+        let address = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+        let filter = bloom_filter(&values, BLOOM_BITS, 20).unwrap();
+        let mut bloom = Bloom::default();
+        bloom.accrue(BloomInput::Raw(address.as_slice()));
+        bloom.accrue(BloomInput::Raw(&U256::from(100).to_be_bytes::<32>()[12..]));
+        assert_eq!(filter, bloom.as_slice());
+        assert!(bloom.contains_input(BloomInput::Raw(address.as_slice())));
+        assert!(bloom_filter(&[U256::MAX], BLOOM_BITS, 20).is_err());
+        assert!(bloom_filter(&values, BLOOM_BITS, 0).is_err());
+        assert!(bloom_filter(&values, BLOOM_BITS, 33).is_err());
+
+        // a smaller filter folds the bits of the full size filter
+        let filter = bloom_filter(&values, BLOOM_BITS, 32).unwrap();
+        let small = bloom_filter(&values, 256, 32).unwrap();
+        assert_eq!(small.len(), 32);
+        let folded = filter.chunks(32).fold(vec![0u8; 32], |mut folded, chunk| {
+            folded.iter_mut().zip(chunk).for_each(|(a, b)| *a |= b);
+            folded
+        });
+        assert_eq!(small, folded);
+
+        assert_eq!(bloom_filter(&[], 64, 32).unwrap(), vec![0u8; 8]);
+        assert!(bloom_filter(&values, 100, 32).is_err());
+        assert!(bloom_filter(&values, 4096, 32).is_err());
+        assert!(bloom_filter(&values, 4, 32).is_err());
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for value in 0u64..2000 {
+            let root = isqrt(U768::from(value)).to::<u64>();
+            assert!(root * root <= value && (root + 1) * (root + 1) > value);
+        }
+        let max = U768::MAX;
+        let root = isqrt(max);
+        assert!(root * root <= max);
+        assert_eq!(root, (U768::from(1) << 384) - U768::from(1));
+    }
+
+    #[test]
+    fn test_median() {
+        let values = vec![U256::from(7), U256::from(1), U256::from(3)];
+        assert_eq!(median(&values).unwrap(), U256::from(3));
+
+        // average of the two middle values, rounded down
+        let values = vec![U256::from(4), U256::from(1), U256::from(8), U256::from(5)];
+        assert_eq!(median(&values).unwrap(), U256::from(4));
+
+        // no overflow on large values
+        let values = vec![U256::MAX, U256::MAX - U256::from(1)];
+        assert_eq!(median(&values).unwrap(), U256::MAX - U256::from(1));
+
+        assert!(median(&[]).is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<U256> = (1..=10).rev().map(U256::from).collect();
+        assert_eq!(percentile(&values, 0).unwrap(), U256::from(1));
+        assert_eq!(percentile(&values, 10).unwrap(), U256::from(1));
+        assert_eq!(percentile(&values, 11).unwrap(), U256::from(2));
+        assert_eq!(percentile(&values, 50).unwrap(), U256::from(5));
+        assert_eq!(percentile(&values, 90).unwrap(), U256::from(9));
+        assert_eq!(percentile(&values, 95).unwrap(), U256::from(10));
+        assert_eq!(percentile(&values, 100).unwrap(), U256::from(10));
+
+        let values = vec![
+            U256::from(15),
+            U256::from(20),
+            U256::from(35),
+            U256::from(40),
+            U256::from(50),
+        ];
+        assert_eq!(percentile(&values, 30).unwrap(), U256::from(20));
+        assert_eq!(percentile(&values, 40).unwrap(), U256::from(20));
+
+        assert!(percentile(&values, 101).is_err());
+        assert!(percentile(&[], 50).is_err());
     }
 
     #[test]
@@ -1,20 +1,48 @@
-use alloy::primitives::U256;
+use alloy::primitives::{keccak256, Bytes, U256};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-use self::integer::Operator;
+use self::{
+    integer::{Operator, Rounding},
+    output::AggregationOutput,
+    predicate::Predicate,
+};
 
 pub mod integer;
+pub mod output;
+pub mod predicate;
 
 /// Aggregation function types
 ///
 /// ### Defined
-/// - AVG - Returns the average of the values
+/// - AVG - Returns the average of the values, as a fixed-point number with the decimals in the context
 /// - SUM - Sum of values
 /// - MIN - Find the minimum value
 /// - MAX - Find the maximum value
 /// - COUNT - Count number of values that satisfy a condition
+/// - MEDIAN - Returns the median of the values
+/// - PERCENTILE - Returns the p-th percentile of the values, with `p` in the context
+/// - VARIANCE - Returns the population variance of the values, rounded to the nearest integer
+/// - STDDEV - Returns the population standard deviation of the values, rounded to the nearest integer
+/// - BLOOM - Returns the hash of the bloom filter of the values, with the size in bits in the context
+/// - MERKLE - Returns the merkle root of the values in order
+/// - TWAP - Returns the average of the values weighted by the time to the next sampled block
+/// - FIRST - Returns the first value
+/// - LAST - Returns the last value
+/// - NET_CHANGE - Returns the change from the first to the last value, as a two's complement int256
+/// - MAX_DRAWDOWN - Returns the largest decline from a value to any later value
+/// - COUNT_DISTINCT - Count number of distinct values
+/// - HISTOGRAM - Returns the hash of the number of values in each bucket, with the boundaries in the context
+///
+/// With `signed` in the context, MIN, MAX, NET_CHANGE, MAX_DRAWDOWN and COUNT take the values as
+/// two's complement int256, while FIRST, LAST and COUNT_DISTINCT return the same result for them.
+/// Other functions take the values as uint256 and reject `signed`.
+///
+/// With a `filter` in the context, any function but TWAP aggregates only the values satisfying
+/// the [`Predicate`]. COUNT without operator counts them. If no value satisfies it, COUNT,
+/// COUNT_DISTINCT, BLOOM and HISTOGRAM return the result of no values, other functions fail
+/// with "No values found". TWAP rejects filters, as dropped values would drop their time intervals.
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AggregationFunction {
@@ -23,6 +51,22 @@ pub enum AggregationFunction {
     MIN,
     MAX,
     COUNT,
+    MEDIAN,
+    PERCENTILE,
+    VARIANCE,
+    STDDEV,
+    BLOOM,
+    MERKLE,
+    TWAP,
+    FIRST,
+    LAST,
+    #[allow(non_camel_case_types)]
+    NET_CHANGE,
+    #[allow(non_camel_case_types)]
+    MAX_DRAWDOWN,
+    #[allow(non_camel_case_types)]
+    COUNT_DISTINCT,
+    HISTOGRAM,
 }
 
 /// Get [`AggregationFunction`] from function id
@@ -36,6 +80,19 @@ impl FromStr for AggregationFunction {
             "MIN" => Ok(Self::MIN),
             "MAX" => Ok(Self::MAX),
             "COUNT" => Ok(Self::COUNT),
+            "MEDIAN" => Ok(Self::MEDIAN),
+            "PERCENTILE" => Ok(Self::PERCENTILE),
+            "VARIANCE" => Ok(Self::VARIANCE),
+            "STDDEV" => Ok(Self::STDDEV),
+            "BLOOM" => Ok(Self::BLOOM),
+            "MERKLE" => Ok(Self::MERKLE),
+            "TWAP" => Ok(Self::TWAP),
+            "FIRST" => Ok(Self::FIRST),
+            "LAST" => Ok(Self::LAST),
+            "NET_CHANGE" => Ok(Self::NET_CHANGE),
+            "MAX_DRAWDOWN" => Ok(Self::MAX_DRAWDOWN),
+            "COUNT_DISTINCT" => Ok(Self::COUNT_DISTINCT),
+            "HISTOGRAM" => Ok(Self::HISTOGRAM),
             _ => bail!("Unknown aggregation function"),
         }
     }
@@ -49,14 +106,52 @@ impl std::fmt::Display for AggregationFunction {
             AggregationFunction::MIN => write!(f, "min"),
             AggregationFunction::MAX => write!(f, "max"),
             AggregationFunction::COUNT => write!(f, "count"),
+            AggregationFunction::MEDIAN => write!(f, "median"),
+            AggregationFunction::PERCENTILE => write!(f, "percentile"),
+            AggregationFunction::VARIANCE => write!(f, "variance"),
+            AggregationFunction::STDDEV => write!(f, "stddev"),
+            AggregationFunction::BLOOM => write!(f, "bloom"),
+            AggregationFunction::MERKLE => write!(f, "merkle"),
+            AggregationFunction::TWAP => write!(f, "twap"),
+            AggregationFunction::FIRST => write!(f, "first"),
+            AggregationFunction::LAST => write!(f, "last"),
+            AggregationFunction::NET_CHANGE => write!(f, "net_change"),
+            AggregationFunction::MAX_DRAWDOWN => write!(f, "max_drawdown"),
+            AggregationFunction::COUNT_DISTINCT => write!(f, "count_distinct"),
+            AggregationFunction::HISTOGRAM => write!(f, "histogram"),
         }
     }
 }
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct FunctionContext {
     pub operator: Operator,
     pub value_to_compare: U256,
+    /// Percentile of PERCENTILE, between 0 and 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<u8>,
+    /// Size of the bloom filter of BLOOM in bits, a power of two up to 2048
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bloom_bits: Option<u16>,
+    /// Values are two's complement int256, see [`AggregationFunction::supports_signed`]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub signed: bool,
+    /// Predicate on the values to aggregate, all values if not set. Not supported by TWAP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Predicate>,
+    /// Decimals of the fixed-point result of AVG, at most [`integer::MAX_DECIMALS`]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub decimals: u8,
+    /// Rounding of the result of AVG
+    #[serde(skip_serializing_if = "Rounding::is_default")]
+    pub rounding: Rounding,
+    /// Strictly ascending boundaries of the buckets of HISTOGRAM
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub boundaries: Vec<U256>,
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
 }
 
 impl Default for FunctionContext {
@@ -64,6 +159,13 @@ impl Default for FunctionContext {
         Self {
             operator: Operator::None,
             value_to_compare: U256::ZERO,
+            percentile: None,
+            bloom_bits: None,
+            signed: false,
+            filter: None,
+            decimals: 0,
+            rounding: Rounding::HalfUp,
+            boundaries: Vec::new(),
         }
     }
 }
@@ -71,7 +173,51 @@ impl Default for FunctionContext {
 impl FromStr for FunctionContext {
     type Err = anyhow::Error;
 
+    /// Parse context from "{operator}.{value}" format, "p{percentile}" format for PERCENTILE,
+    /// "bits{bloom_bits}" format for BLOOM, "signed" or "signed {context}" for signed values,
+    /// "d{decimals}" or "d{decimals}.{rounding}" format for AVG,
+    /// or "buckets({boundary},..)" format for HISTOGRAM,
+    /// optionally followed by "where {predicate}" to filter the values
     fn from_str(context: &str) -> Result<Self, Self::Err> {
+        let context = context.trim();
+        if let Some(filter) = context.strip_prefix("where ") {
+            return Ok(Self::default().with_filter(filter.parse()?));
+        }
+        if let Some((context, filter)) = context.split_once(" where ") {
+            return Ok(Self::from_str(context)?.with_filter(filter.parse()?));
+        }
+        if context == "signed" {
+            return Ok(Self::default().with_signed());
+        }
+        if let Some(context) = context.strip_prefix("signed ") {
+            return Ok(Self::from_str(context)?.with_signed());
+        }
+        if let Some(boundaries) = context
+            .strip_prefix("buckets(")
+            .and_then(|boundaries| boundaries.strip_suffix(')'))
+        {
+            let boundaries = boundaries
+                .split(',')
+                .map(|boundary| match boundary.trim() {
+                    "" => bail!("Empty histogram boundary"),
+                    boundary => Ok(U256::from_str(boundary)?),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(Self::default().with_boundaries(boundaries));
+        }
+        if let Some(fixed_point) = context.strip_prefix('d') {
+            let (decimals, rounding) = match fixed_point.split_once('.') {
+                Some((decimals, rounding)) => (decimals, rounding.parse()?),
+                None => (fixed_point, Rounding::default()),
+            };
+            return Ok(Self::default().with_fixed_point(decimals.parse()?, rounding));
+        }
+        if let Some(bloom_bits) = context.strip_prefix("bits") {
+            return Ok(Self::default().with_bloom_bits(bloom_bits.parse()?));
+        }
+        if let Some(percentile) = context.strip_prefix('p') {
+            return Ok(Self::default().with_percentile(percentile.parse()?));
+        }
         let parts: Vec<&str> = context.split('.').collect();
         if parts.len() != 2 {
             bail!("Invalid FnContext format");
@@ -80,15 +226,60 @@ impl FromStr for FunctionContext {
         let value_to_compare = parts[1].to_string();
 
         Ok(Self {
-            operator: Operator::from_str(&operator).unwrap(),
+            operator: Operator::from_str(&operator)?,
             value_to_compare: U256::from_str(&value_to_compare)?,
+            percentile: None,
+            bloom_bits: None,
+            signed: false,
+            filter: None,
+            decimals: 0,
+            rounding: Rounding::HalfUp,
+            boundaries: Vec::new(),
         })
     }
 }
 
 impl std::fmt::Display for FunctionContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.operator, self.value_to_compare)
+        let context = match (self.percentile, self.bloom_bits) {
+            (Some(percentile), _) => format!("p{}", percentile),
+            (None, Some(bloom_bits)) => format!("bits{}", bloom_bits),
+            (None, None) if !self.boundaries.is_empty() => {
+                let boundaries: Vec<String> = self
+                    .boundaries
+                    .iter()
+                    .map(|boundary| boundary.to_string())
+                    .collect();
+                format!("buckets({})", boundaries.join(","))
+            }
+            (None, None) if self.decimals != 0 || !self.rounding.is_default() => {
+                match self.rounding {
+                    Rounding::HalfUp => format!("d{}", self.decimals),
+                    rounding => format!("d{}.{}", self.decimals, rounding),
+                }
+            }
+            (None, None) => format!("{}.{}", self.operator, self.value_to_compare),
+        };
+        // the signed flag prefixes the rest of the context, if any
+        let unsigned = Self {
+            signed: false,
+            filter: None,
+            ..self.clone()
+        };
+        let context = match self.signed {
+            true if unsigned == Self::default() => "signed".to_string(),
+            true => format!("signed {}", context),
+            false => context,
+        };
+        let without_filter = Self {
+            filter: None,
+            ..self.clone()
+        };
+        match &self.filter {
+            Some(filter) if without_filter == Self::default() => write!(f, "where {}", filter),
+            Some(filter) => write!(f, "{} where {}", context, filter),
+            None => write!(f, "{}", context),
+        }
     }
 }
 
@@ -97,8 +288,46 @@ impl FunctionContext {
         Self {
             operator,
             value_to_compare,
+            percentile: None,
+            bloom_bits: None,
+            signed: false,
+            filter: None,
+            decimals: 0,
+            rounding: Rounding::HalfUp,
+            boundaries: Vec::new(),
         }
     }
+
+    pub fn with_percentile(mut self, percentile: u8) -> Self {
+        self.percentile = Some(percentile);
+        self
+    }
+
+    pub fn with_bloom_bits(mut self, bloom_bits: u16) -> Self {
+        self.bloom_bits = Some(bloom_bits);
+        self
+    }
+
+    pub fn with_signed(mut self) -> Self {
+        self.signed = true;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Predicate) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_boundaries(mut self, boundaries: Vec<U256>) -> Self {
+        self.boundaries = boundaries;
+        self
+    }
+
+    pub fn with_fixed_point(mut self, decimals: u8, rounding: Rounding) -> Self {
+        self.decimals = decimals;
+        self.rounding = rounding;
+        self
+    }
 }
 
 impl AggregationFunction {
@@ -109,6 +338,19 @@ impl AggregationFunction {
             AggregationFunction::MIN => 2,
             AggregationFunction::MAX => 3,
             AggregationFunction::COUNT => 4,
+            AggregationFunction::MEDIAN => 5,
+            AggregationFunction::PERCENTILE => 6,
+            AggregationFunction::VARIANCE => 7,
+            AggregationFunction::STDDEV => 8,
+            AggregationFunction::BLOOM => 9,
+            AggregationFunction::MERKLE => 10,
+            AggregationFunction::TWAP => 11,
+            AggregationFunction::FIRST => 12,
+            AggregationFunction::LAST => 13,
+            AggregationFunction::NET_CHANGE => 14,
+            AggregationFunction::MAX_DRAWDOWN => 15,
+            AggregationFunction::COUNT_DISTINCT => 16,
+            AggregationFunction::HISTOGRAM => 17,
         }
     }
 
@@ -119,30 +361,224 @@ impl AggregationFunction {
             2 => Ok(AggregationFunction::MIN),
             3 => Ok(AggregationFunction::MAX),
             4 => Ok(AggregationFunction::COUNT),
+            5 => Ok(AggregationFunction::MEDIAN),
+            6 => Ok(AggregationFunction::PERCENTILE),
+            7 => Ok(AggregationFunction::VARIANCE),
+            8 => Ok(AggregationFunction::STDDEV),
+            9 => Ok(AggregationFunction::BLOOM),
+            10 => Ok(AggregationFunction::MERKLE),
+            11 => Ok(AggregationFunction::TWAP),
+            12 => Ok(AggregationFunction::FIRST),
+            13 => Ok(AggregationFunction::LAST),
+            14 => Ok(AggregationFunction::NET_CHANGE),
+            15 => Ok(AggregationFunction::MAX_DRAWDOWN),
+            16 => Ok(AggregationFunction::COUNT_DISTINCT),
+            17 => Ok(AggregationFunction::HISTOGRAM),
             _ => bail!("Unknown aggregation function index"),
         }
     }
 
-    pub fn operation(&self, values: &[U256], ctx: Option<FunctionContext>) -> Result<U256> {
+    /// Whether the function takes two's complement int256 values with `signed` in the context
+    pub fn supports_signed(&self) -> bool {
+        matches!(
+            self,
+            AggregationFunction::MIN
+                | AggregationFunction::MAX
+                | AggregationFunction::NET_CHANGE
+                | AggregationFunction::MAX_DRAWDOWN
+                | AggregationFunction::COUNT
+                | AggregationFunction::FIRST
+                | AggregationFunction::LAST
+                | AggregationFunction::COUNT_DISTINCT
+        )
+    }
+
+    /// Check that the function supports the context
+    pub fn validate_context(&self, ctx: &FunctionContext) -> Result<()> {
+        if ctx.signed && !self.supports_signed() {
+            bail!("{} does not support signed values", self);
+        }
+        if let Some(filter) = &ctx.filter {
+            if *self == AggregationFunction::TWAP {
+                bail!("TWAP does not support filters, as they would drop time intervals");
+            }
+            filter.validate_ranges(ctx.signed)?;
+        }
+        Ok(())
+    }
+
+    /// Aggregate the values, which are `value_bytes` wide, see [`Self::operation_with_output`]
+    pub fn operation(
+        &self,
+        values: &[U256],
+        value_bytes: usize,
+        ctx: Option<FunctionContext>,
+    ) -> Result<U256> {
+        self.validate(&ctx)?;
+        let values = filter_values(values, &ctx);
+        self.aggregate(&values, value_bytes, ctx)
+    }
+
+    /// Aggregate the values that are already filtered
+    fn aggregate(
+        &self,
+        values: &[U256],
+        value_bytes: usize,
+        ctx: Option<FunctionContext>,
+    ) -> Result<U256> {
         match self {
             // Aggregation functions for integer values
-            AggregationFunction::AVG => integer::average(values),
+            AggregationFunction::AVG => match ctx {
+                Some(ctx) => integer::fixed_point_average(values, ctx.decimals, &ctx.rounding),
+                None => integer::average(values),
+            },
+            AggregationFunction::MAX if is_signed(&ctx) => integer::find_signed_max(values),
             AggregationFunction::MAX => integer::find_max(values),
+            AggregationFunction::MIN if is_signed(&ctx) => integer::find_signed_min(values),
             AggregationFunction::MIN => integer::find_min(values),
             AggregationFunction::SUM => integer::sum(values),
-            AggregationFunction::COUNT => {
-                if let Some(ctx) = ctx {
-                    integer::count(values, &ctx)
-                } else {
-                    bail!("Context not provided for COUNT")
+            AggregationFunction::COUNT => match ctx {
+                Some(ctx) if ctx.operator == Operator::None && ctx.filter.is_some() => {
+                    Ok(U256::from(values.len()))
                 }
+                Some(ctx) => integer::count(values, &ctx),
+                None => bail!("Context not provided for COUNT"),
+            },
+            AggregationFunction::MEDIAN => integer::median(values),
+            AggregationFunction::PERCENTILE => match ctx.and_then(|ctx| ctx.percentile) {
+                Some(percentile) => integer::percentile(values, percentile),
+                None => bail!("Percentile not provided for PERCENTILE"),
+            },
+            AggregationFunction::VARIANCE => integer::variance(values),
+            AggregationFunction::STDDEV => integer::standard_deviation(values),
+            AggregationFunction::BLOOM => {
+                integer::bloom_filterize(values, bloom_bits(&ctx), value_bytes)
+            }
+            AggregationFunction::MERKLE => integer::merkle_root(values),
+            AggregationFunction::TWAP => bail!("Timestamps not provided for TWAP"),
+            AggregationFunction::FIRST => integer::first(values),
+            AggregationFunction::LAST => integer::last(values),
+            AggregationFunction::NET_CHANGE => integer::net_change(values, is_signed(&ctx)),
+            AggregationFunction::MAX_DRAWDOWN => integer::max_drawdown(values, is_signed(&ctx)),
+            AggregationFunction::COUNT_DISTINCT => integer::count_distinct(values),
+            AggregationFunction::HISTOGRAM => {
+                integer::histogram_commitment(values, boundaries(&ctx))
             }
         }
     }
+
+    /// Aggregate the values sampled at the given timestamps, which only TWAP uses.
+    /// Timestamps are the ones of the sampled blocks, empty if the datalake is not block sampled.
+    pub fn operation_with_timestamps(
+        &self,
+        values: &[U256],
+        timestamps: &[u64],
+        value_bytes: usize,
+        ctx: Option<FunctionContext>,
+    ) -> Result<U256> {
+        self.validate(&ctx)?;
+        let values = filter_values(values, &ctx);
+        self.aggregate_with_timestamps(&values, timestamps, value_bytes, ctx)
+    }
+
+    /// Aggregate the values sampled at the given timestamps, along with the output that is committed
+    /// by the result, e.g. the filter of BLOOM, which are computed once.
+    /// Values are `value_bytes` wide, which BLOOM hashes, see [`integer::bloom_filter`].
+    pub fn operation_with_output(
+        &self,
+        values: &[U256],
+        timestamps: &[u64],
+        value_bytes: usize,
+        ctx: Option<FunctionContext>,
+    ) -> Result<(U256, Option<AggregationOutput>)> {
+        self.validate(&ctx)?;
+        let values = filter_values(values, &ctx);
+        match self {
+            AggregationFunction::BLOOM => {
+                let filter = integer::bloom_filter(&values, bloom_bits(&ctx), value_bytes)?;
+                Ok((
+                    U256::from_be_bytes(keccak256(&filter).0),
+                    Some(AggregationOutput::BloomFilter(Bytes::from(filter))),
+                ))
+            }
+            AggregationFunction::MERKLE => {
+                let (root, proofs) = output::value_proofs(&values)?;
+                Ok((root, Some(AggregationOutput::ValueProofs(proofs))))
+            }
+            AggregationFunction::HISTOGRAM => {
+                let counts = integer::histogram(&values, boundaries(&ctx))?;
+                Ok((
+                    integer::bucket_counts_commitment(&counts),
+                    Some(AggregationOutput::BucketCounts(counts)),
+                ))
+            }
+            _ => Ok((
+                self.aggregate_with_timestamps(&values, timestamps, value_bytes, ctx)?,
+                None,
+            )),
+        }
+    }
+
+    /// Check that the function supports the context, if any
+    fn validate(&self, ctx: &Option<FunctionContext>) -> Result<()> {
+        match ctx {
+            Some(ctx) => self.validate_context(ctx),
+            None => Ok(()),
+        }
+    }
+
+    /// Aggregate the values that are already filtered, along with their timestamps
+    fn aggregate_with_timestamps(
+        &self,
+        values: &[U256],
+        timestamps: &[u64],
+        value_bytes: usize,
+        ctx: Option<FunctionContext>,
+    ) -> Result<U256> {
+        match self {
+            AggregationFunction::TWAP if timestamps.is_empty() => {
+                bail!("TWAP is only supported for block sampled datalakes")
+            }
+            AggregationFunction::TWAP => integer::time_weighted_average(values, timestamps),
+            _ => self.aggregate(values, value_bytes, ctx),
+        }
+    }
+}
+
+/// Values satisfying the filter of the context, which TWAP does not take,
+/// so the timestamps are left as they are
+fn filter_values(values: &[U256], ctx: &Option<FunctionContext>) -> Vec<U256> {
+    match ctx.as_ref().and_then(|ctx| ctx.filter.as_ref()) {
+        Some(filter) => filter.filter(values, is_signed(ctx)),
+        None => values.to_vec(),
+    }
+}
+
+/// Whether the values are two's complement int256
+fn is_signed(ctx: &Option<FunctionContext>) -> bool {
+    ctx.as_ref().is_some_and(|ctx| ctx.signed)
+}
+
+/// Size of the bloom filter of BLOOM, the Ethereum logs bloom size by default
+fn bloom_bits(ctx: &Option<FunctionContext>) -> u16 {
+    ctx.as_ref()
+        .and_then(|ctx| ctx.bloom_bits)
+        .unwrap_or(integer::BLOOM_BITS)
+}
+
+/// Boundaries of HISTOGRAM in the context, none if not set
+fn boundaries(ctx: &Option<FunctionContext>) -> &[U256] {
+    ctx.as_ref()
+        .map(|ctx| &ctx.boundaries[..])
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
+    use alloy::primitives::{keccak256, I256};
+
+    use crate::primitives::merkle_tree::{build_values_merkle_tree, value_leaf};
+
     use super::*;
 
     #[test]
@@ -151,7 +587,7 @@ mod tests {
 
         // 4952100 ~ 4952100, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
         let values = vec![U256::from_str_radix("6776", 10).unwrap()];
-        let result = sum_fn.operation(&values, None).unwrap();
+        let result = sum_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6776));
 
         // 4952100 ~ 4952103, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
@@ -161,12 +597,12 @@ mod tests {
             U256::from_str_radix("6776", 10).unwrap(),
             U256::from_str_radix("6777", 10).unwrap(),
         ];
-        let result = sum_fn.operation(&values, None).unwrap();
+        let result = sum_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(27105));
 
         // 5382810 ~ 5382810, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
         let values = vec![U256::from_str_radix("9184e72a000", 16).unwrap()];
-        let result = sum_fn.operation(&values, None).unwrap();
+        let result = sum_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from_str_radix("10000000000000", 10).unwrap());
 
         // 5382810 ~ 5382813, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
@@ -176,7 +612,7 @@ mod tests {
             U256::from_str_radix("9184e72a000", 16).unwrap(),
             U256::from_str_radix("9184e72a000", 16).unwrap(),
         ];
-        let result = sum_fn.operation(&values, None).unwrap();
+        let result = sum_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from_str_radix("40000000000000", 10).unwrap());
 
         // 4952100 ~ 4952103, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.balance
@@ -186,7 +622,7 @@ mod tests {
             U256::from_str_radix("41697298409483537348", 10).unwrap(),
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
         ];
-        let result = sum_fn.operation(&values, None).unwrap();
+        let result = sum_fn.operation(&values, 32, None).unwrap();
         assert_eq!(
             result,
             U256::from_str_radix("166788991167020783608", 10).unwrap()
@@ -199,7 +635,7 @@ mod tests {
 
         // 4952100 ~ 4952100, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
         let values = vec![U256::from_str_radix("6776", 10).unwrap()];
-        let result = avg_fn.operation(&values, None).unwrap();
+        let result = avg_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6776));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
@@ -216,12 +652,12 @@ mod tests {
             U256::from_str_radix("6777", 10).unwrap(),
             U256::from_str_radix("6777", 10).unwrap(),
         ];
-        let result = avg_fn.operation(&values, None).unwrap();
+        let result = avg_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6777));
 
         // 5382810 ~ 5382810, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
         let values = vec![U256::from_str_radix("9184e72a000", 16).unwrap()];
-        let result = avg_fn.operation(&values, None).unwrap();
+        let result = avg_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 5382810 ~ 5382813, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
@@ -231,7 +667,7 @@ mod tests {
             U256::from_str_radix("9184e72a000", 16).unwrap(),
             U256::from_str_radix("9184e72a000", 16).unwrap(),
         ];
-        let result = avg_fn.operation(&values, None).unwrap();
+        let result = avg_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.balance
@@ -248,7 +684,7 @@ mod tests {
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
         ];
-        let result = avg_fn.operation(&values, None).unwrap();
+        let result = avg_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(41697151157910180414u128));
     }
 
@@ -258,7 +694,7 @@ mod tests {
 
         // 4952100 ~ 4952100, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
         let values = vec![U256::from_str_radix("6776", 10).unwrap()];
-        let result = max_fn.operation(&values, None).unwrap();
+        let result = max_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6776));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
@@ -275,12 +711,12 @@ mod tests {
             U256::from_str_radix("6777", 10).unwrap(),
             U256::from_str_radix("6777", 10).unwrap(),
         ];
-        let result = max_fn.operation(&values, None).unwrap();
+        let result = max_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6777));
 
         // 5382810 ~ 5382810, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
         let values = vec![U256::from_str_radix("9184e72a000", 16).unwrap()];
-        let result = max_fn.operation(&values, None).unwrap();
+        let result = max_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 5382810 ~ 5382813, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
@@ -290,7 +726,7 @@ mod tests {
             U256::from_str_radix("9184e72a000", 16).unwrap(),
             U256::from_str_radix("9184e72a000", 16).unwrap(),
         ];
-        let result = max_fn.operation(&values, None).unwrap();
+        let result = max_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.balance
@@ -307,7 +743,7 @@ mod tests {
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
         ];
-        let result = max_fn.operation(&values, None).unwrap();
+        let result = max_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(41697298409483537348u128));
     }
 
@@ -317,7 +753,7 @@ mod tests {
 
         // 4952100 ~ 4952100, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
         let values = vec![U256::from_str_radix("6776", 10).unwrap()];
-        let result = min_fn.operation(&values, None).unwrap();
+        let result = min_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6776));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.nonce
@@ -334,12 +770,12 @@ mod tests {
             U256::from_str_radix("6777", 10).unwrap(),
             U256::from_str_radix("6777", 10).unwrap(),
         ];
-        let result = min_fn.operation(&values, None).unwrap();
+        let result = min_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(6776));
 
         // 5382810 ~ 5382810, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
         let values = vec![U256::from_str_radix("9184e72a000", 16).unwrap()];
-        let result = min_fn.operation(&values, None).unwrap();
+        let result = min_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 5382810 ~ 5382813, storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000002
@@ -349,7 +785,7 @@ mod tests {
             U256::from_str_radix("9184e72a000", 16).unwrap(),
             U256::from_str_radix("9184e72a000", 16).unwrap(),
         ];
-        let result = min_fn.operation(&values, None).unwrap();
+        let result = min_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(10000000000000u64));
 
         // 4952100 ~ 4952110, account.0x7f2c6f930306d3aa736b3a6c6a98f512f74036d4.balance
@@ -366,7 +802,7 @@ mod tests {
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
             U256::from_str_radix("41697095938570171564", 10).unwrap(),
         ];
-        let result = min_fn.operation(&values, None).unwrap();
+        let result = min_fn.operation(&values, 32, None).unwrap();
         assert_eq!(result, U256::from(41697095938570171564u128));
     }
 
@@ -381,6 +817,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(
                     Operator::GreaterThanOrEqual,
                     U256::from(4095),
@@ -393,6 +830,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(Operator::Equal, U256::from(6776))),
             )
             .unwrap();
@@ -417,6 +855,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(Operator::NotEqual, U256::from(6776))),
             )
             .unwrap();
@@ -427,6 +866,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(
                     Operator::GreaterThan,
                     U256::from(6776),
@@ -442,6 +882,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(
                     Operator::Equal,
                     U256::from_str("10000000000000").unwrap(),
@@ -462,6 +903,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(
                     Operator::LessThanOrEqual,
                     U256::from_str("10000000000001").unwrap(),
@@ -489,6 +931,7 @@ mod tests {
         let result = count
             .operation(
                 &values,
+                32,
                 Some(FunctionContext::new(
                     Operator::LessThanOrEqual,
                     U256::from_str("41697095938570171564").unwrap(),
@@ -497,4 +940,544 @@ mod tests {
             .unwrap();
         assert_eq!(result, U256::from(8));
     }
+
+    #[test]
+    fn test_median_and_percentile() {
+        // 10 base fees, in gwei
+        let values: Vec<U256> = [12, 9, 35, 11, 10, 14, 250, 13, 10, 15]
+            .into_iter()
+            .map(U256::from)
+            .collect();
+
+        let result = AggregationFunction::MEDIAN
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(result, U256::from(12));
+
+        let ctx = FunctionContext::from_str("p90").unwrap();
+        assert_eq!(ctx, FunctionContext::default().with_percentile(90));
+        assert_eq!(ctx.to_string(), "p90");
+        let result = AggregationFunction::PERCENTILE
+            .operation(&values, 32, Some(ctx))
+            .unwrap();
+        assert_eq!(result, U256::from(35));
+
+        assert!(AggregationFunction::PERCENTILE
+            .operation(&values, 32, Some(FunctionContext::default()))
+            .is_err());
+
+        let ctx: FunctionContext = serde_json::from_str(r#"{"percentile": 50}"#).unwrap();
+        assert_eq!(ctx.percentile, Some(50));
+        assert_eq!(ctx.operator, Operator::None);
+        assert_eq!(
+            AggregationFunction::from_str("percentile").unwrap(),
+            AggregationFunction::PERCENTILE
+        );
+        assert_eq!(
+            AggregationFunction::from_index(5).unwrap(),
+            AggregationFunction::MEDIAN
+        );
+    }
+
+    #[test]
+    fn test_variance_and_stddev() {
+        // balances in wei, beyond the precision of f64
+        let base = U256::from(10).pow(U256::from(21));
+        let values: Vec<U256> = [0u64, 2, 4, 6, 8]
+            .into_iter()
+            .map(|offset| base + U256::from(offset))
+            .collect();
+
+        let result = AggregationFunction::VARIANCE
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(result, U256::from(8));
+        let result = AggregationFunction::STDDEV
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(result, U256::from(3));
+
+        assert_eq!(
+            AggregationFunction::from_str("stddev").unwrap(),
+            AggregationFunction::STDDEV
+        );
+        assert_eq!(AggregationFunction::VARIANCE.to_string(), "variance");
+        assert_eq!(
+            AggregationFunction::from_index(AggregationFunction::to_index(
+                &AggregationFunction::STDDEV
+            ))
+            .unwrap(),
+            AggregationFunction::STDDEV
+        );
+        assert_eq!(
+            AggregationFunction::to_index(&AggregationFunction::VARIANCE),
+            7
+        );
+    }
+
+    #[test]
+    fn test_bloom() {
+        let values = vec![U256::from(1), U256::from(2), U256::from(3)];
+
+        let result = AggregationFunction::BLOOM
+            .operation(&values, 32, None)
+            .unwrap();
+        let filter = match AggregationFunction::BLOOM
+            .operation_with_output(&values, &[], 32, None)
+            .unwrap()
+            .1
+        {
+            Some(AggregationOutput::BloomFilter(filter)) => filter,
+            output => panic!("Unexpected output: {:?}", output),
+        };
+        assert_eq!(filter.len(), 256);
+        assert_eq!(result, U256::from_be_bytes(keccak256(&filter).0));
+
+        let ctx = FunctionContext::from_str("bits512").unwrap();
+        assert_eq!(ctx, FunctionContext::default().with_bloom_bits(512));
+        assert_eq!(ctx.to_string(), "bits512");
+        let filter = match AggregationFunction::BLOOM
+            .operation_with_output(&values, &[], 32, Some(ctx.clone()))
+            .unwrap()
+            .1
+        {
+            Some(AggregationOutput::BloomFilter(filter)) => filter,
+            output => panic!("Unexpected output: {:?}", output),
+        };
+        assert_eq!(filter.len(), 64);
+        assert_eq!(
+            AggregationFunction::BLOOM
+                .operation(&values, 32, Some(ctx))
+                .unwrap(),
+            U256::from_be_bytes(keccak256(&filter).0)
+        );
+
+        assert!(AggregationFunction::BLOOM
+            .operation(
+                &values,
+                32,
+                Some(FunctionContext::default().with_bloom_bits(1000))
+            )
+            .is_err());
+
+        // addresses are hashed as 20 bytes by both entry points
+        let addresses = vec![U256::from(0xdead), U256::from(0xbeef)];
+        let (result, _) = AggregationFunction::BLOOM
+            .operation_with_output(&addresses, &[], 20, None)
+            .unwrap();
+        assert_eq!(
+            AggregationFunction::BLOOM
+                .operation(&addresses, 20, None)
+                .unwrap(),
+            result
+        );
+        assert_ne!(
+            AggregationFunction::BLOOM
+                .operation(&addresses, 32, None)
+                .unwrap(),
+            result
+        );
+        assert_eq!(
+            AggregationFunction::SUM
+                .operation_with_output(&values, &[], 32, None)
+                .unwrap()
+                .1,
+            None
+        );
+        assert_eq!(
+            AggregationFunction::from_index(9).unwrap(),
+            AggregationFunction::BLOOM
+        );
+    }
+
+    #[test]
+    fn test_merkle() {
+        // balances of an account over the sampled blocks
+        let values = vec![U256::from(0), U256::from(5), U256::from(5), U256::from(12)];
+
+        let result = AggregationFunction::MERKLE
+            .operation(&values, 32, None)
+            .unwrap();
+        let (tree, _) = build_values_merkle_tree(&values);
+        assert_eq!(result, U256::from_be_bytes(tree.root().0));
+
+        let proofs = match AggregationFunction::MERKLE
+            .operation_with_output(&values, &[], 32, None)
+            .unwrap()
+            .1
+        {
+            Some(AggregationOutput::ValueProofs(proofs)) => proofs,
+            output => panic!("Unexpected output: {:?}", output),
+        };
+        assert_eq!(proofs.len(), 4);
+        assert_eq!(proofs[2].value, U256::from(5));
+        assert_eq!(proofs[2].leaf, value_leaf(2, &U256::from(5)));
+
+        assert!(AggregationFunction::MERKLE
+            .operation(&[], 32, None)
+            .is_err());
+        assert_eq!(
+            AggregationFunction::from_str("merkle").unwrap(),
+            AggregationFunction::MERKLE
+        );
+        assert_eq!(
+            AggregationFunction::to_index(&AggregationFunction::MERKLE),
+            10
+        );
+    }
+
+    #[test]
+    fn test_twap() {
+        let values = vec![U256::from(100), U256::from(200), U256::from(50)];
+        let timestamps = vec![1704067200, 1704067212, 1704067248];
+
+        let result = AggregationFunction::TWAP
+            .operation_with_timestamps(&values, &timestamps, 32, None)
+            .unwrap();
+        // (100 * 12 + 200 * 36) / 48 = 175, where AVG is 117
+        assert_eq!(result, U256::from(175));
+
+        assert!(AggregationFunction::TWAP
+            .operation(&values, 32, None)
+            .is_err());
+        assert!(AggregationFunction::TWAP
+            .operation_with_timestamps(&values, &[], 32, None)
+            .is_err());
+        // other functions ignore the timestamps
+        assert_eq!(
+            AggregationFunction::AVG
+                .operation_with_timestamps(&values, &timestamps, 32, None)
+                .unwrap(),
+            U256::from(117)
+        );
+        assert_eq!(
+            AggregationFunction::from_str("twap").unwrap(),
+            AggregationFunction::TWAP
+        );
+        assert_eq!(
+            AggregationFunction::from_index(11).unwrap(),
+            AggregationFunction::TWAP
+        );
+    }
+
+    #[test]
+    fn test_delta_aggregations() {
+        let values = vec![U256::from(1000), U256::from(1500), U256::from(700)];
+
+        let first = AggregationFunction::FIRST
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(first, U256::from(1000));
+        let last = AggregationFunction::LAST
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(last, U256::from(700));
+        let net_change = AggregationFunction::NET_CHANGE
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(I256::from_raw(net_change), I256::try_from(-300).unwrap());
+        let max_drawdown = AggregationFunction::MAX_DRAWDOWN
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(max_drawdown, U256::from(800));
+
+        // signed values
+        let ctx = FunctionContext::from_str("signed").unwrap();
+        assert_eq!(ctx, FunctionContext::default().with_signed());
+        assert_eq!(ctx.to_string(), "signed");
+        let values: Vec<U256> = [5, -3, 2]
+            .into_iter()
+            .map(|value: i64| I256::try_from(value).unwrap().into_raw())
+            .collect();
+
+        assert!(FunctionContext::from_str("lteqq.0").is_err());
+
+        // signed comparisons survive a round trip through the string form
+        let count_ctx = FunctionContext::new(Operator::LessThan, U256::ZERO).with_signed();
+        assert_eq!(count_ctx.to_string(), "signed lt.0");
+        assert_eq!(
+            FunctionContext::from_str(&count_ctx.to_string()).unwrap(),
+            count_ctx
+        );
+        let filtered_ctx = count_ctx.clone().with_filter("gt.1".parse().unwrap());
+        assert_eq!(filtered_ctx.to_string(), "signed lt.0 where gt.1");
+        assert_eq!(
+            FunctionContext::from_str(&filtered_ctx.to_string()).unwrap(),
+            filtered_ctx
+        );
+        assert_eq!(
+            AggregationFunction::COUNT
+                .operation(&values, 32, Some(count_ctx))
+                .unwrap(),
+            U256::from(1)
+        );
+        let min = AggregationFunction::MIN
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(min, values[1]);
+        let max = AggregationFunction::MAX
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(max, values[0]);
+        let net_change = AggregationFunction::NET_CHANGE
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(I256::from_raw(net_change), I256::try_from(-3).unwrap());
+        let max_drawdown = AggregationFunction::MAX_DRAWDOWN
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(max_drawdown, U256::from(8));
+
+        // COUNT compares the values as signed, -3 < 0
+        let count = AggregationFunction::COUNT
+            .operation(
+                &values,
+                32,
+                Some(FunctionContext::new(Operator::LessThan, U256::ZERO).with_signed()),
+            )
+            .unwrap();
+        assert_eq!(count, U256::from(1));
+        assert_eq!(
+            AggregationFunction::LAST
+                .operation(&values, 32, Some(ctx.clone()))
+                .unwrap(),
+            values[2]
+        );
+
+        // functions taking the values as unsigned reject signed values
+        for aggregate_fn in [
+            AggregationFunction::SUM,
+            AggregationFunction::AVG,
+            AggregationFunction::MEDIAN,
+            AggregationFunction::BLOOM,
+        ] {
+            assert!(aggregate_fn
+                .operation(&values, 32, Some(ctx.clone()))
+                .is_err());
+            assert!(aggregate_fn
+                .operation_with_output(&values, &[], 32, Some(ctx.clone()))
+                .is_err());
+        }
+
+        let ctx: FunctionContext = serde_json::from_str(r#"{"signed": true}"#).unwrap();
+        assert!(ctx.signed);
+        assert_eq!(
+            serde_json::to_string(&FunctionContext::default()).unwrap(),
+            r#"{"operator":"None","valueToCompare":"0x0"}"#
+        );
+        assert_eq!(
+            AggregationFunction::from_str("net_change").unwrap(),
+            AggregationFunction::NET_CHANGE
+        );
+        assert_eq!(
+            AggregationFunction::from_index(15).unwrap(),
+            AggregationFunction::MAX_DRAWDOWN
+        );
+    }
+
+    #[test]
+    fn test_filtered_aggregations() {
+        // base fees, in wei
+        let gwei = U256::from(1000000000u64);
+        let values: Vec<U256> = [0, 2, 150, 40, 1, 60]
+            .into_iter()
+            .map(|fee: u64| U256::from(fee) * gwei)
+            .collect();
+
+        let ctx = FunctionContext::from_str("where and(gt.1000000000,lt.100000000000)").unwrap();
+        assert_eq!(ctx.to_string(), "where and(gt.1000000000,lt.100000000000)");
+        let result = AggregationFunction::SUM
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(result, U256::from(102) * gwei);
+        let result = AggregationFunction::COUNT
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(result, U256::from(3));
+        let result = AggregationFunction::FIRST
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(result, U256::from(2) * gwei);
+
+        // the filter applies along with the operator of COUNT
+        let ctx = FunctionContext::from_str("gteq.40000000000 where not(eq.150000000000)").unwrap();
+        assert_eq!(ctx.operator, Operator::GreaterThanOrEqual);
+        assert_eq!(
+            ctx.to_string(),
+            "gteq.40000000000 where not(eq.150000000000)"
+        );
+        let result = AggregationFunction::COUNT
+            .operation(&values, 32, Some(ctx))
+            .unwrap();
+        assert_eq!(result, U256::from(2));
+
+        // TWAP would drop the time intervals of the filtered values
+        let ctx = FunctionContext::default().with_filter("lt.100000000000".parse().unwrap());
+        let timestamps = vec![0, 12, 24, 36, 48, 60];
+        assert!(AggregationFunction::TWAP
+            .operation_with_timestamps(&values, &timestamps, 32, Some(ctx.clone()))
+            .is_err());
+        let proofs = match AggregationFunction::MERKLE
+            .operation_with_output(&values, &[], 32, Some(ctx))
+            .unwrap()
+            .1
+        {
+            Some(AggregationOutput::ValueProofs(proofs)) => proofs,
+            output => panic!("Unexpected output: {:?}", output),
+        };
+        assert_eq!(proofs.len(), 5);
+
+        let ctx: FunctionContext =
+            serde_json::from_str(r#"{"filter": "in(1,2)", "signed": true}"#).unwrap();
+        assert_eq!(
+            ctx.filter,
+            Some(Predicate::In(vec![U256::from(1), U256::from(2)]))
+        );
+        assert!(AggregationFunction::SUM
+            .operation(&values, 32, Some(ctx))
+            .is_err());
+
+        // a filter matching no value
+        let ctx = FunctionContext::from_str("where gt.1000000000000").unwrap();
+        for aggregate_fn in [AggregationFunction::SUM, AggregationFunction::AVG] {
+            let err = aggregate_fn
+                .operation(&values, 32, Some(ctx.clone()))
+                .unwrap_err();
+            assert_eq!(err.to_string(), "No values found");
+        }
+        assert_eq!(
+            AggregationFunction::COUNT
+                .operation(&values, 32, Some(ctx.clone()))
+                .unwrap(),
+            U256::ZERO
+        );
+        assert_eq!(
+            AggregationFunction::COUNT_DISTINCT
+                .operation(&values, 32, Some(ctx))
+                .unwrap(),
+            U256::ZERO
+        );
+
+        // signed values are filtered as signed
+        let values: Vec<U256> = [-3, 7, -1, 2]
+            .into_iter()
+            .map(|value: i64| I256::try_from(value).unwrap().into_raw())
+            .collect();
+        let ctx = FunctionContext::from_str("signed where lt.0").unwrap();
+        assert_eq!(
+            AggregationFunction::MAX
+                .operation(&values, 32, Some(ctx.clone()))
+                .unwrap(),
+            values[2]
+        );
+        assert_eq!(
+            AggregationFunction::COUNT
+                .operation(&values, 32, Some(ctx))
+                .unwrap(),
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_fixed_point_avg() {
+        // tx_receipt.success of 3 receipts
+        let values = vec![U256::from(1), U256::from(0), U256::from(1)];
+        let avg_fn = AggregationFunction::AVG;
+        assert_eq!(avg_fn.operation(&values, 32, None).unwrap(), U256::from(1));
+
+        let ctx = FunctionContext::from_str("d4").unwrap();
+        assert_eq!(ctx.decimals, 4);
+        assert_eq!(ctx.rounding, Rounding::HalfUp);
+        assert_eq!(ctx.to_string(), "d4");
+        assert_eq!(
+            avg_fn.operation(&values, 32, Some(ctx)).unwrap(),
+            U256::from(6667)
+        );
+
+        let ctx = FunctionContext::from_str("d4.floor").unwrap();
+        assert_eq!(ctx.to_string(), "d4.floor");
+        assert_eq!(
+            avg_fn.operation(&values, 32, Some(ctx)).unwrap(),
+            U256::from(6666)
+        );
+
+        // the fixed point applies to the filtered values
+        let ctx = FunctionContext::from_str("d2.ceil where lt.2").unwrap();
+        assert_eq!(ctx.to_string(), "d2.ceil where lt.2");
+        let values = vec![U256::from(1), U256::from(0), U256::from(5), U256::from(0)];
+        assert_eq!(
+            avg_fn.operation(&values, 32, Some(ctx)).unwrap(),
+            U256::from(34)
+        );
+
+        let ctx: FunctionContext =
+            serde_json::from_str(r#"{"decimals": 18, "rounding": "ceil"}"#).unwrap();
+        assert_eq!(
+            ctx,
+            FunctionContext::default().with_fixed_point(18, Rounding::Ceil)
+        );
+        assert_eq!(
+            serde_json::to_string(&ctx).unwrap(),
+            r#"{"operator":"None","valueToCompare":"0x0","decimals":18,"rounding":"ceil"}"#
+        );
+        assert!(FunctionContext::from_str("d4.up").is_err());
+        assert!(FunctionContext::from_str("d256").is_err());
+    }
+
+    #[test]
+    fn test_count_distinct_and_histogram() {
+        // tx.to of the transactions in a block
+        let values: Vec<U256> = [3, 1, 3, 3, 2, 1].into_iter().map(U256::from).collect();
+        let result = AggregationFunction::COUNT_DISTINCT
+            .operation(&values, 32, None)
+            .unwrap();
+        assert_eq!(result, U256::from(3));
+        assert_eq!(
+            AggregationFunction::COUNT_DISTINCT
+                .operation_with_output(&values, &[], 32, None)
+                .unwrap()
+                .1,
+            None
+        );
+
+        let ctx = FunctionContext::from_str("buckets(2, 3)").unwrap();
+        assert_eq!(ctx.boundaries, vec![U256::from(2), U256::from(3)]);
+        assert_eq!(ctx.to_string(), "buckets(2,3)");
+        let counts = vec![U256::from(2), U256::from(1), U256::from(3)];
+        let commitment: Vec<u8> = counts
+            .iter()
+            .flat_map(|count| count.to_be_bytes::<32>())
+            .collect();
+        let result = AggregationFunction::HISTOGRAM
+            .operation(&values, 32, Some(ctx.clone()))
+            .unwrap();
+        assert_eq!(result, U256::from_be_bytes(keccak256(commitment).0));
+        assert_eq!(
+            AggregationFunction::HISTOGRAM
+                .operation_with_output(&values, &[], 32, Some(ctx))
+                .unwrap()
+                .1,
+            Some(AggregationOutput::BucketCounts(counts))
+        );
+
+        // buckets of the filtered values
+        let ctx = FunctionContext::from_str("buckets(2,3) where gt.1").unwrap();
+        assert_eq!(ctx.to_string(), "buckets(2,3) where gt.1");
+        assert_eq!(
+            AggregationFunction::HISTOGRAM
+                .operation_with_output(&values, &[], 32, Some(ctx))
+                .unwrap()
+                .1,
+            Some(AggregationOutput::BucketCounts(vec![
+                U256::ZERO,
+                U256::from(1),
+                U256::from(3)
+            ]))
+        );
+
+        assert!(AggregationFunction::HISTOGRAM
+            .operation(&values, 32, None)
+            .is_err());
+        assert!(FunctionContext::from_str("buckets()").is_err());
+    }
 }
@@ -0,0 +1,90 @@
+//! Outputs of aggregations that are committed by their result, but too large to be the result.
+//!
+//! - BLOOM outputs the bloom filter, whose hash is the result.
+//! - MERKLE outputs the inclusion proof of each value, against the root that is the result.
+//! - HISTOGRAM outputs the number of values in each bucket, whose hash is the result.
+
+use alloy::{
+    dyn_abi::DynSolValue,
+    primitives::{Bytes, B256, U256},
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::merkle_tree::build_values_merkle_tree;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AggregationOutput {
+    /// Bloom filter of BLOOM
+    BloomFilter(Bytes),
+    /// Inclusion proofs of the values of MERKLE, in order of the values
+    ValueProofs(Vec<ValueProof>),
+    /// Number of values in each bucket of HISTOGRAM, in order of the buckets
+    BucketCounts(Vec<U256>),
+}
+
+/// Inclusion proof of a value in the values merkle tree of MERKLE
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueProof {
+    /// Position of the value in the sampled values
+    pub index: u64,
+    pub value: U256,
+    /// Leaf of the value, `keccak256(index ++ value)`
+    pub leaf: B256,
+    pub proof: Vec<B256>,
+}
+
+/// Root of the values merkle tree, along with the inclusion proofs of all the values
+pub fn value_proofs(values: &[U256]) -> Result<(U256, Vec<ValueProof>)> {
+    if values.is_empty() {
+        bail!("No values found");
+    }
+
+    let (tree, leaves) = build_values_merkle_tree(values);
+    let proofs = values
+        .iter()
+        .zip(leaves)
+        .enumerate()
+        .map(|(index, (value, leaf))| {
+            let proof = tree
+                .get_proof(&DynSolValue::FixedBytes(leaf, 32))
+                .map_err(|e| anyhow::anyhow!("Failed to get value proof: {:?}", e))?;
+            Ok(ValueProof {
+                index: index as u64,
+                value: *value,
+                leaf,
+                proof,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((U256::from_be_bytes(tree.root().0), proofs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_proofs() {
+        let values = vec![U256::from(7), U256::from(7), U256::from(100)];
+        let (root, proofs) = value_proofs(&values).unwrap();
+        let (tree, _) = build_values_merkle_tree(&values);
+        assert_eq!(root, U256::from_be_bytes(tree.root().0));
+        assert_eq!(proofs.len(), 3);
+        for (index, proof) in proofs.into_iter().enumerate() {
+            assert_eq!(proof.index, index as u64);
+            assert_eq!(proof.value, values[index]);
+            assert!(tree.verify_proof(&DynSolValue::FixedBytes(proof.leaf, 32), proof.proof));
+        }
+
+        assert!(value_proofs(&[]).is_err());
+
+        let output = AggregationOutput::BloomFilter(Bytes::from(vec![0u8; 8]));
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            r#"{"bloomFilter":"0x0000000000000000"}"#
+        );
+    }
+}
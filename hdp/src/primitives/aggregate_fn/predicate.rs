@@ -0,0 +1,511 @@
+//! Predicates filtering the values of an aggregation.
+//!
+//! A predicate is written as an expression over the value:
+//! - `{operator}.{value}` compares the value, e.g. `gt.1000000000`, with the operators of [`Operator`].
+//! - `between({low},{high})` is true for values from `low` to `high` (inclusive).
+//! - `in({value},{value},...)` is true for values in the set.
+//! - `and(...)`, `or(...)` and `not(...)` combine predicates.
+//!
+//! Values are compared as uint256, or as two's complement int256 with `signed` in the context,
+//! and are written in decimal or `0x` prefixed hex. Ranges must be ascending in that order.
+//!
+//! The predicate is committed in the computation with a compact prefix encoding, so the Cairo
+//! program can evaluate the same predicate:
+//! - compare: `0x01 ++ u8 operator index ++ u256 value`
+//! - between: `0x02 ++ u256 low ++ u256 high`
+//! - in: `0x03 ++ u16 count ++ u256 values`, in ascending order without duplicates
+//! - and / or: `0x04` / `0x05` `++ u8 count ++ predicates`
+//! - not: `0x06 ++ predicate`
+//!
+//! Numbers are big endian, and every predicate has a single encoding.
+
+use std::{fmt::Display, str::FromStr};
+
+use alloy::primitives::U256;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::integer::{order_key, Operator};
+
+/// Maximum nesting depth of a predicate, bounding its evaluation in Cairo
+pub const MAX_PREDICATE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Predicate {
+    Compare(Operator, U256),
+    /// Inclusive range of values
+    Between(U256, U256),
+    /// Set of values, in ascending order without duplicates
+    In(Vec<U256>),
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Set of values, sorted and deduplicated
+    pub fn in_set(mut values: Vec<U256>) -> Self {
+        values.sort();
+        values.dedup();
+        Predicate::In(values)
+    }
+
+    /// Whether the value satisfies the predicate, comparing the values as int256 if signed
+    pub fn evaluate(&self, value: &U256, signed: bool) -> bool {
+        let key = |value: &U256| order_key(value, signed);
+        match self {
+            Predicate::Compare(operator, to_compare) => {
+                let (value, to_compare) = (key(value), key(to_compare));
+                match operator {
+                    Operator::Equal => value == to_compare,
+                    Operator::NotEqual => value != to_compare,
+                    Operator::GreaterThan => value > to_compare,
+                    Operator::GreaterThanOrEqual => value >= to_compare,
+                    Operator::LessThan => value < to_compare,
+                    Operator::LessThanOrEqual => value <= to_compare,
+                    Operator::None => true,
+                }
+            }
+            Predicate::Between(low, high) => key(low) <= key(value) && key(value) <= key(high),
+            Predicate::In(values) => values.binary_search(value).is_ok(),
+            Predicate::And(predicates) => predicates.iter().all(|p| p.evaluate(value, signed)),
+            Predicate::Or(predicates) => predicates.iter().any(|p| p.evaluate(value, signed)),
+            Predicate::Not(predicate) => !predicate.evaluate(value, signed),
+        }
+    }
+
+    /// Values satisfying the predicate, in order
+    pub fn filter(&self, values: &[U256], signed: bool) -> Vec<U256> {
+        values
+            .iter()
+            .filter(|value| self.evaluate(value, signed))
+            .copied()
+            .collect()
+    }
+
+    /// Check that the predicate has a single encoding and a bounded depth
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at_depth(1)
+    }
+
+    /// Check that the ranges are ascending, comparing the values as int256 if signed
+    pub fn validate_ranges(&self, signed: bool) -> Result<()> {
+        match self {
+            Predicate::Between(low, high) => {
+                if order_key(low, signed) > order_key(high, signed) {
+                    bail!("Invalid range: between({},{})", low, high);
+                }
+            }
+            Predicate::And(predicates) | Predicate::Or(predicates) => {
+                for predicate in predicates {
+                    predicate.validate_ranges(signed)?;
+                }
+            }
+            Predicate::Not(predicate) => predicate.validate_ranges(signed)?,
+            Predicate::Compare(..) | Predicate::In(_) => {}
+        }
+        Ok(())
+    }
+
+    fn validate_at_depth(&self, depth: usize) -> Result<()> {
+        if depth > MAX_PREDICATE_DEPTH {
+            bail!("Predicate is nested deeper than {}", MAX_PREDICATE_DEPTH);
+        }
+        match self {
+            Predicate::Compare(Operator::None, _) => bail!("Predicate needs logical operator"),
+            // ranges are ordered by the signedness of the values, see [`Predicate::validate_ranges`]
+            Predicate::Compare(..) | Predicate::Between(..) => {}
+            Predicate::In(values) => {
+                if values.is_empty() || values.len() > u16::MAX as usize {
+                    bail!("Invalid set size: {}", values.len());
+                }
+                if values.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    bail!("Set values must be in ascending order without duplicates");
+                }
+            }
+            Predicate::And(predicates) | Predicate::Or(predicates) => {
+                if predicates.is_empty() || predicates.len() > u8::MAX as usize {
+                    bail!("Invalid number of predicates: {}", predicates.len());
+                }
+                for predicate in predicates {
+                    predicate.validate_at_depth(depth + 1)?;
+                }
+            }
+            Predicate::Not(predicate) => predicate.validate_at_depth(depth + 1)?,
+        }
+        Ok(())
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        self.validate()?;
+        let mut encoded = vec![];
+        self.encode_into(&mut encoded);
+        Ok(encoded)
+    }
+
+    fn encode_into(&self, encoded: &mut Vec<u8>) {
+        match self {
+            Predicate::Compare(operator, value) => {
+                encoded.push(1);
+                encoded.push(Operator::to_index(operator));
+                encoded.extend_from_slice(&value.to_be_bytes::<32>());
+            }
+            Predicate::Between(low, high) => {
+                encoded.push(2);
+                encoded.extend_from_slice(&low.to_be_bytes::<32>());
+                encoded.extend_from_slice(&high.to_be_bytes::<32>());
+            }
+            Predicate::In(values) => {
+                encoded.push(3);
+                encoded.extend_from_slice(&(values.len() as u16).to_be_bytes());
+                for value in values {
+                    encoded.extend_from_slice(&value.to_be_bytes::<32>());
+                }
+            }
+            Predicate::And(predicates) | Predicate::Or(predicates) => {
+                encoded.push(if matches!(self, Predicate::And(_)) {
+                    4
+                } else {
+                    5
+                });
+                encoded.push(predicates.len() as u8);
+                for predicate in predicates {
+                    predicate.encode_into(encoded);
+                }
+            }
+            Predicate::Not(predicate) => {
+                encoded.push(6);
+                predicate.encode_into(encoded);
+            }
+        }
+    }
+
+    pub fn decode(encoded: &[u8]) -> Result<Self> {
+        let mut reader = Reader(encoded);
+        let predicate = reader.predicate(1)?;
+        if !reader.0.is_empty() {
+            bail!("Trailing bytes after predicate");
+        }
+        predicate.validate()?;
+        Ok(predicate)
+    }
+}
+
+/// Reader of an encoded predicate
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            bail!("Unexpected end of predicate");
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u256(&mut self) -> Result<U256> {
+        Ok(U256::from_be_slice(self.take(32)?))
+    }
+
+    fn predicate(&mut self, depth: usize) -> Result<Predicate> {
+        if depth > MAX_PREDICATE_DEPTH {
+            bail!("Predicate is nested deeper than {}", MAX_PREDICATE_DEPTH);
+        }
+        match self.take(1)?[0] {
+            1 => {
+                let operator = Operator::from_index(self.take(1)?[0])?;
+                Ok(Predicate::Compare(operator, self.u256()?))
+            }
+            2 => Ok(Predicate::Between(self.u256()?, self.u256()?)),
+            3 => {
+                let count = u16::from_be_bytes(self.take(2)?.try_into().unwrap());
+                let values = (0..count).map(|_| self.u256()).collect::<Result<_>>()?;
+                Ok(Predicate::In(values))
+            }
+            tag @ (4 | 5) => {
+                let count = self.take(1)?[0];
+                let predicates = (0..count)
+                    .map(|_| self.predicate(depth + 1))
+                    .collect::<Result<_>>()?;
+                Ok(if tag == 4 {
+                    Predicate::And(predicates)
+                } else {
+                    Predicate::Or(predicates)
+                })
+            }
+            6 => Ok(Predicate::Not(Box::new(self.predicate(depth + 1)?))),
+            tag => bail!("Unknown predicate tag: {}", tag),
+        }
+    }
+}
+
+/// Split the arguments of an expression at the top level commas
+fn split_arguments(arguments: &str) -> Result<Vec<&str>> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or(anyhow::anyhow!("Unbalanced parentheses"))?
+            }
+            ',' if depth == 0 => {
+                parts.push(arguments[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        bail!("Unbalanced parentheses");
+    }
+    parts.push(arguments[start..].trim());
+    Ok(parts)
+}
+
+impl FromStr for Predicate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        // bounds the recursion, as a predicate is at least as deep as its parentheses
+        let parentheses_depth = s
+            .chars()
+            .scan(0i64, |depth, c| {
+                *depth += match c {
+                    '(' => 1,
+                    ')' => -1,
+                    _ => 0,
+                };
+                Some(*depth)
+            })
+            .max()
+            .unwrap_or(0);
+        if parentheses_depth > MAX_PREDICATE_DEPTH as i64 {
+            bail!("Predicate is nested deeper than {}", MAX_PREDICATE_DEPTH);
+        }
+        let predicate = match s.split_once('(') {
+            Some((name, rest)) => {
+                let arguments = match rest.strip_suffix(')') {
+                    Some(arguments) => split_arguments(arguments)?,
+                    None => bail!("Invalid predicate: {}", s),
+                };
+                match name.trim() {
+                    "between" => match arguments[..] {
+                        [low, high] => {
+                            Predicate::Between(U256::from_str(low)?, U256::from_str(high)?)
+                        }
+                        _ => bail!("between takes 2 values"),
+                    },
+                    "in" => Predicate::in_set(
+                        arguments
+                            .into_iter()
+                            .map(U256::from_str)
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    "and" => Predicate::And(
+                        arguments
+                            .into_iter()
+                            .map(Predicate::from_str)
+                            .collect::<Result<_>>()?,
+                    ),
+                    "or" => Predicate::Or(
+                        arguments
+                            .into_iter()
+                            .map(Predicate::from_str)
+                            .collect::<Result<_>>()?,
+                    ),
+                    "not" => match arguments[..] {
+                        [predicate] => Predicate::Not(Box::new(predicate.parse()?)),
+                        _ => bail!("not takes 1 predicate"),
+                    },
+                    name => bail!("Unknown predicate: {}", name),
+                }
+            }
+            None => match s.split_once('.') {
+                Some((operator, value)) => {
+                    Predicate::Compare(parse_operator(operator)?, U256::from_str(value)?)
+                }
+                None => bail!("Invalid predicate: {}", s),
+            },
+        };
+        predicate.validate()?;
+        Ok(predicate)
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |predicates: &[Predicate]| {
+            predicates
+                .iter()
+                .map(|predicate| predicate.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            Predicate::Compare(operator, value) => write!(f, "{}.{}", operator, value),
+            Predicate::Between(low, high) => write!(f, "between({},{})", low, high),
+            Predicate::In(values) => write!(
+                f,
+                "in({})",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Predicate::And(predicates) => write!(f, "and({})", join(predicates)),
+            Predicate::Or(predicates) => write!(f, "or({})", join(predicates)),
+            Predicate::Not(predicate) => write!(f, "not({})", predicate),
+        }
+    }
+}
+
+impl TryFrom<String> for Predicate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Predicate::from_str(&value)
+    }
+}
+
+impl From<Predicate> for String {
+    fn from(value: Predicate) -> Self {
+        value.to_string()
+    }
+}
+
+/// Operators are written as they are displayed, so `lteq` is accepted along with `lteq=`
+fn parse_operator(operator: &str) -> Result<Operator> {
+    match operator {
+        "lteq" => Ok(Operator::LessThanOrEqual),
+        operator => Operator::from_str(operator),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predicate_from_str() {
+        // between 1 and 100 gwei, excluding 50 gwei
+        let predicate: Predicate = "and(gt.1000000000, lt.100000000000, not(in(50000000000)))"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            predicate,
+            Predicate::And(vec![
+                Predicate::Compare(Operator::GreaterThan, U256::from(1000000000u64)),
+                Predicate::Compare(Operator::LessThan, U256::from(100000000000u64)),
+                Predicate::Not(Box::new(Predicate::In(vec![U256::from(50000000000u64)]))),
+            ])
+        );
+        assert_eq!(
+            predicate.to_string(),
+            "and(gt.1000000000,lt.100000000000,not(in(50000000000)))"
+        );
+        assert_eq!(
+            predicate.to_string().parse::<Predicate>().unwrap(),
+            predicate
+        );
+
+        assert!(predicate.evaluate(&U256::from(2000000000u64), false));
+        assert!(!predicate.evaluate(&U256::from(50000000000u64), false));
+        assert!(!predicate.evaluate(&U256::from(1000000000u64), false));
+
+        // sets are canonical
+        assert_eq!(
+            "in(3,0x1,3,2)".parse::<Predicate>().unwrap(),
+            Predicate::In(vec![U256::from(1), U256::from(2), U256::from(3)])
+        );
+        let predicate: Predicate = "or(between(10,20),lteq.2)".parse().unwrap();
+        assert_eq!(
+            predicate.filter(&[1, 5, 10, 15, 20, 25].map(U256::from), false),
+            [1, 10, 15, 20].map(U256::from)
+        );
+
+        assert!("between(20,10)"
+            .parse::<Predicate>()
+            .unwrap()
+            .validate_ranges(false)
+            .is_err());
+        assert!("and()".parse::<Predicate>().is_err());
+        assert!("not(gt.1,gt.2)".parse::<Predicate>().is_err());
+        assert!("none.1".parse::<Predicate>().is_err());
+        assert!("xor(gt.1)".parse::<Predicate>().is_err());
+        assert!("and(gt.1".parse::<Predicate>().is_err());
+        assert!("not(not(not(not(not(not(not(not(eq.1))))))))"
+            .parse::<Predicate>()
+            .is_err());
+        assert!("not(".repeat(100000).parse::<Predicate>().is_err());
+
+        let predicate: Predicate = serde_json::from_str(r#""between(1,2)""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&predicate).unwrap(),
+            r#""between(1,2)""#
+        );
+    }
+
+    #[test]
+    fn test_signed_predicate() {
+        let minus_one = U256::MAX;
+        let minus_ten = U256::MAX - U256::from(9);
+        let values = [minus_ten, minus_one, U256::ZERO, U256::from(5)];
+
+        // lt.0 matches the negative values
+        let predicate: Predicate = "lt.0".parse().unwrap();
+        assert_eq!(predicate.filter(&values, true), [minus_ten, minus_one]);
+        assert_eq!(predicate.filter(&values, false), []);
+
+        // a range from -1 to 5 is only ascending for signed values
+        let predicate: Predicate = format!("between({},5)", minus_one).parse().unwrap();
+        assert!(predicate.validate_ranges(true).is_ok());
+        assert!(predicate.validate_ranges(false).is_err());
+        assert_eq!(
+            predicate.filter(&values, true),
+            [minus_one, U256::ZERO, U256::from(5)]
+        );
+        assert!(Predicate::Not(Box::new(predicate))
+            .validate_ranges(false)
+            .is_err());
+
+        // sets do not depend on the order
+        let predicate = Predicate::in_set(vec![minus_one, U256::from(5)]);
+        assert_eq!(
+            predicate.filter(&values, true),
+            predicate.filter(&values, false)
+        );
+    }
+
+    #[test]
+    fn test_predicate_encoding() {
+        let predicate: Predicate = "and(gteq.5,or(in(7,9),not(between(1,3))))".parse().unwrap();
+        let encoded = predicate.encode().unwrap();
+        // and: 2, gteq: 34, or: 2, in: 3 + 64, not: 1, between: 65
+        assert_eq!(encoded.len(), 2 + 34 + 2 + 67 + 1 + 65);
+        assert_eq!(&encoded[..4], &[4, 2, 1, 4]);
+        assert_eq!(Predicate::decode(&encoded).unwrap(), predicate);
+
+        // non canonical encodings are rejected
+        let unsorted = Predicate::In(vec![U256::from(9), U256::from(7)]);
+        assert!(unsorted.encode().is_err());
+        let mut encoded = Predicate::In(vec![U256::from(7), U256::from(9)])
+            .encode()
+            .unwrap();
+        encoded.swap(34, 66);
+        assert!(Predicate::decode(&encoded).is_err());
+
+        let encoded = predicate.encode().unwrap();
+        assert!(Predicate::decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(Predicate::decode(&[encoded.clone(), vec![0]].concat()).is_err());
+        assert!(Predicate::decode(&[7]).is_err());
+        assert!(Predicate::decode(&[6; 100000]).is_err());
+    }
+}
@@ -130,6 +130,11 @@ impl ChainId {
         }
     }
 
+    /// Whether the chain is a Starknet chain
+    pub fn is_starknet(&self) -> bool {
+        matches!(self, ChainId::StarknetMainnet | ChainId::StarknetSepolia)
+    }
+
     pub fn to_be_bytes(&self) -> [u8; 16] {
         self.to_numeric_id().to_be_bytes()
     }
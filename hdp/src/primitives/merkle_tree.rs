@@ -40,6 +40,33 @@ pub fn build_task_merkle_tree(tasks_commitments: &[B256]) -> StandardMerkleTree
     StandardMerkleTree::of(&task_leaves)
 }
 
+/// Leaf of a value in a values merkle tree, committing to its position: `keccak256(index ++ value)`
+pub fn value_leaf(index: u64, value: &U256) -> B256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(U256::from(index).to_be_bytes::<32>());
+    hasher.update(value.to_be_bytes::<32>());
+    hasher.finalize()
+}
+
+/// Build values merkle tree by providing the values in order,
+/// And returning tree structure and the leaf of each value
+pub fn build_values_merkle_tree(values: &[U256]) -> (StandardMerkleTree, Vec<B256>) {
+    if values.is_empty() {
+        panic!("values have to be non empty to construct values merkle tree")
+    }
+    let leaves: Vec<B256> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| value_leaf(index as u64, value))
+        .collect();
+    let values_leaves: Vec<DynSolValue> = leaves
+        .iter()
+        .map(|leaf| DynSolValue::FixedBytes(*leaf, 32))
+        .collect();
+    let tree = StandardMerkleTree::of(&values_leaves);
+    (tree, leaves)
+}
+
 #[cfg(test)]
 mod tests {
     use alloy::primitives::b256;
@@ -64,4 +91,24 @@ mod tests {
             )]
         )
     }
+
+    #[test]
+    fn test_build_values_merkle_tree() {
+        // same values at different positions are different leaves
+        let values = vec![U256::from(10), U256::from(20), U256::from(10)];
+        let (tree, leaves) = build_values_merkle_tree(&values);
+        assert_eq!(leaves.len(), 3);
+        assert_ne!(leaves[0], leaves[2]);
+        assert_eq!(leaves[1], value_leaf(1, &U256::from(20)));
+        for leaf in leaves {
+            let leaf = DynSolValue::FixedBytes(leaf, 32);
+            let proof = tree.get_proof(&leaf).unwrap();
+            assert!(tree.verify_proof(&leaf, proof));
+        }
+
+        // order of the values is committed
+        let (reordered, _) =
+            build_values_merkle_tree(&[U256::from(20), U256::from(10), U256::from(10)]);
+        assert_ne!(tree.root(), reordered.root());
+    }
 }
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    account::ProcessedAccount, header::ProcessedHeader, mmr::MMRMeta, receipt::ProcessedReceipt,
-    storage::ProcessedStorage, transaction::ProcessedTransaction,
+    account::ProcessedAccount, bytecode::ProcessedBytecode, header::ProcessedHeader, mmr::MMRMeta,
+    receipt::ProcessedReceipt, storage::ProcessedStorage, transaction::ProcessedTransaction,
+    transaction_count::ProcessedTransactionCount,
 };
 
 /// Provider should fetch all the proofs and rlp values from given keys.
@@ -15,4 +16,8 @@ pub struct ProcessedBlockProofs {
     pub storages: Vec<ProcessedStorage>,
     pub transactions: Vec<ProcessedTransaction>,
     pub transaction_receipts: Vec<ProcessedReceipt>,
+    #[serde(default)]
+    pub bytecodes: Vec<ProcessedBytecode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transaction_counts: Vec<ProcessedTransactionCount>,
 }
@@ -0,0 +1,20 @@
+//! Processed bytecode type
+//! This contains the code of an account, keyed by the code hash it was checked against.
+
+use alloy::primitives::{keccak256, Bytes, B256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct ProcessedBytecode {
+    pub code_hash: B256,
+    pub code: Bytes,
+}
+
+impl ProcessedBytecode {
+    pub fn new(code: Bytes) -> Self {
+        ProcessedBytecode {
+            code_hash: keccak256(&code),
+            code,
+        }
+    }
+}
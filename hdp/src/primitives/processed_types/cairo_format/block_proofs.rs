@@ -5,8 +5,8 @@ use crate::primitives::processed_types::{
 };
 
 use super::{
-    AsCairoFormat, ProcessedAccount, ProcessedHeader, ProcessedReceipt, ProcessedStorage,
-    ProcessedTransaction,
+    AsCairoFormat, ProcessedAccount, ProcessedBytecode, ProcessedHeader, ProcessedReceipt,
+    ProcessedStorage, ProcessedTransaction, ProcessedTransactionCount,
 };
 
 impl AsCairoFormat for BaseProcessedBlockProofs {
@@ -40,6 +40,16 @@ impl AsCairoFormat for BaseProcessedBlockProofs {
                 .iter()
                 .map(|receipt| receipt.as_cairo_format())
                 .collect(),
+            bytecodes: self
+                .bytecodes
+                .iter()
+                .map(|bytecode| bytecode.as_cairo_format())
+                .collect(),
+            transaction_counts: self
+                .transaction_counts
+                .iter()
+                .map(|count| count.as_cairo_format())
+                .collect(),
         }
     }
 }
@@ -52,4 +62,29 @@ pub struct ProcessedBlockProofs {
     pub storages: Vec<ProcessedStorage>,
     pub transactions: Vec<ProcessedTransaction>,
     pub transaction_receipts: Vec<ProcessedReceipt>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bytecodes: Vec<ProcessedBytecode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transaction_counts: Vec<ProcessedTransactionCount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optional_proofs_are_omitted() {
+        let proofs: ProcessedBlockProofs = serde_json::from_str(
+            r#"{"mmr_metas":[],"headers":[],"accounts":[],"storages":[],"transactions":[],"transaction_receipts":[]}"#,
+        )
+        .unwrap();
+        assert!(proofs.bytecodes.is_empty());
+
+        // programs without code fields keep the input format they had before bytecodes
+        let serialized = serde_json::to_string(&proofs).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"mmr_metas":[],"headers":[],"accounts":[],"storages":[],"transactions":[],"transaction_receipts":[]}"#
+        );
+    }
 }
@@ -0,0 +1,58 @@
+//! Processed bytecode type
+//! This contains the processed bytecode type and its conversion to cairo format.
+
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet_crypto::Felt;
+
+use crate::primitives::processed_types::bytecode::ProcessedBytecode as BaseProcessedBytecode;
+
+use super::{felt_vec_unit::FieldElementVectorUnit, traits::AsCairoFormat};
+
+impl AsCairoFormat for BaseProcessedBytecode {
+    type Output = ProcessedBytecode;
+
+    fn as_cairo_format(&self) -> Self::Output {
+        let felts_unit = FieldElementVectorUnit::from_bytes(&self.code).unwrap();
+        ProcessedBytecode {
+            code_hash: self.code_hash,
+            code: felts_unit.felts,
+            code_bytes_len: felts_unit.bytes_len,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct ProcessedBytecode {
+    pub code_hash: B256,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub code: Vec<Felt>,
+    /// code_bytes_len is the byte( 8 bit ) length of the code
+    pub code_bytes_len: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{bytes, keccak256};
+
+    use super::*;
+
+    #[test]
+    fn test_bytecode_as_cairo_format() {
+        let code = bytes!("6080604052348015600f57600080fd");
+        let bytecode = BaseProcessedBytecode::new(code.clone());
+        assert_eq!(bytecode.code_hash, keccak256(&code));
+
+        let bytecode_in_felts = bytecode.as_cairo_format();
+        assert_eq!(bytecode_in_felts.code_hash, bytecode.code_hash);
+        assert_eq!(bytecode_in_felts.code_bytes_len, 15);
+        assert_eq!(bytecode_in_felts.code.len(), 2);
+        assert_eq!(
+            bytecode_in_felts.code[0],
+            Felt::from(u64::from_le_bytes(code[..8].try_into().unwrap()))
+        );
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod account;
 pub mod block_proofs;
+pub mod bytecode;
 pub mod datalake_compute;
 pub mod felt_vec_unit;
 pub mod header;
@@ -14,9 +15,11 @@ pub mod storage;
 pub mod task;
 pub mod traits;
 pub mod transaction;
+pub mod transaction_count;
 
 pub use account::*;
 pub use block_proofs::*;
+pub use bytecode::*;
 pub use datalake_compute::*;
 pub use felt_vec_unit::*;
 pub use header::*;
@@ -28,3 +31,4 @@ pub use storage::*;
 pub use task::*;
 pub use traits::*;
 pub use transaction::*;
+pub use transaction_count::*;
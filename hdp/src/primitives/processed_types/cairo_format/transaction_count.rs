@@ -0,0 +1,35 @@
+//! Processed transaction count type
+//! This contains the processed transaction count type and its conversion to cairo format.
+
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::processed_types::transaction_count::ProcessedTransactionCount as BaseProcessedTransactionCount;
+
+use super::{traits::AsCairoFormat, transaction::ProcessedTransaction};
+
+impl AsCairoFormat for BaseProcessedTransactionCount {
+    type Output = ProcessedTransactionCount;
+
+    fn as_cairo_format(&self) -> Self::Output {
+        ProcessedTransactionCount {
+            block_number: self.block_number,
+            count: self.count,
+            transactions_root: self.transactions_root,
+            last_transaction: self
+                .last_transaction
+                .as_ref()
+                .map(|transaction| transaction.as_cairo_format()),
+            non_inclusion: self.non_inclusion.as_cairo_format(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct ProcessedTransactionCount {
+    pub block_number: u64,
+    pub count: u64,
+    pub transactions_root: B256,
+    pub last_transaction: Option<ProcessedTransaction>,
+    pub non_inclusion: ProcessedTransaction,
+}
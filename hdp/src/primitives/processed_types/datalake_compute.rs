@@ -1,4 +1,6 @@
 use alloy::primitives::{Bytes, B256, U256};
+
+use crate::primitives::aggregate_fn::output::AggregationOutput;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
@@ -21,6 +23,9 @@ pub struct ProcessedDatalakeCompute {
     pub datalake_type: u8,
     // ex. "header", "account", "storage"
     pub property_type: u8,
+    /// output committed by the result, e.g. the bloom filter of BLOOM or the value proofs of MERKLE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation_output: Option<AggregationOutput>,
 }
 
 impl ProcessedDatalakeCompute {
@@ -45,6 +50,15 @@ impl ProcessedDatalakeCompute {
             encoded_datalake,
             datalake_type,
             property_type,
+            aggregation_output: None,
         }
     }
+
+    pub fn with_aggregation_output(
+        mut self,
+        aggregation_output: Option<AggregationOutput>,
+    ) -> Self {
+        self.aggregation_output = aggregation_output;
+        self
+    }
 }
@@ -2,6 +2,7 @@ pub mod cairo_format;
 
 pub mod account;
 pub mod block_proofs;
+pub mod bytecode;
 pub mod datalake_compute;
 pub mod header;
 pub mod mmr;
@@ -13,4 +14,5 @@ pub mod receipt;
 pub mod storage;
 pub mod task;
 pub mod transaction;
+pub mod transaction_count;
 pub mod uint256;
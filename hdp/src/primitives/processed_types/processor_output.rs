@@ -1,6 +1,8 @@
 use alloy::primitives::B256;
 use serde::Serialize;
 
+use crate::primitives::aggregate_fn::output::AggregationOutput;
+
 use super::mmr::MMRMeta;
 
 #[derive(Debug, Serialize)]
@@ -21,6 +23,9 @@ pub struct ProcessorOutput {
     pub tasks_root: B256,
     /// mmr metas related to processed tasks
     pub mmr_metas: Vec<MMRMeta>,
+    /// outputs committed by the results, e.g. value proofs of MERKLE, in order of the tasks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aggregation_outputs: Vec<Option<AggregationOutput>>,
 }
 
 impl ProcessorOutput {
@@ -43,6 +48,18 @@ impl ProcessorOutput {
             results_root,
             tasks_root,
             mmr_metas,
+            aggregation_outputs: vec![],
+        }
+    }
+
+    /// Set the aggregation outputs of the tasks, omitted if no task has one
+    pub fn with_aggregation_outputs(
+        mut self,
+        aggregation_outputs: Vec<Option<AggregationOutput>>,
+    ) -> Self {
+        if aggregation_outputs.iter().any(Option::is_some) {
+            self.aggregation_outputs = aggregation_outputs;
         }
+        self
     }
 }
@@ -69,5 +69,11 @@ impl ProcessorInput {
             self.tasks_root,
             self.proofs.mmr_metas.clone(),
         )
+        .with_aggregation_outputs(
+            self.tasks
+                .iter()
+                .map(|task| task.get_aggregation_output())
+                .collect(),
+        )
     }
 }
@@ -1,6 +1,8 @@
 use alloy::primitives::B256;
 use serde::{Deserialize, Serialize};
 
+use crate::primitives::aggregate_fn::output::AggregationOutput;
+
 use super::{datalake_compute::ProcessedDatalakeCompute, module::ProcessedModule};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -53,4 +55,13 @@ impl ProcessedTask {
             ProcessedTask::Module(module) => module.result_proof.clone(),
         }
     }
+
+    pub fn get_aggregation_output(&self) -> Option<AggregationOutput> {
+        match self {
+            ProcessedTask::DatalakeCompute(datalake_compute) => {
+                datalake_compute.aggregation_output.clone()
+            }
+            ProcessedTask::Module(_) => None,
+        }
+    }
 }
@@ -0,0 +1,40 @@
+//! Processed transaction count type
+//! This contains the proofs of the number of transactions of a block.
+
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+use super::transaction::ProcessedTransaction;
+
+/// The number of transactions `count` of a block is proven by the inclusion of the
+/// transaction at index `count - 1` and the non-inclusion of the key `rlp(count)`
+/// in the transactions trie. An empty block is proven by its transactions root being
+/// the empty trie root, it has no last transaction and an empty non-inclusion proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct ProcessedTransactionCount {
+    pub block_number: u64,
+    pub count: u64,
+    pub transactions_root: B256,
+    /// Inclusion proof of the transaction at index `count - 1`, `None` for an empty block
+    pub last_transaction: Option<ProcessedTransaction>,
+    /// Non-inclusion proof of the key at index `count`
+    pub non_inclusion: ProcessedTransaction,
+}
+
+impl ProcessedTransactionCount {
+    pub fn new(
+        block_number: u64,
+        count: u64,
+        transactions_root: B256,
+        last_transaction: Option<ProcessedTransaction>,
+        non_inclusion: ProcessedTransaction,
+    ) -> Self {
+        Self {
+            block_number,
+            count,
+            transactions_root,
+            last_transaction,
+            non_inclusion,
+        }
+    }
+}
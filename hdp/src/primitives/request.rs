@@ -1,4 +1,7 @@
-use crate::primitives::task::{datalake::DatalakeCompute, module::Module};
+use crate::primitives::task::{
+    datalake::{DatalakeCompute, MultiDatalakeCompute},
+    module::Module,
+};
 use serde::{Deserialize, Serialize};
 
 use super::ChainId;
@@ -7,6 +10,8 @@ use super::ChainId;
 #[serde(tag = "type")]
 pub enum Task {
     DatalakeCompute(DatalakeCompute),
+    /// Several properties sampled over the same blocks, expanded to a [`DatalakeCompute`] per property
+    MultiDatalakeCompute(MultiDatalakeCompute),
     Module(Module),
 }
 
@@ -98,3 +103,50 @@ fn test_serialize_submit_batch_query_module() {
     let parsed: SubmitBatchQuery = serde_json::from_str(json_data).unwrap();
     println!("{:?}", parsed);
 }
+
+#[test]
+fn test_serialize_submit_batch_query_multi_datalake() {
+    let json_data = r#"
+    {
+      "destinationChainId": "ETHEREUM_SEPOLIA",
+      "tasks": [
+        {
+          "type": "MultiDatalakeCompute",
+          "datalake": {
+            "chainId": "ETHEREUM_SEPOLIA",
+            "blockRangeStart": 5515020,
+            "blockRangeEnd": 5515039,
+            "increment": 10,
+            "sampledProperties": ["header.base_fee_per_gas", "header.gas_used"]
+          },
+          "computes": [
+            {
+              "aggregateFnId": "avg"
+            },
+            {
+              "aggregateFnId": "max"
+            }
+          ]
+        }
+      ]
+    }
+    "#;
+
+    let parsed: SubmitBatchQuery = serde_json::from_str(json_data).unwrap();
+    let Task::MultiDatalakeCompute(task) = parsed.tasks.into_iter().next().unwrap() else {
+        panic!("expected multi datalake compute task");
+    };
+    let datalake_computes = task.into_datalake_computes();
+    assert_eq!(datalake_computes.len(), 2);
+    assert_eq!(
+        datalake_computes[1].compute.aggregate_fn_id,
+        crate::primitives::aggregate_fn::AggregationFunction::MAX
+    );
+
+    // a computation per property, or a single one for every property
+    let invalid_json_data = json_data.replace(
+        r#""header.gas_used""#,
+        r#""header.gas_used", "header.number""#,
+    );
+    assert!(serde_json::from_str::<SubmitBatchQuery>(&invalid_json_data).is_err());
+}
@@ -5,7 +5,11 @@ use alloy::{
 use anyhow::{bail, Result};
 
 use crate::{
-    primitives::aggregate_fn::{integer::Operator, AggregationFunction, FunctionContext},
+    primitives::aggregate_fn::{
+        integer::{Operator, Rounding},
+        predicate::Predicate,
+        AggregationFunction, FunctionContext,
+    },
     primitives::solidity_types::traits::Codecs,
     primitives::task::datalake::compute::Computation,
 };
@@ -44,9 +48,33 @@ impl Codecs for BatchedComputation {
     }
 }
 
+/// Tags of the context parameters, which are encoded in ascending tag order
+const PERCENTILE_TAG: u8 = 1;
+const BLOOM_BITS_TAG: u8 = 2;
+const SIGNED_TAG: u8 = 3;
+const FIXED_POINT_TAG: u8 = 4;
+const BOUNDARIES_TAG: u8 = 5;
+const FILTER_TAG: u8 = 6;
+
+/// Computation is encoded as `(uint8 aggregate_fn_id, uint8 operator, uint256 value_to_compare)`
+/// if the context has no other parameter, so plain computations keep their encoding.
+///
+/// Otherwise it is encoded as `(uint8 aggregate_fn_id, uint8 operator, uint256 value_to_compare, bytes ctx)`,
+/// with `ctx` the encoding of the set parameters as `(uint8 tag, bytes value)[]`, in ascending tag order:
+/// - `PERCENTILE_TAG`: `uint8` percentile
+/// - `BLOOM_BITS_TAG`: `uint16` bloom filter size in bits
+/// - `SIGNED_TAG`: empty, the values are signed
+/// - `FIXED_POINT_TAG`: `(uint8 decimals, uint8 rounding)`, with the index of [`Rounding`]
+/// - `BOUNDARIES_TAG`: `uint256[]` boundaries of the buckets
+/// - `FILTER_TAG`: the encoding of the [`Predicate`]
+///
+/// The encoding is canonical: decoding fails on any other encoding of the same computation.
 impl Codecs for Computation {
     fn decode(encoded_compute: &[u8]) -> Result<Self> {
-        let aggregate_fn_type: DynSolType = "(uint8,uint8,uint256)".parse()?;
+        let aggregate_fn_type: DynSolType = match encoded_compute.len() {
+            96 => "(uint8,uint8,uint256)".parse()?,
+            _ => "(uint8,uint8,uint256,bytes)".parse()?,
+        };
         let decoded = aggregate_fn_type.abi_decode(encoded_compute)?;
 
         let value = decoded.as_tuple().unwrap();
@@ -60,8 +88,9 @@ impl Codecs for Computation {
             }
             _ => bail!("Invalid aggregate_fn_id type"),
         };
+
         // Turn bytes into hex string
-        match value[1].as_uint() {
+        let aggregate_fn_ctx = match value[1].as_uint() {
             Some((index, size)) => {
                 if size != 8 {
                     bail!("Invalid operator size");
@@ -72,23 +101,37 @@ impl Codecs for Computation {
                         if size != 256 {
                             bail!("Invalid value_to_compare size");
                         }
-                        let aggregate_fn_ctx: FunctionContext =
-                            FunctionContext::new(operator, value);
-
-                        Ok(Self {
-                            aggregate_fn_id,
-                            aggregate_fn_ctx,
-                        })
+                        FunctionContext::new(operator, value)
                     }
                     None => bail!("Invalid value_to_compare type"),
                 }
             }
             None => bail!("Invalid operator type"),
+        };
+        let aggregate_fn_ctx = match value.get(3) {
+            Some(DynSolValue::Bytes(parameters)) => decode_context(parameters, aggregate_fn_ctx)?,
+            Some(_) => bail!("Invalid context type"),
+            None => aggregate_fn_ctx,
+        };
+
+        let computation = Self {
+            aggregate_fn_id,
+            aggregate_fn_ctx,
+        };
+        if computation.encode()? != encoded_compute {
+            bail!("Non canonical computation encoding");
         }
+        Ok(computation)
     }
 
     /// Encode the task without datalake
     fn encode(&self) -> Result<Vec<u8>> {
+        if self.aggregate_fn_id == AggregationFunction::HISTOGRAM
+            && self.aggregate_fn_ctx.boundaries.is_empty()
+        {
+            bail!("Boundaries not provided for HISTOGRAM")
+        }
+
         let aggregate_fn_id = DynSolValue::Uint(
             U256::from(AggregationFunction::to_index(&self.aggregate_fn_id)),
             8,
@@ -101,13 +144,120 @@ impl Codecs for Computation {
 
         let value_to_compare = DynSolValue::Uint(self.aggregate_fn_ctx.value_to_compare, 32);
 
-        let header_tuple_value =
-            DynSolValue::Tuple(vec![aggregate_fn_id, operator, value_to_compare]);
+        let mut compute_values = vec![aggregate_fn_id, operator, value_to_compare];
+        let parameters = encode_context(&self.aggregate_fn_ctx)?;
+        if !parameters.is_empty() {
+            compute_values.push(DynSolValue::Bytes(
+                DynSolValue::Array(parameters).abi_encode(),
+            ));
+        }
+        let header_tuple_value = DynSolValue::Tuple(compute_values);
 
         Ok(header_tuple_value.abi_encode())
     }
 }
 
+/// Encode the set parameters of the context as `(uint8 tag, bytes value)` tuples
+fn encode_context(ctx: &FunctionContext) -> Result<Vec<DynSolValue>> {
+    let tagged = |tag: u8, value: Vec<u8>| {
+        DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(tag), 8),
+            DynSolValue::Bytes(value),
+        ])
+    };
+
+    let mut parameters = Vec::new();
+    if let Some(percentile) = ctx.percentile {
+        let percentile = DynSolValue::Uint(U256::from(percentile), 8);
+        parameters.push(tagged(PERCENTILE_TAG, percentile.abi_encode()));
+    }
+    if let Some(bloom_bits) = ctx.bloom_bits {
+        let bloom_bits = DynSolValue::Uint(U256::from(bloom_bits), 16);
+        parameters.push(tagged(BLOOM_BITS_TAG, bloom_bits.abi_encode()));
+    }
+    if ctx.signed {
+        parameters.push(tagged(SIGNED_TAG, Vec::new()));
+    }
+    if ctx.decimals != 0 || !ctx.rounding.is_default() {
+        let fixed_point = DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(ctx.decimals), 8),
+            DynSolValue::Uint(U256::from(Rounding::to_index(&ctx.rounding)), 8),
+        ]);
+        parameters.push(tagged(FIXED_POINT_TAG, fixed_point.abi_encode()));
+    }
+    if !ctx.boundaries.is_empty() {
+        let boundaries = DynSolValue::Array(
+            ctx.boundaries
+                .iter()
+                .map(|boundary| DynSolValue::Uint(*boundary, 256))
+                .collect(),
+        );
+        parameters.push(tagged(BOUNDARIES_TAG, boundaries.abi_encode()));
+    }
+    if let Some(filter) = &ctx.filter {
+        parameters.push(tagged(FILTER_TAG, filter.encode()?));
+    }
+    Ok(parameters)
+}
+
+/// Decode the tagged parameters of the context, see [`encode_context`]
+fn decode_context(encoded: &[u8], mut ctx: FunctionContext) -> Result<FunctionContext> {
+    let parameters_type: DynSolType = "(uint8,bytes)[]".parse()?;
+    let parameters = parameters_type.abi_decode(encoded)?;
+
+    let mut last_tag = 0;
+    for parameter in parameters.as_array().unwrap_or_default() {
+        let (tag, value) = match parameter.as_tuple() {
+            Some([DynSolValue::Uint(tag, 8), DynSolValue::Bytes(value)]) => (tag.to::<u8>(), value),
+            _ => bail!("Invalid context parameter"),
+        };
+        if tag <= last_tag {
+            bail!("Context parameters are not in ascending tag order");
+        }
+        last_tag = tag;
+
+        ctx = match tag {
+            PERCENTILE_TAG => ctx.with_percentile(decode_uint(value, 8)?.to()),
+            BLOOM_BITS_TAG => ctx.with_bloom_bits(decode_uint(value, 16)?.to()),
+            SIGNED_TAG => ctx.with_signed(),
+            FIXED_POINT_TAG => {
+                let fixed_point_type: DynSolType = "(uint8,uint8)".parse()?;
+                match fixed_point_type.abi_decode(value)?.as_tuple() {
+                    Some([DynSolValue::Uint(decimals, 8), DynSolValue::Uint(rounding, 8)]) => {
+                        ctx.with_fixed_point(decimals.to(), Rounding::from_index(rounding.to())?)
+                    }
+                    _ => bail!("Invalid fixed point"),
+                }
+            }
+            BOUNDARIES_TAG => {
+                let boundaries_type: DynSolType = "uint256[]".parse()?;
+                let boundaries = boundaries_type
+                    .abi_decode(value)?
+                    .as_array()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|boundary| match boundary.as_uint() {
+                        Some((boundary, 256)) => Ok(boundary),
+                        _ => bail!("Invalid boundary type"),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                ctx.with_boundaries(boundaries)
+            }
+            FILTER_TAG => ctx.with_filter(Predicate::decode(value)?),
+            _ => bail!("Unknown context parameter tag: {}", tag),
+        };
+    }
+    Ok(ctx)
+}
+
+/// Decode an unsigned integer of the given size
+fn decode_uint(encoded: &[u8], size: usize) -> Result<U256> {
+    match DynSolType::Uint(size).abi_decode(encoded)?.as_uint() {
+        Some((value, value_size)) if value_size == size && value.bit_len() <= size => Ok(value),
+        _ => bail!("Invalid uint{}", size),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::primitives::{
@@ -218,6 +368,238 @@ mod tests {
         assert_eq!(task, deserialized);
     }
 
+    /// Tags of the context parameters of the encoded computation
+    fn context_tags(encoded: &[u8]) -> Vec<u8> {
+        let decoded = "(uint8,uint8,uint256,bytes)"
+            .parse::<DynSolType>()
+            .unwrap()
+            .abi_decode(encoded)
+            .unwrap();
+        let parameters = decoded.as_tuple().unwrap()[3].as_bytes().unwrap();
+        "(uint8,bytes)[]"
+            .parse::<DynSolType>()
+            .unwrap()
+            .abi_decode(parameters)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter.as_tuple().unwrap()[0].as_uint().unwrap().0.to())
+            .collect()
+    }
+
+    #[test]
+    fn test_task_with_percentile_serialize() {
+        let task = Computation::new(
+            AggregationFunction::PERCENTILE,
+            Some(FunctionContext::default().with_percentile(95)),
+        );
+        let serialized = task.encode().unwrap();
+        // the tuple with the context starts with its offset
+        assert_eq!(serialized[63], 6);
+        assert_eq!(context_tags(&serialized), [PERCENTILE_TAG]);
+        let deserialized = Computation::decode(&serialized).unwrap();
+        assert_eq!(task, deserialized);
+
+        // median has no context, so its encoding is the same as the other functions
+        let task = Computation::new(AggregationFunction::MEDIAN, None);
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized.len(), 96);
+        assert_eq!(serialized[31], 5);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+    }
+
+    #[test]
+    fn test_task_with_bloom_bits_serialize() {
+        let task = Computation::new(
+            AggregationFunction::BLOOM,
+            Some(FunctionContext::default().with_bloom_bits(512)),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized[63], 9);
+        assert_eq!(context_tags(&serialized), [BLOOM_BITS_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        // the default size is not encoded
+        let task = Computation::new(AggregationFunction::BLOOM, None);
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized.len(), 96);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+    }
+
+    #[test]
+    fn test_task_with_signed_serialize() {
+        let task = Computation::new(
+            AggregationFunction::NET_CHANGE,
+            Some(FunctionContext::default().with_signed()),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized[63], 14);
+        assert_eq!(context_tags(&serialized), [SIGNED_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        // unsigned values keep the encoding of the other functions
+        let task = Computation::new(AggregationFunction::MAX_DRAWDOWN, None);
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized.len(), 96);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+    }
+
+    #[test]
+    fn test_task_with_fixed_point_serialize() {
+        let task = Computation::new(
+            AggregationFunction::AVG,
+            Some(FunctionContext::default().with_fixed_point(18, Rounding::Ceil)),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized[63], 0);
+        assert_eq!(context_tags(&serialized), [FIXED_POINT_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        // rounding without decimals is kept
+        let task = Computation::new(
+            AggregationFunction::AVG,
+            Some(FunctionContext::default().with_fixed_point(0, Rounding::Floor)),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(context_tags(&serialized), [FIXED_POINT_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        // no decimals with half up rounding is the plain average
+        let task = Computation::new(
+            AggregationFunction::AVG,
+            Some(FunctionContext::default().with_fixed_point(0, Rounding::HalfUp)),
+        );
+        assert_eq!(task.encode().unwrap().len(), 96);
+    }
+
+    #[test]
+    fn test_task_with_histogram_serialize() {
+        let boundaries = vec![U256::from(10), U256::from(100)];
+        let task = Computation::new(
+            AggregationFunction::HISTOGRAM,
+            Some(FunctionContext::default().with_boundaries(boundaries.clone())),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized[63], 17);
+        assert_eq!(context_tags(&serialized), [BOUNDARIES_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        let filter: Predicate = "not(eq.0)".parse().unwrap();
+        let task = Computation::new(
+            AggregationFunction::HISTOGRAM,
+            Some(
+                FunctionContext::default()
+                    .with_boundaries(boundaries)
+                    .with_filter(filter),
+            ),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(context_tags(&serialized), [BOUNDARIES_TAG, FILTER_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        assert!(Computation::new(AggregationFunction::HISTOGRAM, None)
+            .encode()
+            .is_err());
+
+        // count distinct has no context
+        let task = Computation::new(AggregationFunction::COUNT_DISTINCT, None);
+        let serialized = task.encode().unwrap();
+        assert_eq!(serialized.len(), 96);
+        assert_eq!(serialized[31], 16);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+    }
+
+    #[test]
+    fn test_task_with_filter_serialize() {
+        let filter: Predicate = "and(gt.1000000000,lt.100000000000)".parse().unwrap();
+        let task = Computation::new(
+            AggregationFunction::SUM,
+            Some(FunctionContext::default().with_filter(filter.clone())),
+        );
+        let serialized = task.encode().unwrap();
+        assert_eq!(context_tags(&serialized), [FILTER_TAG]);
+        assert_eq!(Computation::decode(&serialized).unwrap(), task);
+
+        // parameters are kept along with the filter
+        for (aggregate_fn_id, ctx, tags) in [
+            (
+                AggregationFunction::PERCENTILE,
+                FunctionContext::default().with_percentile(0),
+                vec![PERCENTILE_TAG, FILTER_TAG],
+            ),
+            (
+                AggregationFunction::NET_CHANGE,
+                FunctionContext::default().with_signed(),
+                vec![SIGNED_TAG, FILTER_TAG],
+            ),
+            (
+                AggregationFunction::COUNT,
+                FunctionContext::new(Operator::Equal, U256::from(5)),
+                vec![FILTER_TAG],
+            ),
+        ] {
+            let task = Computation::new(aggregate_fn_id, Some(ctx.with_filter(filter.clone())));
+            let serialized = task.encode().unwrap();
+            assert_eq!(context_tags(&serialized), tags);
+            assert_eq!(Computation::decode(&serialized).unwrap(), task);
+        }
+
+        // a computation without filter keeps its encoding
+        let task = Computation::new(AggregationFunction::SUM, None);
+        assert_eq!(task.encode().unwrap().len(), 96);
+    }
+
+    #[test]
+    fn test_task_with_non_canonical_context() {
+        let encode = |parameters: Vec<(u8, Vec<u8>)>| {
+            let parameters = parameters
+                .into_iter()
+                .map(|(tag, value)| {
+                    DynSolValue::Tuple(vec![
+                        DynSolValue::Uint(U256::from(tag), 8),
+                        DynSolValue::Bytes(value),
+                    ])
+                })
+                .collect();
+            DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(6), 8),
+                DynSolValue::Uint(U256::ZERO, 8),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Bytes(DynSolValue::Array(parameters).abi_encode()),
+            ])
+            .abi_encode()
+        };
+        let percentile = DynSolValue::Uint(U256::from(95), 8).abi_encode();
+        let filter = "gt.0".parse::<Predicate>().unwrap().encode().unwrap();
+
+        let task = Computation::decode(&encode(vec![
+            (PERCENTILE_TAG, percentile.clone()),
+            (FILTER_TAG, filter.clone()),
+        ]))
+        .unwrap();
+        assert_eq!(task.aggregate_fn_ctx.percentile, Some(95));
+
+        // tags out of order, repeated or unknown
+        assert!(Computation::decode(&encode(vec![
+            (FILTER_TAG, filter),
+            (PERCENTILE_TAG, percentile.clone()),
+        ]))
+        .is_err());
+        assert!(Computation::decode(&encode(vec![
+            (PERCENTILE_TAG, percentile.clone()),
+            (PERCENTILE_TAG, percentile),
+        ]))
+        .is_err());
+        assert!(Computation::decode(&encode(vec![(7, vec![])])).is_err());
+        // a plain context is only encoded without parameters
+        assert!(Computation::decode(&encode(vec![])).is_err());
+        // values out of their range
+        let percentile = DynSolValue::Uint(U256::from(256), 256).abi_encode();
+        assert!(Computation::decode(&encode(vec![(PERCENTILE_TAG, percentile)])).is_err());
+        assert!(Computation::decode(&encode(vec![(SIGNED_TAG, vec![0])])).is_err());
+    }
+
     #[test]
     fn test_task_with_datalake() {
         let task = Computation::new(
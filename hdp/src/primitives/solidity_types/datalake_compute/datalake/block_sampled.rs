@@ -1,6 +1,6 @@
 use crate::primitives::solidity_types::traits::DatalakeCodecs;
 use crate::primitives::task::datalake::block_sampled::{
-    BlockSampledCollection, BlockSampledDatalake,
+    BlockSampledCollection, BlockSampledDatalake, RandomSampling, TimestampRange,
 };
 use crate::primitives::task::datalake::datalake_type::DatalakeType;
 use crate::primitives::task::datalake::DatalakeCollection;
@@ -9,7 +9,7 @@ use crate::primitives::ChainId;
 use alloy::primitives::keccak256;
 use alloy::{
     dyn_abi::{DynSolType, DynSolValue},
-    primitives::B256,
+    primitives::{BlockNumber, B256, U256},
 };
 use anyhow::{bail, Result};
 
@@ -28,14 +28,31 @@ impl DatalakeCodecs for BlockSampledDatalake {
         let sampled_property: DynSolValue = self.sampled_property.serialize()?.into();
         let increment: DynSolValue = self.increment.into();
 
-        let tuple_value = DynSolValue::Tuple(vec![
+        let mut tuple = vec![
             datalake_code,
             chain_id,
             block_range_start,
             block_range_end,
             increment,
             sampled_property,
-        ]);
+        ];
+        // explicit block list, marked by an increment of 0, or random sampling is appended if present
+        let sampling = if !self.block_list.is_empty() {
+            Some(encode_block_list(&self.block_list))
+        } else {
+            self.random_sampling
+                .as_ref()
+                .map(|random_sampling| random_sampling.serialize())
+        };
+        // resolved timestamp range is appended after the sampling, which is then empty if absent
+        match &self.timestamp_range {
+            Some(timestamp_range) => {
+                tuple.push(sampling.unwrap_or_default().into());
+                tuple.push(timestamp_range.serialize()?.into());
+            }
+            None => tuple.extend(sampling.map(DynSolValue::from)),
+        }
+        let tuple_value = DynSolValue::Tuple(tuple);
 
         match tuple_value.abi_encode_sequence() {
             Some(encoded_datalake) => Ok(encoded_datalake),
@@ -50,7 +67,19 @@ impl DatalakeCodecs for BlockSampledDatalake {
 
     /// Decode the encoded block sampled datalake
     fn decode(encoded: &[u8]) -> Result<Self> {
-        let abi_type: DynSolType = "(uint256,uint256,uint256,uint256,uint256,bytes)".parse()?;
+        // sampled property is offset by one more word for each appended parameter
+        let sampled_property_offset = if encoded.len() >= 6 * 32 {
+            U256::from_be_slice(&encoded[5 * 32..6 * 32])
+        } else {
+            U256::ZERO
+        };
+        let abi_type: DynSolType = if sampled_property_offset == U256::from(8 * 32) {
+            "(uint256,uint256,uint256,uint256,uint256,bytes,bytes,bytes)".parse()?
+        } else if sampled_property_offset == U256::from(7 * 32) {
+            "(uint256,uint256,uint256,uint256,uint256,bytes,bytes)".parse()?
+        } else {
+            "(uint256,uint256,uint256,uint256,uint256,bytes)".parse()?
+        };
         let decoded = abi_type.abi_decode_sequence(encoded)?;
         let value = decoded.as_tuple().unwrap();
         let datalake_code = value[0].as_uint().unwrap().0.to_string().parse::<u8>()?;
@@ -61,17 +90,97 @@ impl DatalakeCodecs for BlockSampledDatalake {
 
         let chain_id =
             ChainId::from_numeric_id(value[1].as_uint().unwrap().0.to_string().parse::<u128>()?)?;
+        if chain_id.is_starknet() {
+            bail!(
+                "Block sampled datalakes are not supported on {} yet",
+                chain_id
+            );
+        }
         let block_range_start = value[2].as_uint().unwrap().0.to_string().parse::<u64>()?;
         let block_range_end = value[3].as_uint().unwrap().0.to_string().parse::<u64>()?;
         let increment = value[4].as_uint().unwrap().0.to_string().parse::<u64>()?;
         let sampled_property = BlockSampledCollection::deserialize(value[5].as_bytes().unwrap())?;
+        let sampling = value
+            .get(6)
+            .map(|sampling| sampling.as_bytes().unwrap())
+            .filter(|sampling| !sampling.is_empty());
+        let timestamp_range = value
+            .get(7)
+            .map(|timestamp_range| TimestampRange::deserialize(timestamp_range.as_bytes().unwrap()))
+            .transpose()?;
 
-        Ok(Self {
+        let datalake = Self::decode_blocks(
             chain_id,
             block_range_start,
             block_range_end,
             increment,
+            sampling,
             sampled_property,
+        )?;
+        Ok(Self {
+            timestamp_range,
+            ..datalake
         })
     }
 }
+
+impl BlockSampledDatalake {
+    /// Build the datalake from the decoded blocks, with the optional sampling parameter
+    fn decode_blocks(
+        chain_id: ChainId,
+        block_range_start: BlockNumber,
+        block_range_end: BlockNumber,
+        increment: u64,
+        sampling: Option<&[u8]>,
+        sampled_property: BlockSampledCollection,
+    ) -> Result<Self> {
+        match sampling {
+            Some(random_sampling) if increment != 0 => Self::from_random_sampling(
+                chain_id,
+                block_range_start,
+                block_range_end,
+                increment,
+                RandomSampling::deserialize(random_sampling)?,
+                sampled_property,
+            ),
+            Some(block_list) => {
+                let datalake = Self::from_block_list(
+                    chain_id,
+                    decode_block_list(block_list)?,
+                    sampled_property,
+                )?;
+                if datalake.block_range_start != block_range_start
+                    || datalake.block_range_end != block_range_end
+                {
+                    bail!("Block range does not match the block list");
+                }
+                Ok(datalake)
+            }
+            None => Self::from_block_range(
+                chain_id,
+                block_range_start,
+                block_range_end,
+                increment,
+                sampled_property,
+            ),
+        }
+    }
+}
+
+/// Encode the block list as packed 8 bytes big endian block numbers
+fn encode_block_list(block_list: &[BlockNumber]) -> Vec<u8> {
+    block_list
+        .iter()
+        .flat_map(|block| block.to_be_bytes())
+        .collect()
+}
+
+fn decode_block_list(encoded: &[u8]) -> Result<Vec<BlockNumber>> {
+    if encoded.len() % 8 != 0 {
+        bail!("Invalid block list");
+    }
+    Ok(encoded
+        .chunks(8)
+        .map(|chunk| BlockNumber::from_be_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
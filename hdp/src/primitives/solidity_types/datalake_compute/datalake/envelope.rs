@@ -1,8 +1,12 @@
 use crate::{
     primitives::solidity_types::traits::{Codecs, DatalakeCodecs},
     primitives::task::datalake::{
-        block_sampled::BlockSampledDatalake, datalake_type::DatalakeType,
-        envelope::DatalakeEnvelope, transactions::TransactionsInBlockDatalake,
+        block_sampled::BlockSampledDatalake,
+        datalake_type::DatalakeType,
+        envelope::DatalakeEnvelope,
+        transactions::{
+            TransactionsByHashDatalake, TransactionsInBlockDatalake, TransactionsInRangeDatalake,
+        },
     },
     primitives::utils::last_byte_to_u8,
 };
@@ -26,6 +30,12 @@ impl Codecs for BatchedDatalakeEnvelope {
                 DatalakeEnvelope::TransactionsInBlock(transactions_datalake) => {
                     transactions_datalake.encode()?
                 }
+                DatalakeEnvelope::TransactionsInRange(transactions_datalake) => {
+                    transactions_datalake.encode()?
+                }
+                DatalakeEnvelope::TransactionsByHash(transactions_datalake) => {
+                    transactions_datalake.encode()?
+                }
             };
             encoded_datalakes.push(DynSolValue::Bytes(encoded_datalake));
         }
@@ -63,6 +73,12 @@ impl DatalakeCodecs for DatalakeEnvelope {
             DatalakeType::TransactionsInBlock => DatalakeEnvelope::TransactionsInBlock(
                 TransactionsInBlockDatalake::decode(encoded_datalake)?,
             ),
+            DatalakeType::TransactionsInRange => DatalakeEnvelope::TransactionsInRange(
+                TransactionsInRangeDatalake::decode(encoded_datalake)?,
+            ),
+            DatalakeType::TransactionsByHash => DatalakeEnvelope::TransactionsByHash(
+                TransactionsByHashDatalake::decode(encoded_datalake)?,
+            ),
         };
         Ok(decoded_datalake)
     }
@@ -71,6 +87,8 @@ impl DatalakeCodecs for DatalakeEnvelope {
         match self {
             DatalakeEnvelope::BlockSampled(datalake) => datalake.encode(),
             DatalakeEnvelope::TransactionsInBlock(datalake) => datalake.encode(),
+            DatalakeEnvelope::TransactionsInRange(datalake) => datalake.encode(),
+            DatalakeEnvelope::TransactionsByHash(datalake) => datalake.encode(),
         }
     }
 
@@ -78,6 +96,8 @@ impl DatalakeCodecs for DatalakeEnvelope {
         match self {
             DatalakeEnvelope::BlockSampled(_) => DatalakeType::BlockSampled,
             DatalakeEnvelope::TransactionsInBlock(_) => DatalakeType::TransactionsInBlock,
+            DatalakeEnvelope::TransactionsInRange(_) => DatalakeType::TransactionsInRange,
+            DatalakeEnvelope::TransactionsByHash(_) => DatalakeType::TransactionsByHash,
         }
     }
 
@@ -85,6 +105,8 @@ impl DatalakeCodecs for DatalakeEnvelope {
         match self {
             DatalakeEnvelope::BlockSampled(datalake) => datalake.commit(),
             DatalakeEnvelope::TransactionsInBlock(datalake) => datalake.commit(),
+            DatalakeEnvelope::TransactionsInRange(datalake) => datalake.commit(),
+            DatalakeEnvelope::TransactionsByHash(datalake) => datalake.commit(),
         }
     }
 }
@@ -1,3 +1,5 @@
 pub mod block_sampled;
 pub mod envelope;
+pub mod transactions_by_hash;
 pub mod transactions_in_block;
+pub mod transactions_in_range;
@@ -0,0 +1,69 @@
+use crate::primitives::{
+    solidity_types::traits::DatalakeCodecs,
+    task::datalake::{
+        datalake_type::DatalakeType,
+        transactions::{TransactionsByHashDatalake, TransactionsCollection},
+        DatalakeCollection,
+    },
+    ChainId,
+};
+use alloy::primitives::keccak256;
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    primitives::B256,
+};
+use anyhow::{bail, Result};
+
+impl DatalakeCodecs for TransactionsByHashDatalake {
+    /// Get the datalake code for transactions by hash datalake
+    fn get_datalake_type(&self) -> DatalakeType {
+        DatalakeType::TransactionsByHash
+    }
+
+    /// Encode the [`TransactionsByHashDatalake`] into a hex string.
+    /// The transactions must be resolved, as their positions are committed along with their hashes.
+    fn encode(&self) -> Result<Vec<u8>> {
+        let datalake_code: DynSolValue = self.get_datalake_type().to_u8().into();
+        let chain_id: DynSolValue = self.chain_id.to_numeric_id().into();
+        let sampled_property: DynSolValue = self.sampled_property.serialize()?.into();
+        let transactions: DynSolValue = self.serialize_transactions()?.into();
+
+        let tuple_value = DynSolValue::Tuple(vec![
+            datalake_code,
+            chain_id,
+            sampled_property,
+            transactions,
+        ]);
+
+        match tuple_value.abi_encode_sequence() {
+            Some(encoded_datalake) => Ok(encoded_datalake),
+            None => bail!("Encoding failed"),
+        }
+    }
+
+    /// Get the commitment hash of the [`TransactionsByHashDatalake`]
+    fn commit(&self) -> B256 {
+        let encoded_datalake = self.encode().expect("Encoding failed");
+        keccak256(encoded_datalake)
+    }
+
+    /// Decode the encoded transactions by hash datalake hex string into a [`TransactionsByHashDatalake`]
+    fn decode(encoded: &[u8]) -> Result<Self> {
+        let abi_type: DynSolType = "(uint256,uint256,bytes,bytes)".parse()?;
+        let decoded = abi_type.abi_decode_sequence(encoded)?;
+
+        let value = decoded.as_tuple().unwrap();
+        let datalake_code = value[0].as_uint().unwrap().0.to_string().parse::<u8>()?;
+
+        if DatalakeType::from_index(datalake_code)? != DatalakeType::TransactionsByHash {
+            bail!("Encoded datalake is not a transactions by hash datalake");
+        }
+
+        let chain_id =
+            ChainId::from_numeric_id(value[1].as_uint().unwrap().0.to_string().parse::<u128>()?)?;
+        let sampled_property = TransactionsCollection::deserialize(value[2].as_bytes().unwrap())?;
+        let (tx_hashes, positions) = Self::deserialize_transactions(value[3].as_bytes().unwrap())?;
+
+        Self::new(chain_id, tx_hashes, sampled_property)?.with_positions(positions)
+    }
+}
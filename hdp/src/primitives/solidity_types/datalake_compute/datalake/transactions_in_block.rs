@@ -2,7 +2,10 @@ use crate::primitives::{
     solidity_types::traits::DatalakeCodecs,
     task::datalake::{
         datalake_type::DatalakeType,
-        transactions::{IncludedTypes, TransactionsCollection, TransactionsInBlockDatalake},
+        transactions::{
+            IncludedTypes, TransactionsCollection, TransactionsFilter, TransactionsInBlockDatalake,
+            TxIndexRange,
+        },
         DatalakeCollection,
     },
     ChainId,
@@ -10,7 +13,7 @@ use crate::primitives::{
 use alloy::primitives::keccak256;
 use alloy::{
     dyn_abi::{DynSolType, DynSolValue},
-    primitives::B256,
+    primitives::{B256, U256},
 };
 use anyhow::{bail, Result};
 
@@ -31,7 +34,7 @@ impl DatalakeCodecs for TransactionsInBlockDatalake {
         let increment: DynSolValue = self.increment.into();
         let included_types: DynSolValue = self.included_types.to_uint256().into();
 
-        let tuple_value = DynSolValue::Tuple(vec![
+        let mut tuple_value = vec![
             datalake_code,
             chain_id,
             target_block,
@@ -40,9 +43,17 @@ impl DatalakeCodecs for TransactionsInBlockDatalake {
             increment,
             included_types,
             sampled_property,
-        ]);
+        ];
+        // filter is only appended if set, so that unfiltered datalakes keep their encoding.
+        // the resolved index range is appended after the filter, which is then always appended
+        if !self.filter.is_empty() || self.index_range.is_some() {
+            tuple_value.push(self.filter.serialize().into());
+        }
+        if let Some(index_range) = &self.index_range {
+            tuple_value.push(index_range.serialize()?.into());
+        }
 
-        match tuple_value.abi_encode_sequence() {
+        match DynSolValue::Tuple(tuple_value).abi_encode_sequence() {
             Some(encoded_datalake) => Ok(encoded_datalake),
             None => bail!("Encoding failed"),
         }
@@ -56,8 +67,22 @@ impl DatalakeCodecs for TransactionsInBlockDatalake {
 
     /// Decode the encoded transactions datalake hex string into a [`TransactionsDatalake`]
     fn decode(encoded: &[u8]) -> Result<Self> {
-        let abi_type: DynSolType =
-            "(uint256,uint256, uint256, uint256, uint256, uint256, uint256, bytes)".parse()?;
+        // sampled property is placed right after the head, which grows with the appended params
+        let appended_params = match encoded.len() >= 8 * 32 {
+            true => match U256::from_be_slice(&encoded[7 * 32..8 * 32]).to::<usize>() / 32 {
+                9 => 1,
+                10 => 2,
+                _ => 0,
+            },
+            false => 0,
+        };
+        let has_filter = appended_params >= 1;
+        let abi_type: DynSolType = match appended_params {
+            2 => "(uint256,uint256, uint256, uint256, uint256, uint256, uint256, bytes, bytes, bytes)",
+            1 => "(uint256,uint256, uint256, uint256, uint256, uint256, uint256, bytes, bytes)",
+            _ => "(uint256,uint256, uint256, uint256, uint256, uint256, uint256, bytes)",
+        }
+        .parse()?;
         let decoded = abi_type.abi_decode_sequence(encoded)?;
 
         let value = decoded.as_tuple().unwrap();
@@ -75,6 +100,14 @@ impl DatalakeCodecs for TransactionsInBlockDatalake {
         let increment = value[5].as_uint().unwrap().0.to_string().parse::<u64>()?;
         let included_types = IncludedTypes::from_uint256(value[6].as_uint().unwrap().0);
         let sampled_property = TransactionsCollection::deserialize(value[7].as_bytes().unwrap())?;
+        let filter = match has_filter {
+            true => TransactionsFilter::deserialize(value[8].as_bytes().unwrap())?,
+            false => TransactionsFilter::default(),
+        };
+        let index_range = match appended_params {
+            2 => Some(TxIndexRange::deserialize(value[9].as_bytes().unwrap())?),
+            _ => None,
+        };
 
         Ok(Self {
             chain_id,
@@ -84,6 +117,8 @@ impl DatalakeCodecs for TransactionsInBlockDatalake {
             increment,
             included_types,
             sampled_property,
+            filter,
+            index_range,
         })
     }
 }
@@ -0,0 +1,115 @@
+use crate::primitives::{
+    solidity_types::traits::DatalakeCodecs,
+    task::datalake::{
+        datalake_type::DatalakeType,
+        transactions::{
+            IncludedTypes, TransactionsCollection, TransactionsFilter, TransactionsInRangeDatalake,
+        },
+        DatalakeCollection,
+    },
+    ChainId,
+};
+use alloy::primitives::keccak256;
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    primitives::{B256, U256},
+};
+use anyhow::{bail, Result};
+
+impl DatalakeCodecs for TransactionsInRangeDatalake {
+    /// Get the datalake code for transactions in range datalake
+    fn get_datalake_type(&self) -> DatalakeType {
+        DatalakeType::TransactionsInRange
+    }
+
+    /// Encode the [`TransactionsInRangeDatalake`] into a hex string
+    fn encode(&self) -> Result<Vec<u8>> {
+        let datalake_code: DynSolValue = self.get_datalake_type().to_u8().into();
+        let chain_id: DynSolValue = self.chain_id.to_numeric_id().into();
+        let block_range_start: DynSolValue = self.block_range_start.into();
+        let block_range_end: DynSolValue = self.block_range_end.into();
+        let block_increment: DynSolValue = self.block_increment.into();
+        let sampled_property: DynSolValue = self.sampled_property.serialize()?.into();
+        let start_index: DynSolValue = self.start_index.into();
+        let end_index: DynSolValue = self.end_index.into();
+        let increment: DynSolValue = self.increment.into();
+        let included_types: DynSolValue = self.included_types.to_uint256().into();
+
+        let mut tuple_value = vec![
+            datalake_code,
+            chain_id,
+            block_range_start,
+            block_range_end,
+            block_increment,
+            start_index,
+            end_index,
+            increment,
+            included_types,
+            sampled_property,
+        ];
+        // filter is only appended if set, so that unfiltered datalakes keep their encoding
+        if !self.filter.is_empty() {
+            tuple_value.push(self.filter.serialize().into());
+        }
+
+        match DynSolValue::Tuple(tuple_value).abi_encode_sequence() {
+            Some(encoded_datalake) => Ok(encoded_datalake),
+            None => bail!("Encoding failed"),
+        }
+    }
+
+    /// Get the commitment hash of the [`TransactionsInRangeDatalake`]
+    fn commit(&self) -> B256 {
+        let encoded_datalake = self.encode().expect("Encoding failed");
+        keccak256(encoded_datalake)
+    }
+
+    /// Decode the encoded transactions in range datalake hex string into a [`TransactionsInRangeDatalake`]
+    fn decode(encoded: &[u8]) -> Result<Self> {
+        // sampled property is placed right after the head unless the filter is appended
+        let has_filter = encoded.len() >= 10 * 32
+            && U256::from_be_slice(&encoded[9 * 32..10 * 32]) == U256::from(11 * 32);
+        let abi_type: DynSolType = if has_filter {
+            "(uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,bytes,bytes)"
+        } else {
+            "(uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,bytes)"
+        }
+        .parse()?;
+        let decoded = abi_type.abi_decode_sequence(encoded)?;
+
+        let value = decoded.as_tuple().unwrap();
+        let datalake_code = value[0].as_uint().unwrap().0.to_string().parse::<u8>()?;
+
+        if DatalakeType::from_index(datalake_code)? != DatalakeType::TransactionsInRange {
+            bail!("Encoded datalake is not a transactions in range datalake");
+        }
+
+        let chain_id =
+            ChainId::from_numeric_id(value[1].as_uint().unwrap().0.to_string().parse::<u128>()?)?;
+        let block_range_start = value[2].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let block_range_end = value[3].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let block_increment = value[4].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let start_index = value[5].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let end_index = value[6].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let increment = value[7].as_uint().unwrap().0.to_string().parse::<u64>()?;
+        let included_types = IncludedTypes::from_uint256(value[8].as_uint().unwrap().0);
+        let sampled_property = TransactionsCollection::deserialize(value[9].as_bytes().unwrap())?;
+        let filter = match has_filter {
+            true => TransactionsFilter::deserialize(value[10].as_bytes().unwrap())?,
+            false => TransactionsFilter::default(),
+        };
+
+        Ok(Self {
+            chain_id,
+            block_range_start,
+            block_range_end,
+            block_increment,
+            start_index,
+            end_index,
+            increment,
+            included_types,
+            sampled_property,
+            filter,
+        })
+    }
+}
@@ -107,7 +107,7 @@ mod tests {
     use std::str::FromStr;
 
     use crate::primitives::{
-        aggregate_fn::FunctionContext,
+        aggregate_fn::{integer::Rounding, FunctionContext},
         task::datalake::{
             block_sampled::{BlockSampledCollection, BlockSampledDatalake},
             transactions::{IncludedTypes, TransactionsCollection, TransactionsInBlockDatalake},
@@ -128,12 +128,22 @@ mod tests {
                 sampled_property: BlockSampledCollection::Header(
                     crate::primitives::task::datalake::block_sampled::HeaderField::ExcessBlobGas,
                 ),
+                block_list: vec![],
+                random_sampling: None,
+                timestamp_range: None,
             }),
             compute: Computation {
                 aggregate_fn_id: AggregationFunction::COUNT,
                 aggregate_fn_ctx: FunctionContext {
                     operator: Operator::GreaterThan,
                     value_to_compare: U256::from_str("10000000").unwrap(),
+                    percentile: None,
+                    bloom_bits: None,
+                    signed: false,
+                    filter: None,
+                    decimals: 0,
+                    rounding: Rounding::HalfUp,
+                    boundaries: Vec::new(),
                 },
             },
         };
@@ -158,12 +168,21 @@ mod tests {
                 sampled_property: TransactionsCollection::TranasactionReceipts(
                     crate::primitives::task::datalake::transactions::TransactionReceiptField::Success,
                 ),
+                filter: Default::default(),
+                index_range: None,
             }),
             compute: Computation {
                 aggregate_fn_id: AggregationFunction::COUNT,
                 aggregate_fn_ctx: FunctionContext {
                     operator: Operator::GreaterThan,
                     value_to_compare: U256::from_str("50").unwrap(),
+                    percentile: None,
+                    bloom_bits: None,
+                    signed: false,
+                    filter: None,
+                    decimals: 0,
+                    rounding: Rounding::HalfUp,
+                    boundaries: Vec::new(),
                 },
             },
         };
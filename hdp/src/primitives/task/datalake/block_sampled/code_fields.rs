@@ -0,0 +1,148 @@
+//! This module defines the fields that can be queried from the code of an account.
+//! The code is not part of the account trie, so it is fetched separately and checked
+//! against the proven code hash of the account.
+
+use std::{fmt::Display, str::FromStr};
+
+use alloy::primitives::U256;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub enum CodeField {
+    /// Length of the code in bytes
+    Size,
+    /// 32 bytes word of the code starting at the given byte offset.
+    /// Bytes past the end of the code are zero, as with `CODECOPY`.
+    Word(u32),
+}
+
+impl CodeField {
+    pub fn variants() -> Vec<String> {
+        vec!["SIZE".to_string(), "WORD[{offset}]".to_string()]
+    }
+
+    pub fn to_index(&self) -> u8 {
+        match self {
+            CodeField::Size => 0,
+            CodeField::Word(_) => 1,
+        }
+    }
+
+    /// Serialize the field as its index, followed by the big endian offset for `Word`
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = vec![self.to_index()];
+        if let CodeField::Word(offset) = self {
+            serialized.extend_from_slice(&offset.to_be_bytes());
+        }
+        serialized
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        match serialized {
+            [0] => Ok(CodeField::Size),
+            [1, offset @ ..] if offset.len() == 4 => Ok(CodeField::Word(u32::from_be_bytes(
+                offset.try_into().unwrap(),
+            ))),
+            _ => bail!("Invalid code field"),
+        }
+    }
+
+    pub fn decode_field_from_code(&self, code: &[u8]) -> U256 {
+        match self {
+            CodeField::Size => U256::from(code.len()),
+            CodeField::Word(offset) => {
+                let mut word = [0u8; 32];
+                let start = (*offset as usize).min(code.len());
+                let end = (start + 32).min(code.len());
+                word[..end - start].copy_from_slice(&code[start..end]);
+                U256::from_be_bytes(word)
+            }
+        }
+    }
+}
+
+impl FromStr for CodeField {
+    type Err = anyhow::Error;
+
+    /// Parse field from "SIZE" or "WORD[{offset}]" format
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.to_uppercase();
+        if s == "SIZE" {
+            return Ok(CodeField::Size);
+        }
+        match s.strip_prefix("WORD[").and_then(|s| s.strip_suffix(']')) {
+            Some(offset) => Ok(CodeField::Word(offset.trim().parse()?)),
+            None => bail!("Unknown code field"),
+        }
+    }
+}
+
+impl TryFrom<String> for CodeField {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        CodeField::from_str(&value)
+    }
+}
+
+impl Display for CodeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodeField::Size => write!(f, "SIZE"),
+            CodeField::Word(offset) => write!(f, "WORD[{}]", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_field_serialize() {
+        assert_eq!(CodeField::Size.serialize(), [0]);
+        assert_eq!(CodeField::Word(64).serialize(), [1, 0, 0, 0, 64]);
+        assert_eq!(
+            CodeField::deserialize(&[1, 0, 0, 1, 0]).unwrap(),
+            CodeField::Word(256)
+        );
+        assert!(CodeField::deserialize(&[1, 0]).is_err());
+        assert!(CodeField::deserialize(&[2]).is_err());
+
+        assert_eq!("size".parse::<CodeField>().unwrap(), CodeField::Size);
+        assert_eq!(
+            "WORD[32]".parse::<CodeField>().unwrap(),
+            CodeField::Word(32)
+        );
+        assert_eq!(CodeField::Word(32).to_string(), "WORD[32]");
+        assert!("WORD[-1]".parse::<CodeField>().is_err());
+    }
+
+    #[test]
+    fn test_decode_field_from_code() {
+        let code: Vec<u8> = (1..=40).collect();
+        assert_eq!(
+            CodeField::Size.decode_field_from_code(&code),
+            U256::from(40)
+        );
+        assert_eq!(
+            CodeField::Word(0).decode_field_from_code(&code),
+            U256::from_be_slice(&code[..32])
+        );
+
+        // bytes past the end of the code are zero
+        let mut expected = [0u8; 32];
+        expected[..8].copy_from_slice(&code[32..]);
+        assert_eq!(
+            CodeField::Word(32).decode_field_from_code(&code),
+            U256::from_be_bytes(expected)
+        );
+        assert_eq!(
+            CodeField::Word(100).decode_field_from_code(&code),
+            U256::ZERO
+        );
+        assert_eq!(CodeField::Size.decode_field_from_code(&[]), U256::ZERO);
+    }
+}
@@ -1,25 +1,111 @@
 use std::{fmt::Display, str::FromStr};
 
-use alloy::primitives::{Address, StorageKey};
+use alloy::primitives::{Address, StorageKey, U256};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::primitives::task::datalake::{DatalakeCollection, DatalakeField};
 
-use super::rlp_fields::{AccountField, HeaderField};
+use super::{
+    code_fields::CodeField,
+    rlp_fields::{AccountField, HeaderField},
+    storage_layout::StorageLayout,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(try_from = "String")]
+#[serde(try_from = "String", into = "String")]
 pub enum BlockSampledCollection {
     Header(HeaderField),
     Account(Address, AccountField),
-    Storage(Address, StorageKey),
+    /// Storage slot of a contract. If the slice is set, only that part of the slot is sampled.
+    Storage(Address, StorageKey, Option<StorageSlice>),
+    /// Code of an account, checked against the proven code hash
+    Code(Address, CodeField),
+}
+
+/// Position of a packed value inside a 32 bytes storage slot.
+///
+/// `offset` is counted in bytes from the least significant end of the slot,
+/// the same way solc reports it in the storage layout.
+/// If `signed` is set, the value is sign extended to a 256 bits two's complement,
+/// which only aggregations with the `signed` context take as such.
+///
+/// The slice is encoded as the bytes `offset` and `width | signed << 7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StorageSlice {
+    pub offset: u8,
+    pub width: u8,
+    pub signed: bool,
+}
+
+impl StorageSlice {
+    pub fn new(offset: u8, width: u8, signed: bool) -> Result<Self> {
+        if width == 0 || offset as u16 + width as u16 > 32 {
+            bail!("Invalid storage slice: offset {}, width {}", offset, width);
+        }
+        Ok(Self {
+            offset,
+            width,
+            signed,
+        })
+    }
+
+    /// Extract the packed value from the storage slot value
+    pub fn extract(&self, slot_value: U256) -> U256 {
+        let shifted = slot_value >> (self.offset as usize * 8);
+        if self.width == 32 {
+            return shifted;
+        }
+
+        let bits = self.width as usize * 8;
+        let value = shifted & ((U256::from(1) << bits) - U256::from(1));
+        if self.signed && value.bit(bits - 1) {
+            // fill the upper bits with the sign bit
+            value | (U256::MAX << bits)
+        } else {
+            value
+        }
+    }
+}
+
+impl FromStr for StorageSlice {
+    type Err = anyhow::Error;
+
+    /// Parse slice from "[{offset}:{width}]" or "[{offset}:{width}:signed]" format
+    fn from_str(s: &str) -> Result<Self> {
+        let inner = s
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| anyhow::anyhow!("Invalid storage slice format"))?;
+        let parts: Vec<&str> = inner.split(':').map(str::trim).collect();
+        let signed = match parts.get(2) {
+            None => false,
+            Some(&"signed") => true,
+            Some(&"unsigned") => false,
+            Some(_) => bail!("Invalid storage slice signedness"),
+        };
+        if !(parts.len() == 2 || parts.len() == 3) {
+            bail!("Invalid storage slice format");
+        }
+        Self::new(parts[0].parse()?, parts[1].parse()?, signed)
+    }
+}
+
+impl Display for StorageSlice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.signed {
+            write!(f, "[{}:{}:signed]", self.offset, self.width)
+        } else {
+            write!(f, "[{}:{}]", self.offset, self.width)
+        }
+    }
 }
 
 pub enum BlockSampledCollectionType {
     Header,
     Account,
     Storage,
+    Code,
 }
 
 impl BlockSampledCollectionType {
@@ -28,6 +114,7 @@ impl BlockSampledCollectionType {
             "HEADER".to_string(),
             "ACCOUNT".to_string(),
             "STORAGE".to_string(),
+            "CODE".to_string(),
         ]
     }
 
@@ -36,6 +123,7 @@ impl BlockSampledCollectionType {
             BlockSampledCollectionType::Header => 0,
             BlockSampledCollectionType::Account => 1,
             BlockSampledCollectionType::Storage => 2,
+            BlockSampledCollectionType::Code => 3,
         }
     }
 }
@@ -48,6 +136,7 @@ impl FromStr for BlockSampledCollectionType {
             "HEADER" => Ok(BlockSampledCollectionType::Header),
             "ACCOUNT" => Ok(BlockSampledCollectionType::Account),
             "STORAGE" => Ok(BlockSampledCollectionType::Storage),
+            "CODE" => Ok(BlockSampledCollectionType::Code),
             _ => bail!("Unknown block sampled collection type"),
         }
     }
@@ -59,6 +148,7 @@ impl DatalakeCollection for BlockSampledCollection {
             BlockSampledCollection::Header(_) => 1,
             BlockSampledCollection::Account(..) => 2,
             BlockSampledCollection::Storage(..) => 3,
+            BlockSampledCollection::Code(..) => 4,
         }
     }
 
@@ -74,10 +164,19 @@ impl DatalakeCollection for BlockSampledCollection {
                 serialized.extend_from_slice(address.as_slice());
                 serialized.push(field.to_index());
             }
-            BlockSampledCollection::Storage(address, slot) => {
+            BlockSampledCollection::Storage(address, slot, slice) => {
                 serialized.push(3);
                 serialized.extend_from_slice(address.as_slice());
                 serialized.extend_from_slice(slot.as_ref());
+                if let Some(slice) = slice {
+                    serialized.push(slice.offset);
+                    serialized.push(slice.width | (slice.signed as u8) << 7);
+                }
+            }
+            BlockSampledCollection::Code(address, field) => {
+                serialized.push(4);
+                serialized.extend_from_slice(address.as_slice());
+                serialized.extend_from_slice(&field.serialize());
             }
         }
 
@@ -109,16 +208,41 @@ impl DatalakeCollection for BlockSampledCollection {
                 ))
             }
             3 => {
-                if serialized.len() != 53 {
-                    bail!("Invalid storage property");
-                }
+                let slice = match serialized.len() {
+                    53 => None,
+                    55 => Some(StorageSlice::new(
+                        serialized[53],
+                        serialized[54] & 0x7f,
+                        serialized[54] & 0x80 != 0,
+                    )?),
+                    _ => bail!("Invalid storage property"),
+                };
                 let address = Address::from_slice(&serialized[1..21]);
                 let slot = StorageKey::from_slice(&serialized[21..53]);
-                Ok(BlockSampledCollection::Storage(address, slot))
+                Ok(BlockSampledCollection::Storage(address, slot, slice))
+            }
+            4 => {
+                if serialized.len() < 22 {
+                    bail!("Invalid code property");
+                }
+                let address = Address::from_slice(&serialized[1..21]);
+                Ok(BlockSampledCollection::Code(
+                    address,
+                    CodeField::deserialize(&serialized[21..])?,
+                ))
             }
+            5..=7 => bail!("Starknet collections are not supported yet"),
             _ => bail!("Unknown block sampled collection"),
         }
     }
+
+    fn value_bytes(&self) -> usize {
+        match self {
+            BlockSampledCollection::Header(HeaderField::Beneficiary) => 20,
+            BlockSampledCollection::Storage(_, _, Some(slice)) => slice.width as usize,
+            _ => 32,
+        }
+    }
 }
 
 impl FromStr for BlockSampledCollection {
@@ -142,14 +266,58 @@ impl FromStr for BlockSampledCollection {
             }
             "STORAGE" => {
                 let address = Address::from_str(parts[1])?;
-                let slot = StorageKey::from_str(parts[2])?;
-                Ok(BlockSampledCollection::Storage(address, slot))
+                // slot can be followed by a slice, e.g. "0x...08[14:14]"
+                let (slot, slice) = match parts[2].find('[') {
+                    Some(index) => (
+                        &parts[2][..index],
+                        Some(StorageSlice::from_str(&parts[2][index..])?),
+                    ),
+                    None => (parts[2], None),
+                };
+                let slot = StorageKey::from_str(slot)?;
+                Ok(BlockSampledCollection::Storage(address, slot, slice))
+            }
+            "CODE" => {
+                let address = Address::from_str(parts[1])?;
+                let field = parts
+                    .get(2)
+                    .ok_or_else(|| anyhow::anyhow!("Missing code field"))?
+                    .parse()?;
+                Ok(BlockSampledCollection::Code(address, field))
+            }
+            // Starknet state is not fetched by any provider yet
+            "STARKNET_HEADER" | "STARKNET_STORAGE" | "STARKNET_CONTRACT" => {
+                bail!("Starknet collections are not supported yet")
             }
             _ => bail!("Unknown block sampled collection"),
         }
     }
 }
 
+impl BlockSampledCollection {
+    /// Parse the collection, resolving storage variables by name with the given storage layout.
+    ///
+    /// e.g. "storage.0xpool.reserve0", "storage.0xtoken._balances[0xholder]"
+    pub fn from_str_with_layout(s: &str, layout: &StorageLayout) -> Result<Self> {
+        if let Ok(collection) = Self::from_str(s) {
+            return Ok(collection);
+        }
+
+        // variable path may contain '.' for struct members
+        let parts: Vec<&str> = s.splitn(3, '.').collect();
+        if parts.len() != 3 || parts[0].to_uppercase() != "STORAGE" {
+            bail!("Invalid block sampled collection format");
+        }
+        let address = Address::from_str(parts[1])?;
+        let variable = layout.resolve(parts[2])?;
+        Ok(BlockSampledCollection::Storage(
+            address,
+            variable.slot,
+            variable.slice,
+        ))
+    }
+}
+
 impl TryFrom<String> for BlockSampledCollection {
     type Error = anyhow::Error;
 
@@ -158,6 +326,12 @@ impl TryFrom<String> for BlockSampledCollection {
     }
 }
 
+impl From<BlockSampledCollection> for String {
+    fn from(value: BlockSampledCollection) -> Self {
+        value.to_string()
+    }
+}
+
 impl Display for BlockSampledCollection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -165,8 +339,15 @@ impl Display for BlockSampledCollection {
             BlockSampledCollection::Account(address, field) => {
                 write!(f, "account.{}.{}", address, field)
             }
-            BlockSampledCollection::Storage(address, slot) => {
-                write!(f, "storage.{}.{}", address, slot)
+            BlockSampledCollection::Storage(address, slot, slice) => {
+                write!(f, "storage.{}.{}", address, slot)?;
+                if let Some(slice) = slice {
+                    write!(f, "{}", slice)?;
+                }
+                Ok(())
+            }
+            BlockSampledCollection::Code(address, field) => {
+                write!(f, "code.{}.{}", address, field)
             }
         }
     }
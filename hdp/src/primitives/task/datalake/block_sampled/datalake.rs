@@ -1,15 +1,31 @@
+use std::str::FromStr;
+
+use alloy::primitives::BlockNumber;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::primitives::{task::datalake::envelope::default_increment, ChainId};
 
-use super::collection::BlockSampledCollection;
+use super::{
+    collection::BlockSampledCollection, sampling::RandomSampling, storage_layout::StorageLayout,
+    timestamp::TimestampRange,
+};
 
 /// [`BlockSampledDatalake`] is a struct that represents a block sampled datalake.
 /// It contains the block range, the sampled property, and the increment.
 ///
 /// The block range is inclusive, so the block range is from `block_range_start` to `block_range_end`
+///
+/// Instead of a range, blocks can be sampled from an explicit `block_list`.
+/// In that case, the range covers the first to the last block of the list and the increment is 0.
+///
+/// With `random_sampling`, only a pseudo-random subset of the blocks in the range is sampled,
+/// derived from a seed committed in the datalake. See [`RandomSampling`].
+///
+/// The range can also be requested with timestamps, which are resolved to blocks before compilation.
+/// See [`TimestampRange`].
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", try_from = "BlockSampledDatalakeRequest")]
 pub struct BlockSampledDatalake {
     pub chain_id: ChainId,
     /// The start of the block range
@@ -21,6 +37,16 @@ pub struct BlockSampledDatalake {
     pub increment: u64,
     /// The sampled property
     pub sampled_property: BlockSampledCollection,
+    /// Explicit, strictly ascending list of blocks to sample. Empty if the range is sampled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block_list: Vec<BlockNumber>,
+    /// Pseudo-random sampling of the blocks in the range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_sampling: Option<RandomSampling>,
+    /// Requested timestamp range, which the blocks are resolved from.
+    /// It is committed along with the blocks once resolved.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub timestamp_range: Option<TimestampRange>,
 }
 
 impl BlockSampledDatalake {
@@ -37,6 +63,228 @@ impl BlockSampledDatalake {
             block_range_end,
             increment,
             sampled_property,
+            block_list: vec![],
+            random_sampling: None,
+            timestamp_range: None,
+        }
+    }
+
+    /// Create a datalake sampling every `increment` blocks of the range.
+    /// An increment of 0 marks a block list, so it is rejected here.
+    pub fn from_block_range(
+        chain_id: ChainId,
+        block_range_start: u64,
+        block_range_end: u64,
+        increment: u64,
+        sampled_property: BlockSampledCollection,
+    ) -> Result<Self> {
+        if increment == 0 {
+            bail!("Increment must be positive without a block list");
+        }
+        Ok(Self::new(
+            chain_id,
+            block_range_start,
+            block_range_end,
+            increment,
+            sampled_property,
+        ))
+    }
+
+    /// Create a datalake sampling the given blocks
+    pub fn from_block_list(
+        chain_id: ChainId,
+        block_list: Vec<BlockNumber>,
+        sampled_property: BlockSampledCollection,
+    ) -> Result<Self> {
+        if block_list.is_empty() {
+            bail!("Block list is empty");
+        }
+        if block_list.windows(2).any(|pair| pair[0] >= pair[1]) {
+            bail!("Block list must be strictly ascending");
+        }
+
+        Ok(Self {
+            chain_id,
+            block_range_start: block_list[0],
+            block_range_end: block_list[block_list.len() - 1],
+            increment: 0,
+            sampled_property,
+            block_list,
+            random_sampling: None,
+            timestamp_range: None,
+        })
+    }
+
+    /// Create a datalake sampling a pseudo-random subset of the blocks in the range
+    pub fn from_random_sampling(
+        chain_id: ChainId,
+        block_range_start: u64,
+        block_range_end: u64,
+        increment: u64,
+        random_sampling: RandomSampling,
+        sampled_property: BlockSampledCollection,
+    ) -> Result<Self> {
+        // check the sample set can be derived
+        random_sampling.derive_blocks(block_range_start, block_range_end, increment)?;
+
+        Ok(Self {
+            random_sampling: Some(random_sampling),
+            ..Self::new(
+                chain_id,
+                block_range_start,
+                block_range_end,
+                increment,
+                sampled_property,
+            )
+        })
+    }
+
+    /// Create a datalake over a timestamp range, to be resolved to blocks before compilation
+    pub fn from_timestamp_range(
+        chain_id: ChainId,
+        timestamp_range: TimestampRange,
+        sampled_property: BlockSampledCollection,
+    ) -> Self {
+        Self {
+            timestamp_range: Some(timestamp_range),
+            ..Self::new(chain_id, 0, 0, 1, sampled_property)
         }
     }
+
+    /// Whether the blocks are known, i.e. the timestamp range, if any, has been resolved
+    pub fn is_resolved(&self) -> bool {
+        self.timestamp_range.map_or(true, |timestamp_range| {
+            timestamp_range.latest_block.is_some()
+        })
+    }
+
+    /// Whether both datalakes sample the same blocks, whatever the sampled property
+    pub fn has_same_blocks(&self, other: &Self) -> bool {
+        self.chain_id == other.chain_id
+            && self.block_range_start == other.block_range_start
+            && self.block_range_end == other.block_range_end
+            && self.increment == other.increment
+            && self.block_list == other.block_list
+            && self.random_sampling == other.random_sampling
+            && self.timestamp_range == other.timestamp_range
+    }
+
+    /// Whether every block of the range is sampled
+    pub fn is_full_range(&self) -> bool {
+        self.block_list.is_empty() && self.random_sampling.is_none()
+    }
+
+    /// Blocks sampled by the datalake, in ascending order
+    pub fn blocks(&self) -> Vec<BlockNumber> {
+        if !self.block_list.is_empty() {
+            return self.block_list.clone();
+        }
+        match &self.random_sampling {
+            Some(sampling) => sampling
+                .derive_blocks(self.block_range_start, self.block_range_end, self.increment)
+                .expect("Random sampling is checked on creation"),
+            None => (self.block_range_start..=self.block_range_end)
+                .step_by(self.increment as usize)
+                .collect(),
+        }
+    }
+}
+
+/// Requested form of [`BlockSampledDatalake`].
+///
+/// Storage variables in the sampled property can be referred by name
+/// if the `storageLayout` of the contract is provided.
+/// The block range can be omitted if a `blockList` or `fromTimestamp` and `toTimestamp` are provided.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockSampledDatalakeRequest {
+    chain_id: ChainId,
+    block_range_start: Option<u64>,
+    block_range_end: Option<u64>,
+    #[serde(default = "default_increment")]
+    increment: u64,
+    sampled_property: String,
+    storage_layout: Option<StorageLayout>,
+    #[serde(default)]
+    block_list: Vec<BlockNumber>,
+    random_sampling: Option<RandomSampling>,
+    #[serde(flatten)]
+    timestamp_range: Option<TimestampRange>,
+}
+
+impl TryFrom<BlockSampledDatalakeRequest> for BlockSampledDatalake {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BlockSampledDatalakeRequest) -> Result<Self> {
+        let sampled_property = match &value.storage_layout {
+            Some(layout) => {
+                BlockSampledCollection::from_str_with_layout(&value.sampled_property, layout)?
+            }
+            None => BlockSampledCollection::from_str(&value.sampled_property)?,
+        };
+        if value.chain_id.is_starknet() {
+            bail!(
+                "Block sampled datalakes are not supported on {} yet",
+                value.chain_id
+            );
+        }
+
+        if let Some(range) = value.timestamp_range {
+            TimestampRange::new(range.from_timestamp, range.to_timestamp, range.time_step)?;
+            if range.latest_block.is_some()
+                && value.block_range_end.is_none()
+                && value.block_list.is_empty()
+            {
+                bail!("Resolved timestamp range requires its blocks");
+            }
+        }
+
+        let datalake = if !value.block_list.is_empty() {
+            if value.random_sampling.is_some() {
+                bail!("Random sampling is not supported with a block list");
+            }
+            Self::from_block_list(value.chain_id, value.block_list, sampled_property)?
+        } else {
+            match (value.block_range_start, value.block_range_end) {
+                (Some(block_range_start), Some(block_range_end)) => match value.random_sampling {
+                    Some(random_sampling) => Self::from_random_sampling(
+                        value.chain_id,
+                        block_range_start,
+                        block_range_end,
+                        value.increment,
+                        random_sampling,
+                        sampled_property,
+                    ),
+                    None => Self::from_block_range(
+                        value.chain_id,
+                        block_range_start,
+                        block_range_end,
+                        value.increment,
+                        sampled_property,
+                    ),
+                },
+                _ => match value.timestamp_range {
+                    Some(timestamp_range) => {
+                        if value.random_sampling.is_some() {
+                            bail!("Random sampling is not supported with a timestamp range");
+                        }
+                        Ok(Self::from_timestamp_range(
+                            value.chain_id,
+                            timestamp_range,
+                            sampled_property,
+                        ))
+                    }
+                    None => {
+                        bail!("Either a block range, a block list or a timestamp range is required")
+                    }
+                },
+            }?
+        };
+
+        // resolved blocks are kept along with the timestamp range they are resolved from
+        Ok(Self {
+            timestamp_range: value.timestamp_range,
+            ..datalake
+        })
+    }
 }
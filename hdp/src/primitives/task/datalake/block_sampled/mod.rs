@@ -1,22 +1,40 @@
+pub mod code_fields;
 pub mod collection;
 pub mod datalake;
+pub mod multi;
 pub mod rlp_fields;
+pub mod sampling;
+pub mod storage_layout;
+pub mod timestamp;
 
 // Export all types
+pub use code_fields::*;
 pub use collection::*;
 pub use datalake::*;
+pub use multi::*;
 pub use rlp_fields::*;
+pub use sampling::*;
+pub use storage_layout::*;
+pub use timestamp::*;
 
 #[cfg(test)]
 mod tests {
     use crate::primitives::{
-        solidity_types::traits::DatalakeCodecs, task::datalake::DatalakeCollection, ChainId,
+        aggregate_fn::{AggregationFunction, FunctionContext},
+        block::{account::Account, header::Header},
+        solidity_types::traits::DatalakeCodecs,
+        task::datalake::{
+            compute::Computation, envelope::DatalakeEnvelope, DatalakeCollection, DatalakeCompute,
+            DatalakeField,
+        },
+        ChainId,
     };
 
     use super::*;
     use alloy::{
+        consensus::constants::KECCAK_EMPTY,
         hex,
-        primitives::{Address, StorageKey},
+        primitives::{keccak256, Address, Bloom, Bytes, StorageKey, B256, U256},
     };
     use std::str::FromStr;
 
@@ -120,7 +138,8 @@ mod tests {
                 StorageKey::from_str(
                     "0x00000000000000000000000000000000000000000000000000000000000000ff"
                 )
-                .unwrap()
+                .unwrap(),
+                None
             )
         );
     }
@@ -136,6 +155,104 @@ mod tests {
         assert_eq!(serialized, [1, 7]);
     }
 
+    #[test]
+    fn test_derived_header_fields() {
+        let header = Header::new(
+            B256::ZERO,
+            B256::ZERO,
+            Address::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            Bloom::ZERO,
+            U256::ZERO,
+            20000000,
+            30000000,
+            12345678,
+            1700000000,
+            Bytes::new(),
+            B256::ZERO,
+            0,
+            Some(7),
+            Some(B256::ZERO),
+            Some(393216),
+            Some(0),
+            Some(B256::ZERO),
+        )
+        .rlp_encode();
+
+        assert_eq!(
+            HeaderField::Hash.decode_field_from_rlp(&header).unwrap(),
+            U256::from_be_bytes(keccak256(&header).0)
+        );
+        // 12345678 / 30000000 = 41.15%
+        assert_eq!(
+            HeaderField::GasUtilization
+                .decode_field_from_rlp(&header)
+                .unwrap(),
+            U256::from(4115)
+        );
+        assert_eq!(
+            HeaderField::BlobCount
+                .decode_field_from_rlp(&header)
+                .unwrap(),
+            U256::from(3)
+        );
+
+        let header_collection: BlockSampledCollection = "header.tx_count".parse().unwrap();
+        assert_eq!(
+            header_collection,
+            BlockSampledCollection::Header(HeaderField::TxCount)
+        );
+        assert_eq!(header_collection.serialize().unwrap(), [1, 23]);
+        assert_eq!(
+            BlockSampledCollection::deserialize(&[1, 20]).unwrap(),
+            BlockSampledCollection::Header(HeaderField::Hash)
+        );
+    }
+
+    #[test]
+    fn test_derived_header_fields_of_legacy_header() {
+        let header = Header::new(
+            B256::ZERO,
+            B256::ZERO,
+            Address::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            Bloom::ZERO,
+            U256::ZERO,
+            0,
+            0,
+            0,
+            0,
+            Bytes::new(),
+            B256::ZERO,
+            0,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .rlp_encode();
+
+        // no blob gas before Dencun
+        assert_eq!(
+            HeaderField::BlobCount
+                .decode_field_from_rlp(&header)
+                .unwrap(),
+            U256::ZERO
+        );
+        assert!(HeaderField::GasUtilization
+            .decode_field_from_rlp(&header)
+            .is_err());
+        assert!(HeaderField::BaseFeePerGas
+            .decode_field_from_rlp(&header)
+            .is_err());
+        assert!(HeaderField::TxCount.decode_field_from_rlp(&header).is_err());
+    }
+
     #[test]
     fn test_account_collection_serialize() {
         let account_collection = BlockSampledCollection::Account(
@@ -167,6 +284,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_code_collection_serialize() {
+        let address = Address::from_str("0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6").unwrap();
+        let code_collection: BlockSampledCollection =
+            "code.0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6.word[64]"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            code_collection,
+            BlockSampledCollection::Code(address, CodeField::Word(64))
+        );
+
+        let serialized = code_collection.serialize().unwrap();
+        assert_eq!(serialized.len(), 26);
+        assert_eq!(serialized[0], 4);
+        assert_eq!(serialized[21..], [1, 0, 0, 0, 64]);
+        assert_eq!(
+            BlockSampledCollection::deserialize(&serialized).unwrap(),
+            code_collection
+        );
+        assert_eq!(
+            code_collection
+                .to_string()
+                .parse::<BlockSampledCollection>()
+                .unwrap(),
+            code_collection
+        );
+
+        let code_collection = BlockSampledCollection::Code(address, CodeField::Size);
+        let serialized = code_collection.serialize().unwrap();
+        assert_eq!(serialized.len(), 22);
+        assert_eq!(
+            BlockSampledCollection::deserialize(&serialized).unwrap(),
+            code_collection
+        );
+        assert!(BlockSampledCollection::deserialize(&serialized[..21]).is_err());
+        assert!("code.0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6"
+            .parse::<BlockSampledCollection>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_account_is_contract() {
+        let eoa = Account::new(1, U256::from(1), B256::ZERO, KECCAK_EMPTY).rlp_encode();
+        let contract =
+            Account::new(1, U256::ZERO, B256::ZERO, keccak256([0x60, 0x80])).rlp_encode();
+        assert_eq!(
+            AccountField::IsContract
+                .decode_field_from_rlp(&eoa)
+                .unwrap(),
+            U256::ZERO
+        );
+        assert_eq!(
+            AccountField::IsContract
+                .decode_field_from_rlp(&contract)
+                .unwrap(),
+            U256::from(1)
+        );
+        assert_eq!(
+            "IS_CONTRACT".parse::<AccountField>().unwrap(),
+            AccountField::IsContract
+        );
+        assert_eq!(
+            AccountField::from_index(4).unwrap(),
+            AccountField::IsContract
+        );
+    }
+
     #[test]
     fn test_storage_collection_serialize() {
         let storage_collection = BlockSampledCollection::Storage(
@@ -175,6 +360,7 @@ mod tests {
                 "0x000000000000000000000000000000000000000000000000000000000000fffe",
             )
             .unwrap(),
+            None,
         );
 
         let serialized = storage_collection.serialize().unwrap();
@@ -187,4 +373,481 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_storage_collection_with_slice() {
+        let storage_collection = BlockSampledCollection::from_str(
+            "storage.0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6.0x0000000000000000000000000000000000000000000000000000000000000008[14:14]",
+        )
+        .unwrap();
+        assert_eq!(
+            storage_collection,
+            BlockSampledCollection::Storage(
+                Address::from_str("0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6").unwrap(),
+                StorageKey::from(U256::from(8)),
+                Some(StorageSlice::new(14, 14, false).unwrap()),
+            )
+        );
+
+        let serialized = storage_collection.serialize().unwrap();
+        assert_eq!(serialized.len(), 55);
+        assert_eq!(serialized[53..], [14, 14]);
+        assert_eq!(storage_collection.value_bytes(), 14);
+        assert_eq!(
+            BlockSampledCollection::Header(HeaderField::Beneficiary).value_bytes(),
+            20
+        );
+        assert_eq!(
+            BlockSampledCollection::deserialize(&serialized).unwrap(),
+            storage_collection
+        );
+        assert_eq!(
+            BlockSampledCollection::from_str(&storage_collection.to_string()).unwrap(),
+            storage_collection
+        );
+
+        // reserve1 of uniswap v2 pair, packed at [14:14]
+        let slot_value =
+            U256::from_str("0x66f0c3d30000000000000000001a8cd4b1b40000000000000000057c1c5c73de")
+                .unwrap();
+        assert_eq!(
+            StorageSlice::new(14, 14, false)
+                .unwrap()
+                .extract(slot_value),
+            U256::from_str("0x1a8cd4b1b4").unwrap()
+        );
+        assert_eq!(
+            StorageSlice::new(28, 4, false).unwrap().extract(slot_value),
+            U256::from(0x66f0c3d3u64)
+        );
+        assert!(StorageSlice::new(30, 4, false).is_err());
+    }
+
+    #[test]
+    fn test_signed_storage_slice() {
+        // tick of uniswap v3 slot0, int24 packed at [20:3]
+        let storage_collection = BlockSampledCollection::from_str(
+            "storage.0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6.0x0000000000000000000000000000000000000000000000000000000000000000[20:3:signed]",
+        )
+        .unwrap();
+        let slice = StorageSlice::new(20, 3, true).unwrap();
+        assert_eq!(
+            storage_collection,
+            BlockSampledCollection::Storage(
+                Address::from_str("0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6").unwrap(),
+                StorageKey::ZERO,
+                Some(slice),
+            )
+        );
+
+        let serialized = storage_collection.serialize().unwrap();
+        assert_eq!(serialized.len(), 55);
+        assert_eq!(serialized[53..], [20, 0x83]);
+        assert_eq!(
+            BlockSampledCollection::deserialize(&serialized).unwrap(),
+            storage_collection
+        );
+        let mut invalid = serialized.clone();
+        invalid[54] = 0x40 | 3;
+        assert!(BlockSampledCollection::deserialize(&invalid).is_err());
+
+        // signed values are only aggregated as such
+        let datalake = DatalakeEnvelope::BlockSampled(BlockSampledDatalake::new(
+            ChainId::EthereumSepolia,
+            100,
+            110,
+            1,
+            storage_collection.clone(),
+        ));
+        let task = |compute| DatalakeCompute::new(datalake.clone(), compute);
+        assert!(task(Computation::new(AggregationFunction::SUM, None))
+            .validate()
+            .is_err());
+        assert!(task(Computation::new(
+            AggregationFunction::MIN,
+            Some(FunctionContext::default().with_signed())
+        ))
+        .validate()
+        .is_ok());
+        // the signed context is only taken by functions that support it
+        assert!(task(Computation::new(
+            AggregationFunction::SUM,
+            Some(FunctionContext::default().with_signed())
+        ))
+        .validate()
+        .is_err());
+        assert_eq!(
+            BlockSampledCollection::from_str(&storage_collection.to_string()).unwrap(),
+            storage_collection
+        );
+
+        // tick -887272 is sign extended, positive tick is left as is
+        let negative =
+            U256::from_str("0x000000000000000001f276180000000000000000000000000000000000000001")
+                .unwrap();
+        assert_eq!(
+            slice.extract(negative),
+            U256::from_str("0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffff27618")
+                .unwrap()
+        );
+        let positive =
+            U256::from_str("0x000000000000000001000d880000000000000000000000000000000000000001")
+                .unwrap();
+        assert_eq!(slice.extract(positive), U256::from(0xd88));
+        assert_eq!(
+            StorageSlice::new(20, 3, false).unwrap().extract(negative),
+            U256::from(0xf27618)
+        );
+        assert!(StorageSlice::from_str("[20:3:maybe]").is_err());
+    }
+
+    #[test]
+    fn test_block_datalake_with_storage_layout() {
+        let json = r#"
+        {
+          "chainId": "ETHEREUM_SEPOLIA",
+          "blockRangeStart": 10399990,
+          "blockRangeEnd": 10400000,
+          "sampledProperty": "storage.0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6.reserve1",
+          "storageLayout": {
+            "storage": [
+              { "label": "reserve0", "offset": 0, "slot": "8", "type": "t_uint112" },
+              { "label": "reserve1", "offset": 14, "slot": "8", "type": "t_uint112" }
+            ],
+            "types": {
+              "t_uint112": { "encoding": "inplace", "label": "uint112", "numberOfBytes": "14" }
+            }
+          }
+        }
+        "#;
+        let block_datalake: BlockSampledDatalake = serde_json::from_str(json).unwrap();
+        assert_eq!(block_datalake.increment, 1);
+        assert_eq!(
+            block_datalake.sampled_property,
+            BlockSampledCollection::Storage(
+                Address::from_str("0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6").unwrap(),
+                StorageKey::from(U256::from(8)),
+                Some(StorageSlice::new(14, 14, false).unwrap()),
+            )
+        );
+    }
+
+    #[test]
+    fn test_block_datalake_with_block_list() {
+        let block_list = vec![10399990, 10400100, 10416000];
+        let block_datalake = BlockSampledDatalake::from_block_list(
+            ChainId::EthereumSepolia,
+            block_list.clone(),
+            "header.base_fee_per_gas".parse().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(block_datalake.block_range_start, 10399990);
+        assert_eq!(block_datalake.block_range_end, 10416000);
+        assert_eq!(block_datalake.increment, 0);
+        assert_eq!(block_datalake.blocks(), block_list);
+
+        let encoded = block_datalake.encode().unwrap();
+        // block list is packed as 8 bytes block numbers after the sampled property
+        assert_eq!(encoded.len(), 7 * 32 + 2 * 64);
+        assert_eq!(
+            encoded[encoded.len() - 32..][..24],
+            hex::decode("00000000009eb0f600000000009eb16400000000009eef80").unwrap()
+        );
+        let decoded_datalake = BlockSampledDatalake::decode(&encoded).unwrap();
+        assert_eq!(decoded_datalake, block_datalake);
+        assert_ne!(
+            block_datalake.commit(),
+            BlockSampledDatalake::new(
+                ChainId::EthereumSepolia,
+                10399990,
+                10416000,
+                1,
+                "header.base_fee_per_gas".parse().unwrap(),
+            )
+            .commit()
+        );
+
+        let block_datalake: BlockSampledDatalake = serde_json::from_str(
+            r#"{"chainId": "ETHEREUM_SEPOLIA", "blockList": [10399990, 10400100, 10416000], "sampledProperty": "header.base_fee_per_gas"}"#,
+        )
+        .unwrap();
+        assert_eq!(block_datalake.blocks(), block_list);
+
+        assert!(BlockSampledDatalake::from_block_list(
+            ChainId::EthereumSepolia,
+            vec![10400100, 10399990],
+            "header.number".parse().unwrap(),
+        )
+        .is_err());
+        assert!(BlockSampledDatalake::from_block_list(
+            ChainId::EthereumSepolia,
+            vec![],
+            "header.number".parse().unwrap(),
+        )
+        .is_err());
+
+        // an increment of 0 marks a block list, which is then required
+        assert!(serde_json::from_str::<BlockSampledDatalake>(
+            r#"{"chainId": "ETHEREUM_SEPOLIA", "blockRangeStart": 10399990, "blockRangeEnd": 10400000, "increment": 0, "sampledProperty": "header.number"}"#,
+        )
+        .is_err());
+        assert!(serde_json::from_str::<BlockSampledDatalake>(
+            r#"{"chainId": "ETHEREUM_SEPOLIA", "blockRangeStart": 10399990, "blockRangeEnd": 10400000, "increment": 0, "blockList": [], "sampledProperty": "header.number"}"#,
+        )
+        .is_err());
+        let encoded = BlockSampledDatalake::new(
+            ChainId::EthereumSepolia,
+            10399990,
+            10400000,
+            0,
+            "header.number".parse().unwrap(),
+        )
+        .encode()
+        .unwrap();
+        assert!(BlockSampledDatalake::decode(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_block_datalake_with_random_sampling() {
+        let random_sampling = RandomSampling::new(B256::repeat_byte(0x42), 12, Some(10416001));
+        let block_datalake = BlockSampledDatalake::from_random_sampling(
+            ChainId::EthereumSepolia,
+            10000000,
+            10416000,
+            100,
+            random_sampling,
+            "header.base_fee_per_gas".parse().unwrap(),
+        )
+        .unwrap();
+        let blocks = block_datalake.blocks();
+        assert_eq!(blocks.len(), 12);
+        assert_eq!(
+            blocks,
+            random_sampling
+                .derive_blocks(10000000, 10416000, 100)
+                .unwrap()
+        );
+
+        let encoded = block_datalake.encode().unwrap();
+        assert_eq!(
+            BlockSampledDatalake::decode(&encoded).unwrap(),
+            block_datalake
+        );
+        // seed is committed
+        let other_seed = BlockSampledDatalake::from_random_sampling(
+            ChainId::EthereumSepolia,
+            10000000,
+            10416000,
+            100,
+            RandomSampling::new(B256::repeat_byte(0x43), 12, Some(10416001)),
+            "header.base_fee_per_gas".parse().unwrap(),
+        )
+        .unwrap();
+        assert_ne!(other_seed.commit(), block_datalake.commit());
+        assert_ne!(other_seed.blocks(), blocks);
+
+        let block_datalake: BlockSampledDatalake = serde_json::from_str(
+            r#"{
+                "chainId": "ETHEREUM_SEPOLIA",
+                "blockRangeStart": 10000000,
+                "blockRangeEnd": 10416000,
+                "increment": 100,
+                "sampledProperty": "header.base_fee_per_gas",
+                "randomSampling": {
+                    "seed": "0x4242424242424242424242424242424242424242424242424242424242424242",
+                    "sampleSize": 12,
+                    "seedBlock": 10416001
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(block_datalake.blocks(), blocks);
+
+        assert!(BlockSampledDatalake::from_random_sampling(
+            ChainId::EthereumSepolia,
+            10000000,
+            10000010,
+            1,
+            RandomSampling::new(B256::ZERO, 12, None),
+            "header.number".parse().unwrap(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_block_datalake_with_timestamp_range() {
+        let block_datalake: BlockSampledDatalake = serde_json::from_str(
+            r#"{
+                "chainId": "ETHEREUM_SEPOLIA",
+                "fromTimestamp": 1704067200,
+                "toTimestamp": 1706745600,
+                "timeStep": 604800,
+                "sampledProperty": "header.base_fee_per_gas"
+            }"#,
+        )
+        .unwrap();
+        let timestamp_range = TimestampRange::new(1704067200, 1706745600, Some(604800)).unwrap();
+        assert_eq!(block_datalake.timestamp_range, Some(timestamp_range));
+        assert!(!block_datalake.is_resolved());
+        assert!(block_datalake.encode().is_err());
+        assert_eq!(
+            block_datalake,
+            BlockSampledDatalake::from_timestamp_range(
+                ChainId::EthereumSepolia,
+                timestamp_range,
+                "header.base_fee_per_gas".parse().unwrap(),
+            )
+        );
+
+        // resolved request keeps the timestamp range along with the blocks
+        let resolved_datalake = BlockSampledDatalake {
+            timestamp_range: Some(timestamp_range.resolve(5200000)),
+            ..BlockSampledDatalake::from_block_list(
+                ChainId::EthereumSepolia,
+                vec![4996000, 5041000, 5086000, 5131000, 5176000],
+                "header.base_fee_per_gas".parse().unwrap(),
+            )
+            .unwrap()
+        };
+        assert!(resolved_datalake.is_resolved());
+        let serialized = serde_json::to_string(&resolved_datalake).unwrap();
+        assert!(serialized.contains(r#""fromTimestamp":1704067200"#));
+        assert!(serialized.contains(r#""latestBlock":5200000"#));
+        let deserialized: BlockSampledDatalake = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, resolved_datalake);
+
+        // timestamp range is committed along with the blocks
+        let decoded_datalake =
+            BlockSampledDatalake::decode(&resolved_datalake.encode().unwrap()).unwrap();
+        assert_eq!(decoded_datalake, resolved_datalake);
+        let blocks_only = BlockSampledDatalake {
+            timestamp_range: None,
+            ..resolved_datalake.clone()
+        };
+        assert_ne!(blocks_only.commit(), resolved_datalake.commit());
+        assert_eq!(
+            BlockSampledDatalake::decode(&blocks_only.encode().unwrap()).unwrap(),
+            blocks_only
+        );
+
+        // resolved range without a time step, ending at the latest block
+        let resolved_range = BlockSampledDatalake {
+            timestamp_range: Some(
+                TimestampRange::new(1704067200, u64::MAX, None)
+                    .unwrap()
+                    .resolve(5200000),
+            ),
+            ..BlockSampledDatalake::new(
+                ChainId::EthereumSepolia,
+                4996000,
+                5200000,
+                1,
+                "header.base_fee_per_gas".parse().unwrap(),
+            )
+        };
+        assert!(resolved_range.is_resolved());
+        assert_eq!(
+            BlockSampledDatalake::decode(&resolved_range.encode().unwrap()).unwrap(),
+            resolved_range
+        );
+
+        assert!(serde_json::from_str::<BlockSampledDatalake>(
+            r#"{"chainId": "ETHEREUM_SEPOLIA", "fromTimestamp": 1706745600, "toTimestamp": 1704067200, "sampledProperty": "header.number"}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_multi_block_sampled_datalake() {
+        let multi_datalake: MultiBlockSampledDatalake = serde_json::from_str(
+            r#"{
+                "chainId": "ETHEREUM_SEPOLIA",
+                "blockRangeStart": 5515020,
+                "blockRangeEnd": 5515039,
+                "increment": 10,
+                "sampledProperties": [
+                    "header.base_fee_per_gas",
+                    "header.gas_used",
+                    "storage.0x75CeC1db9dCeb703200EAa6595f66885C962B920.0x0000000000000000000000000000000000000000000000000000000000000001"
+                ]
+            }"#,
+        )
+        .unwrap();
+        let datalakes = multi_datalake.datalakes();
+        assert_eq!(datalakes.len(), 3);
+        assert_eq!(
+            datalakes[1],
+            BlockSampledDatalake::new(
+                ChainId::EthereumSepolia,
+                5515020,
+                5515039,
+                10,
+                BlockSampledCollection::Header(HeaderField::GasUsed),
+            )
+        );
+        assert!(datalakes[2].has_same_blocks(&datalakes[0]));
+        assert_eq!(multi_datalake.blocks(), vec![5515020, 5515030]);
+
+        // each property is committed as its own datalake
+        assert_ne!(datalakes[0].commit(), datalakes[1].commit());
+
+        let serialized = serde_json::to_string(&multi_datalake).unwrap();
+        assert!(!serialized.contains("sampledProperty\""));
+        let deserialized: MultiBlockSampledDatalake = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, multi_datalake);
+
+        // properties must be sampled over the same blocks
+        let other_blocks = BlockSampledDatalake::new(
+            ChainId::EthereumSepolia,
+            5515020,
+            5515039,
+            1,
+            BlockSampledCollection::Header(HeaderField::GasUsed),
+        );
+        assert!(!other_blocks.has_same_blocks(&datalakes[0]));
+        assert!(MultiBlockSampledDatalake::from_datalakes(vec![
+            datalakes[0].clone(),
+            other_blocks.clone()
+        ])
+        .is_err());
+        assert!(MultiBlockSampledDatalake::new(other_blocks, vec![]).is_err());
+
+        assert!(serde_json::from_str::<MultiBlockSampledDatalake>(
+            r#"{"chainId": "ETHEREUM_SEPOLIA", "blockRangeStart": 1, "blockRangeEnd": 2, "sampledProperty": "header.number", "sampledProperties": ["header.number"]}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_starknet_collections_are_rejected() {
+        // Starknet state is not fetched by any provider yet
+        assert!("starknet_header.sequencer_address"
+            .parse::<BlockSampledCollection>()
+            .is_err());
+        assert!(
+            "starknet_storage.0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7.0x3c1"
+                .parse::<BlockSampledCollection>()
+                .is_err()
+        );
+        assert!(BlockSampledCollection::deserialize(&[5, 0]).is_err());
+
+        assert!(serde_json::from_str::<BlockSampledDatalake>(
+            r#"{"chainId": "STARKNET_SEPOLIA", "blockRangeStart": 100000, "blockRangeEnd": 100010, "sampledProperty": "starknet_header.timestamp"}"#,
+        )
+        .is_err());
+        assert!(serde_json::from_str::<BlockSampledDatalake>(
+            r#"{"chainId": "STARKNET_SEPOLIA", "blockRangeStart": 100000, "blockRangeEnd": 100010, "sampledProperty": "header.timestamp"}"#,
+        )
+        .is_err());
+        let encoded = BlockSampledDatalake::new(
+            ChainId::StarknetSepolia,
+            100000,
+            100010,
+            1,
+            "header.timestamp".parse().unwrap(),
+        )
+        .encode()
+        .unwrap();
+        assert!(BlockSampledDatalake::decode(&encoded).is_err());
+    }
 }
@@ -0,0 +1,134 @@
+//! Several properties sampled over the same blocks.
+//!
+//! Each property is committed as its own [`BlockSampledDatalake`] task with its own computation,
+//! so the commitments are unchanged. The proofs shared by the properties, e.g. the headers,
+//! or the account proofs of several slots of one account, are fetched and included once.
+//!
+//! The requested form is a block sampled datalake with `sampledProperties` instead of `sampledProperty`.
+
+use alloy::primitives::BlockNumber;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{collection::BlockSampledCollection, datalake::BlockSampledDatalake};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    try_from = "MultiBlockSampledDatalakeRequest",
+    into = "MultiBlockSampledDatalakeRequest"
+)]
+pub struct MultiBlockSampledDatalake {
+    /// Datalake of each sampled property, all sampling the same blocks
+    datalakes: Vec<BlockSampledDatalake>,
+}
+
+impl MultiBlockSampledDatalake {
+    /// Create a datalake sampling the given properties over the blocks of the given datalake
+    pub fn new(
+        datalake: BlockSampledDatalake,
+        sampled_properties: Vec<BlockSampledCollection>,
+    ) -> Result<Self> {
+        Self::from_datalakes(
+            sampled_properties
+                .into_iter()
+                .map(|sampled_property| BlockSampledDatalake {
+                    sampled_property,
+                    ..datalake.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// Create a datalake from datalakes sampling the same blocks
+    pub fn from_datalakes(datalakes: Vec<BlockSampledDatalake>) -> Result<Self> {
+        if datalakes.is_empty() {
+            bail!("No sampled property");
+        }
+        if datalakes
+            .iter()
+            .any(|datalake| !datalake.has_same_blocks(&datalakes[0]))
+        {
+            bail!("Sampled properties must be sampled over the same blocks");
+        }
+        Ok(Self { datalakes })
+    }
+
+    pub fn datalakes(&self) -> &[BlockSampledDatalake] {
+        &self.datalakes
+    }
+
+    pub fn into_datalakes(self) -> Vec<BlockSampledDatalake> {
+        self.datalakes
+    }
+
+    pub fn sampled_properties(&self) -> Vec<&BlockSampledCollection> {
+        self.datalakes
+            .iter()
+            .map(|datalake| &datalake.sampled_property)
+            .collect()
+    }
+
+    /// Blocks sampled by the datalake, in ascending order
+    pub fn blocks(&self) -> Vec<BlockNumber> {
+        self.datalakes[0].blocks()
+    }
+}
+
+impl From<BlockSampledDatalake> for MultiBlockSampledDatalake {
+    fn from(datalake: BlockSampledDatalake) -> Self {
+        Self {
+            datalakes: vec![datalake],
+        }
+    }
+}
+
+/// Requested form of [`MultiBlockSampledDatalake`].
+///
+/// Every field but `sampledProperties` is the same as in the requested form of [`BlockSampledDatalake`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MultiBlockSampledDatalakeRequest {
+    sampled_properties: Vec<String>,
+    #[serde(flatten)]
+    blocks: Map<String, Value>,
+}
+
+impl TryFrom<MultiBlockSampledDatalakeRequest> for MultiBlockSampledDatalake {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MultiBlockSampledDatalakeRequest) -> Result<Self> {
+        if value.blocks.contains_key("sampledProperty") {
+            bail!("Use `sampledProperties` to sample several properties");
+        }
+        let datalakes = value
+            .sampled_properties
+            .into_iter()
+            .map(|sampled_property| {
+                let mut request = value.blocks.clone();
+                request.insert("sampledProperty".to_string(), sampled_property.into());
+                Ok(serde_json::from_value(Value::Object(request))?)
+            })
+            .collect::<Result<Vec<BlockSampledDatalake>>>()?;
+        Self::from_datalakes(datalakes)
+    }
+}
+
+impl From<MultiBlockSampledDatalake> for MultiBlockSampledDatalakeRequest {
+    fn from(value: MultiBlockSampledDatalake) -> Self {
+        let sampled_properties = value
+            .sampled_properties()
+            .into_iter()
+            .map(|sampled_property| sampled_property.to_string())
+            .collect();
+        let mut blocks = match serde_json::to_value(&value.datalakes[0]) {
+            Ok(Value::Object(blocks)) => blocks,
+            _ => unreachable!("Block sampled datalake serializes to an object"),
+        };
+        blocks.remove("sampledProperty");
+        Self {
+            sampled_properties,
+            blocks,
+        }
+    }
+}
@@ -4,8 +4,12 @@
 
 use std::{fmt::Display, str::FromStr};
 
-use alloy::primitives::U256;
-use anyhow::{bail, Result};
+use alloy::{
+    consensus::constants::KECCAK_EMPTY,
+    eips::eip4844::DATA_GAS_PER_BLOB,
+    primitives::{keccak256, U256},
+};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -36,6 +40,15 @@ pub enum HeaderField {
     BlobGasUsed,
     ExcessBlobGas,
     ParentBeaconBlockRoot,
+    // ===== Derived fields =====
+    /// Keccak hash of the rlp encoded header
+    Hash,
+    /// Gas used over gas limit, in basis points
+    GasUtilization,
+    /// Number of blobs, derived from the blob gas used
+    BlobCount,
+    /// Number of transactions, proven via the transactions trie
+    TxCount,
 }
 
 impl HeaderField {
@@ -61,6 +74,10 @@ impl HeaderField {
             "BLOB_GAS_USED".to_string(),
             "EXCESS_BLOB_GAS".to_string(),
             "PARENT_BEACON_BLOCK_ROOT".to_string(),
+            "HASH".to_string(),
+            "GAS_UTILIZATION".to_string(),
+            "BLOB_COUNT".to_string(),
+            "TX_COUNT".to_string(),
         ]
     }
 
@@ -103,6 +120,10 @@ impl DatalakeField for HeaderField {
             17 => Ok(HeaderField::BlobGasUsed),
             18 => Ok(HeaderField::ExcessBlobGas),
             19 => Ok(HeaderField::ParentBeaconBlockRoot),
+            20 => Ok(HeaderField::Hash),
+            21 => Ok(HeaderField::GasUtilization),
+            22 => Ok(HeaderField::BlobCount),
+            23 => Ok(HeaderField::TxCount),
             _ => bail!("Unknown header field"),
         }
     }
@@ -129,47 +150,74 @@ impl DatalakeField for HeaderField {
             HeaderField::BlobGasUsed => 17,
             HeaderField::ExcessBlobGas => 18,
             HeaderField::ParentBeaconBlockRoot => 19,
+            HeaderField::Hash => 20,
+            HeaderField::GasUtilization => 21,
+            HeaderField::BlobCount => 22,
+            HeaderField::TxCount => 23,
         }
     }
 
-    fn decode_field_from_rlp(&self, header_rlp: &[u8]) -> U256 {
+    /// Decodes the field from the rlp encoded header.
+    ///
+    /// `BlobCount` is `0` for blocks without blob gas, i.e. before Dencun. `TxCount` is not
+    /// part of the header and is an error, it is proven with the transactions trie instead.
+    fn decode_field_from_rlp(&self, header_rlp: &[u8]) -> Result<U256> {
         let decoded = <Header>::rlp_decode(header_rlp);
 
-        match self {
+        let value = match self {
             HeaderField::ParentHash => decoded.parent_hash.into(),
             HeaderField::OmmerHash => decoded.ommers_hash.into(),
-            HeaderField::Beneficiary => {
-                U256::from_str_radix(&decoded.beneficiary.to_string(), 16).unwrap()
-            }
+            HeaderField::Beneficiary => U256::from_be_slice(decoded.beneficiary.as_slice()),
             HeaderField::StateRoot => decoded.state_root.into(),
             HeaderField::TransactionsRoot => decoded.transactions_root.into(),
             HeaderField::ReceiptsRoot => decoded.receipts_root.into(),
             HeaderField::LogsBloom => U256::from_str_radix(&decoded.logs_bloom.to_string(), 16)
-                .expect("logs bloom does not match U256"),
+                .context("logs bloom does not match U256")?,
             HeaderField::Difficulty => U256::from(decoded.difficulty),
             HeaderField::Number => U256::from(decoded.number),
             HeaderField::GasLimit => U256::from(decoded.gas_limit),
             HeaderField::GasUsed => U256::from(decoded.gas_used),
             HeaderField::Timestamp => U256::from(decoded.timestamp),
-            HeaderField::ExtraData => todo!("extra data doesn't fit into U256"),
+            HeaderField::ExtraData => bail!("extra data doesn't fit into U256"),
             HeaderField::MixHash => decoded.mix_hash.into(),
             HeaderField::Nonce => U256::from(decoded.nonce),
             HeaderField::BaseFeePerGas => U256::from(
                 decoded
                     .base_fee_per_gas
-                    .expect("base fee per gas does not exist"),
+                    .context("base fee per gas does not exist")?,
             ),
             HeaderField::WithdrawalsRoot => decoded
                 .withdrawals_root
-                .expect("withdrawals root does not exist")
+                .context("withdrawals root does not exist")?
                 .into(),
-            HeaderField::BlobGasUsed => U256::from(decoded.blob_gas_used.unwrap()),
-            HeaderField::ExcessBlobGas => U256::from(decoded.excess_blob_gas.unwrap()),
+            HeaderField::BlobGasUsed => U256::from(
+                decoded
+                    .blob_gas_used
+                    .context("blob gas used does not exist")?,
+            ),
+            HeaderField::ExcessBlobGas => U256::from(
+                decoded
+                    .excess_blob_gas
+                    .context("excess blob gas does not exist")?,
+            ),
             HeaderField::ParentBeaconBlockRoot => decoded
                 .parent_beacon_block_root
-                .expect("parent beacon block root does not exist")
+                .context("parent beacon block root does not exist")?
                 .into(),
-        }
+            HeaderField::Hash => keccak256(header_rlp).into(),
+            HeaderField::GasUtilization => (U256::from(decoded.gas_used) * U256::from(10_000))
+                .checked_div(U256::from(decoded.gas_limit))
+                .context("gas utilization of a block with zero gas limit")?,
+            HeaderField::BlobCount => {
+                U256::from(decoded.blob_gas_used.unwrap_or_default() / DATA_GAS_PER_BLOB)
+            }
+            HeaderField::TxCount => {
+                bail!(
+                    "tx count is not decodable from header, it is proven via the transactions trie"
+                )
+            }
+        };
+        Ok(value)
     }
 }
 
@@ -198,6 +246,10 @@ impl FromStr for HeaderField {
             "BLOB_GAS_USED" => Ok(HeaderField::BlobGasUsed),
             "EXCESS_BLOB_GAS" => Ok(HeaderField::ExcessBlobGas),
             "PARENT_BEACON_BLOCK_ROOT" => Ok(HeaderField::ParentBeaconBlockRoot),
+            "HASH" => Ok(HeaderField::Hash),
+            "GAS_UTILIZATION" => Ok(HeaderField::GasUtilization),
+            "BLOB_COUNT" => Ok(HeaderField::BlobCount),
+            "TX_COUNT" => Ok(HeaderField::TxCount),
             _ => bail!("Unknown header field"),
         }
     }
@@ -234,6 +286,10 @@ impl Display for HeaderField {
             HeaderField::BlobGasUsed => write!(f, "BLOB_GAS_USED"),
             HeaderField::ExcessBlobGas => write!(f, "EXCESS_BLOB_GAS"),
             HeaderField::ParentBeaconBlockRoot => write!(f, "PARENT_BEACON_BLOCK_ROOT"),
+            HeaderField::Hash => write!(f, "HASH"),
+            HeaderField::GasUtilization => write!(f, "GAS_UTILIZATION"),
+            HeaderField::BlobCount => write!(f, "BLOB_COUNT"),
+            HeaderField::TxCount => write!(f, "TX_COUNT"),
         }
     }
 }
@@ -247,6 +303,8 @@ pub enum AccountField {
     Balance,
     StorageRoot,
     CodeHash,
+    /// 1 if the account has code, derived from the code hash
+    IsContract,
 }
 
 impl AccountField {
@@ -256,6 +314,7 @@ impl AccountField {
             "BALANCE".to_string(),
             "STORAGE_ROOT".to_string(),
             "CODE_HASH".to_string(),
+            "IS_CONTRACT".to_string(),
         ]
     }
 }
@@ -269,6 +328,7 @@ impl FromStr for AccountField {
             "BALANCE" => Ok(AccountField::Balance),
             "STORAGE_ROOT" => Ok(AccountField::StorageRoot),
             "CODE_HASH" => Ok(AccountField::CodeHash),
+            "IS_CONTRACT" => Ok(AccountField::IsContract),
             _ => bail!("Unknown account field"),
         }
     }
@@ -289,6 +349,7 @@ impl DatalakeField for AccountField {
             1 => Ok(AccountField::Balance),
             2 => Ok(AccountField::StorageRoot),
             3 => Ok(AccountField::CodeHash),
+            4 => Ok(AccountField::IsContract),
             _ => bail!("Invalid account field index"),
         }
     }
@@ -299,17 +360,20 @@ impl DatalakeField for AccountField {
             AccountField::Balance => 1,
             AccountField::StorageRoot => 2,
             AccountField::CodeHash => 3,
+            AccountField::IsContract => 4,
         }
     }
 
-    fn decode_field_from_rlp(&self, account_rlp: &[u8]) -> U256 {
+    fn decode_field_from_rlp(&self, account_rlp: &[u8]) -> Result<U256> {
         let decoded = <Account>::rlp_decode(account_rlp);
-        match self {
+        let value = match self {
             AccountField::Nonce => U256::from(decoded.nonce),
             AccountField::Balance => U256::from(decoded.balance),
             AccountField::StorageRoot => decoded.storage_root.into(),
             AccountField::CodeHash => decoded.code_hash.into(),
-        }
+            AccountField::IsContract => U256::from(decoded.code_hash != KECCAK_EMPTY),
+        };
+        Ok(value)
     }
 }
 
@@ -320,6 +384,7 @@ impl Display for AccountField {
             AccountField::Balance => write!(f, "BALANCE"),
             AccountField::StorageRoot => write!(f, "STORAGE_ROOT"),
             AccountField::CodeHash => write!(f, "CODE_HASH"),
+            AccountField::IsContract => write!(f, "IS_CONTRACT"),
         }
     }
 }
@@ -0,0 +1,188 @@
+//! Verifiable pseudo-random sampling of the blocks of a [`BlockSampledDatalake`].
+//!
+//! Sampled blocks are derived from a seed that is committed in the datalake,
+//! so the Cairo program can re-derive the same sample set:
+//!
+//! 1. Candidates are the blocks of the range, `start + k * increment` for `k` in `0..n`.
+//! 2. For `i = 0, 1, 2, ...`, `k = keccak256(seed ++ u64_be(i)) mod n` is picked,
+//!    skipping the ones that are already picked, until `sample_size` blocks are picked.
+//! 3. Picked blocks are sorted in ascending order.
+//!
+//! [`BlockSampledDatalake`]: super::BlockSampledDatalake
+
+use std::collections::BTreeSet;
+
+use alloy::primitives::{keccak256, BlockNumber, B256, U256};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomSampling {
+    /// Seed of the sampling, e.g. a future block hash or a user provided nonce
+    pub seed: B256,
+    /// Number of blocks to sample
+    pub sample_size: u64,
+    /// If set, the seed must be the hash of this block, which is proven along with the datalake.
+    /// The seed block must come after the block range, so the seed is unknown when the range is fixed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_block: Option<BlockNumber>,
+}
+
+impl RandomSampling {
+    pub fn new(seed: B256, sample_size: u64, seed_block: Option<BlockNumber>) -> Self {
+        Self {
+            seed,
+            sample_size,
+            seed_block,
+        }
+    }
+
+    /// Derive the sampled blocks from the block range, in ascending order
+    pub fn derive_blocks(
+        &self,
+        block_range_start: BlockNumber,
+        block_range_end: BlockNumber,
+        increment: u64,
+    ) -> Result<Vec<BlockNumber>> {
+        if increment == 0 || block_range_end < block_range_start {
+            bail!("Invalid block range for random sampling");
+        }
+        if let Some(seed_block) = self.seed_block {
+            if seed_block <= block_range_end {
+                bail!(
+                    "Seed block {} must come after the block range end {}",
+                    seed_block,
+                    block_range_end
+                );
+            }
+        }
+        let candidates = (block_range_end - block_range_start) / increment + 1;
+        if self.sample_size == 0 || self.sample_size > candidates {
+            bail!(
+                "Invalid sample size {} for {} candidate blocks",
+                self.sample_size,
+                candidates
+            );
+        }
+
+        let mut picked = BTreeSet::new();
+        let mut preimage = [0u8; 40];
+        preimage[..32].copy_from_slice(self.seed.as_slice());
+        let mut counter: u64 = 0;
+        while (picked.len() as u64) < self.sample_size {
+            preimage[32..].copy_from_slice(&counter.to_be_bytes());
+            let random = U256::from_be_bytes(keccak256(preimage).0);
+            picked.insert((random % U256::from(candidates)).to::<u64>());
+            counter += 1;
+        }
+
+        Ok(picked
+            .into_iter()
+            .map(|k| block_range_start + k * increment)
+            .collect())
+    }
+
+    /// Serialize as the seed, the sample size and the optional seed block,
+    /// with numbers as 8 bytes big endian.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = self.seed.to_vec();
+        serialized.extend_from_slice(&self.sample_size.to_be_bytes());
+        if let Some(seed_block) = self.seed_block {
+            serialized.extend_from_slice(&seed_block.to_be_bytes());
+        }
+        serialized
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        let seed_block = match serialized.len() {
+            40 => None,
+            48 => Some(BlockNumber::from_be_bytes(
+                serialized[40..48].try_into().unwrap(),
+            )),
+            _ => bail!("Invalid random sampling"),
+        };
+        Ok(Self {
+            seed: B256::from_slice(&serialized[..32]),
+            sample_size: u64::from_be_bytes(serialized[32..40].try_into().unwrap()),
+            seed_block,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn test_derive_blocks() {
+        let sampling = RandomSampling::new(
+            b256!("1c4f3c6bbd2e9e3ab4d0a7d0b0e2f4d1f5b8a9c2e3d4f5a6b7c8d9e0f1a2b3c4"),
+            10,
+            None,
+        );
+        let blocks = sampling.derive_blocks(19000000, 21600000, 100).unwrap();
+        assert_eq!(blocks.len(), 10);
+        assert!(blocks.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(blocks
+            .iter()
+            .all(|block| (19000000..=21600000).contains(block) && block % 100 == 0));
+
+        // first pick is keccak256(seed ++ 0) mod n
+        let mut preimage = sampling.seed.to_vec();
+        preimage.extend_from_slice(&0u64.to_be_bytes());
+        let first = U256::from_be_bytes(keccak256(&preimage).0) % U256::from(26001);
+        assert!(blocks.contains(&(19000000 + first.to::<u64>() * 100)));
+
+        // derivation is deterministic, and depends on the seed
+        assert_eq!(
+            sampling.derive_blocks(19000000, 21600000, 100).unwrap(),
+            blocks
+        );
+        let other = RandomSampling::new(B256::ZERO, 10, None);
+        assert_ne!(
+            other.derive_blocks(19000000, 21600000, 100).unwrap(),
+            blocks
+        );
+
+        // every candidate is picked if the sample size is the number of candidates
+        let all = RandomSampling::new(B256::ZERO, 5, None);
+        assert_eq!(
+            all.derive_blocks(10, 14, 1).unwrap(),
+            vec![10, 11, 12, 13, 14]
+        );
+        assert!(RandomSampling::new(B256::ZERO, 6, None)
+            .derive_blocks(10, 14, 1)
+            .is_err());
+        assert!(RandomSampling::new(B256::ZERO, 0, None)
+            .derive_blocks(10, 14, 1)
+            .is_err());
+
+        // the seed block must come after the range
+        assert!(RandomSampling::new(B256::ZERO, 5, Some(15))
+            .derive_blocks(10, 14, 1)
+            .is_ok());
+        assert!(RandomSampling::new(B256::ZERO, 5, Some(14))
+            .derive_blocks(10, 14, 1)
+            .is_err());
+        assert!(RandomSampling::new(B256::ZERO, 5, Some(9))
+            .derive_blocks(10, 14, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_random_sampling_serialize() {
+        let sampling = RandomSampling::new(B256::repeat_byte(0xab), 365, None);
+        let serialized = sampling.serialize();
+        assert_eq!(serialized.len(), 40);
+        assert_eq!(RandomSampling::deserialize(&serialized).unwrap(), sampling);
+
+        let sampling = RandomSampling::new(B256::repeat_byte(0xab), 365, Some(21600001));
+        let serialized = sampling.serialize();
+        assert_eq!(serialized.len(), 48);
+        assert_eq!(RandomSampling::deserialize(&serialized).unwrap(), sampling);
+        assert!(RandomSampling::deserialize(&serialized[..44]).is_err());
+    }
+}
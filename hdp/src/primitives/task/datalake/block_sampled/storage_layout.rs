@@ -0,0 +1,422 @@
+//! Solidity storage layout support for [`BlockSampledCollection::Storage`].
+//!
+//! The layout is the JSON emitted by `solc --storage-layout` (or `forge inspect <contract> storageLayout`).
+//! It lets a request refer to a state variable by name, e.g. `reserve0`, `_balances[0xabc]`,
+//! `allowance[0xowner][0xspender]` or `positions[1].liquidity`, instead of a raw storage slot.
+//!
+//! [`BlockSampledCollection::Storage`]: super::BlockSampledCollection::Storage
+
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::primitives::{hex, keccak256, Address, StorageKey, I256, U256};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::collection::StorageSlice;
+
+/// Storage layout of a contract as emitted by solc
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageLayoutEntry>,
+    #[serde(default)]
+    pub types: HashMap<String, StorageLayoutType>,
+}
+
+/// A state variable, or a struct member, in the storage layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayoutEntry {
+    pub label: String,
+    pub offset: u8,
+    pub slot: String,
+    #[serde(rename = "type")]
+    pub type_id: String,
+}
+
+/// A type referenced by the storage layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageLayoutType {
+    /// One of `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    pub label: String,
+    pub number_of_bytes: String,
+    /// Key type of a mapping
+    pub key: Option<String>,
+    /// Value type of a mapping
+    pub value: Option<String>,
+    /// Element type of an array
+    pub base: Option<String>,
+    /// Members of a struct
+    pub members: Option<Vec<StorageLayoutEntry>>,
+}
+
+impl StorageLayoutType {
+    /// Size of the type in bytes, which is never 0
+    fn size(&self) -> Result<u64> {
+        let size: u64 = self
+            .number_of_bytes
+            .parse()
+            .with_context(|| format!("Invalid size of type {}", self.label))?;
+        if size == 0 {
+            bail!("Invalid size of type {}: 0 bytes", self.label);
+        }
+        Ok(size)
+    }
+
+    /// Type referenced by the type, e.g. the value type of a mapping
+    fn referenced<'a>(&self, type_id: &'a Option<String>, name: &str) -> Result<&'a String> {
+        type_id
+            .as_ref()
+            .with_context(|| format!("Missing {} type of {}", name, self.label))
+    }
+}
+
+/// Storage location of a value type variable resolved from the layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageVariable {
+    pub slot: StorageKey,
+    /// Position of the value inside the slot. `None` if the value occupies the whole slot.
+    pub slice: Option<StorageSlice>,
+}
+
+enum Accessor {
+    Index(String),
+    Member(String),
+}
+
+impl FromStr for StorageLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+impl StorageLayout {
+    /// Resolve a variable path like `_balances[0xabc]` into its storage slot,
+    /// along with the position of the value in the slot if it is packed.
+    pub fn resolve(&self, path: &str) -> Result<StorageVariable> {
+        let (name, accessors) = parse_path(path)?;
+        let entry = self
+            .storage
+            .iter()
+            .find(|entry| entry.label == name)
+            .with_context(|| format!("Unknown storage variable: {}", name))?;
+
+        let mut slot = U256::from_str(&entry.slot)?;
+        let mut offset = entry.offset;
+        let mut type_id = entry.type_id.clone();
+
+        for accessor in accessors {
+            let layout_type = self.get_type(&type_id)?;
+            match (layout_type.encoding.as_str(), accessor) {
+                ("mapping", Accessor::Index(key)) => {
+                    let key_type =
+                        self.get_type(layout_type.referenced(&layout_type.key, "key")?)?;
+                    let mut preimage = encode_mapping_key(&key_type.label, &key)?;
+                    preimage.extend_from_slice(&slot.to_be_bytes::<32>());
+                    slot = keccak256(preimage).into();
+                    offset = 0;
+                    type_id = layout_type.referenced(&layout_type.value, "value")?.clone();
+                }
+                ("dynamic_array", Accessor::Index(index)) => {
+                    let base = layout_type.referenced(&layout_type.base, "base")?.clone();
+                    let start: U256 = keccak256(slot.to_be_bytes::<32>()).into();
+                    (slot, offset) = self.locate_element(start, &base, &index)?;
+                    type_id = base;
+                }
+                ("inplace", Accessor::Index(index)) if layout_type.base.is_some() => {
+                    let base = layout_type.referenced(&layout_type.base, "base")?.clone();
+                    (slot, offset) = self.locate_element(slot, &base, &index)?;
+                    type_id = base;
+                }
+                ("inplace", Accessor::Member(member)) if layout_type.members.is_some() => {
+                    let member = layout_type
+                        .members
+                        .iter()
+                        .flatten()
+                        .find(|entry| entry.label == member)
+                        .with_context(|| {
+                            format!("Unknown member {} of {}", member, layout_type.label)
+                        })?;
+                    slot = slot.wrapping_add(U256::from_str(&member.slot)?);
+                    offset = member.offset;
+                    type_id = member.type_id.clone();
+                }
+                _ => bail!(
+                    "Invalid access on storage variable of type {}",
+                    layout_type.label
+                ),
+            }
+        }
+
+        let layout_type = self.get_type(&type_id)?;
+        if layout_type.encoding != "inplace"
+            || layout_type.members.is_some()
+            || layout_type.base.is_some()
+        {
+            bail!(
+                "Storage variable of type {} is not a value type",
+                layout_type.label
+            );
+        }
+
+        let width = layout_type.size()?;
+        if width > 32 {
+            bail!(
+                "Invalid size of value type {}: {} bytes",
+                layout_type.label,
+                width
+            );
+        }
+        let width = width as u8;
+        let slice = if offset == 0 && width == 32 {
+            None
+        } else {
+            let signed = layout_type.label.starts_with("int");
+            Some(StorageSlice::new(offset, width, signed)?)
+        };
+
+        Ok(StorageVariable {
+            slot: StorageKey::from(slot),
+            slice,
+        })
+    }
+
+    fn get_type(&self, type_id: &str) -> Result<&StorageLayoutType> {
+        self.types
+            .get(type_id)
+            .with_context(|| format!("Unknown type in storage layout: {}", type_id))
+    }
+
+    /// Locate an array element. Elements of 16 bytes or less are packed into a slot.
+    fn locate_element(&self, start: U256, base: &str, index: &str) -> Result<(U256, u8)> {
+        let index = U256::from_str(index)?;
+        let size = self.get_type(base)?.size()?;
+        if size <= 16 {
+            let items_per_slot = U256::from(32 / size);
+            let offset = (index % items_per_slot).to::<u64>() * size;
+            Ok((start.wrapping_add(index / items_per_slot), offset as u8))
+        } else {
+            let slots_per_item = U256::from(size.div_ceil(32));
+            Ok((start.wrapping_add(index.wrapping_mul(slots_per_item)), 0))
+        }
+    }
+}
+
+/// Split a variable path into its name and the chain of accessors applied to it.
+fn parse_path(path: &str) -> Result<(String, Vec<Accessor>)> {
+    let end = path.find(['[', '.']).unwrap_or(path.len());
+    let name = &path[..end];
+    if name.is_empty() {
+        bail!("Invalid storage variable: {}", path);
+    }
+
+    let mut accessors = Vec::new();
+    let mut rest = &path[end..];
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let close = inner
+                .find(']')
+                .with_context(|| format!("Unclosed bracket in storage variable: {}", path))?;
+            accessors.push(Accessor::Index(inner[..close].trim().to_string()));
+            rest = &inner[close + 1..];
+        } else if let Some(inner) = rest.strip_prefix('.') {
+            let end = inner.find(['[', '.']).unwrap_or(inner.len());
+            if end == 0 {
+                bail!("Invalid storage variable: {}", path);
+            }
+            accessors.push(Accessor::Member(inner[..end].to_string()));
+            rest = &inner[end..];
+        } else {
+            bail!("Invalid storage variable: {}", path);
+        }
+    }
+
+    Ok((name.to_string(), accessors))
+}
+
+/// Encode a mapping key the way solidity does before hashing it with the slot.
+///
+/// Value types are padded to 32 bytes, `string` and `bytes` keys are used as is.
+fn encode_mapping_key(key_label: &str, key: &str) -> Result<Vec<u8>> {
+    let encoded = if key_label == "address" || key_label.starts_with("contract ") {
+        Address::from_str(key)?.into_word().to_vec()
+    } else if key_label == "bool" {
+        match key {
+            "true" => U256::from(1),
+            "false" => U256::ZERO,
+            _ => bail!("Invalid bool mapping key: {}", key),
+        }
+        .to_be_bytes::<32>()
+        .to_vec()
+    } else if key_label.starts_with("uint") || key_label.starts_with("enum ") {
+        U256::from_str(key)?.to_be_bytes::<32>().to_vec()
+    } else if key_label.starts_with("int") {
+        I256::from_str(key)?.to_be_bytes::<32>().to_vec()
+    } else if key_label == "string" {
+        key.as_bytes().to_vec()
+    } else if key_label == "bytes" {
+        hex::decode(key)?
+    } else if key_label.starts_with("bytes") {
+        let bytes = hex::decode(key)?;
+        if bytes.len() > 32 {
+            bail!("Invalid {} mapping key: {}", key_label, key);
+        }
+        let mut padded = bytes;
+        padded.resize(32, 0);
+        padded
+    } else {
+        bail!("Unsupported mapping key type: {}", key_label);
+    };
+
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    const LAYOUT: &str = r#"
+    {
+      "storage": [
+        { "astId": 1, "contract": "Pool.sol:Pool", "label": "_balances", "offset": 0, "slot": "0", "type": "t_mapping(t_address,t_uint256)" },
+        { "astId": 2, "contract": "Pool.sol:Pool", "label": "reserve0", "offset": 0, "slot": "8", "type": "t_uint112" },
+        { "astId": 3, "contract": "Pool.sol:Pool", "label": "reserve1", "offset": 14, "slot": "8", "type": "t_uint112" },
+        { "astId": 4, "contract": "Pool.sol:Pool", "label": "blockTimestampLast", "offset": 28, "slot": "8", "type": "t_uint32" },
+        { "astId": 5, "contract": "Pool.sol:Pool", "label": "totalSupply", "offset": 0, "slot": "9", "type": "t_uint256" },
+        { "astId": 6, "contract": "Pool.sol:Pool", "label": "checkpoints", "offset": 0, "slot": "10", "type": "t_array(t_uint64)dyn_storage" },
+        { "astId": 7, "contract": "Pool.sol:Pool", "label": "positions", "offset": 0, "slot": "11", "type": "t_mapping(t_uint256,t_struct(Position)1_storage)" },
+        { "astId": 10, "contract": "Pool.sol:Pool", "label": "tick", "offset": 20, "slot": "12", "type": "t_int24" }
+      ],
+      "types": {
+        "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+        "t_int24": { "encoding": "inplace", "label": "int24", "numberOfBytes": "3" },
+        "t_uint32": { "encoding": "inplace", "label": "uint32", "numberOfBytes": "4" },
+        "t_uint64": { "encoding": "inplace", "label": "uint64", "numberOfBytes": "8" },
+        "t_uint112": { "encoding": "inplace", "label": "uint112", "numberOfBytes": "14" },
+        "t_uint128": { "encoding": "inplace", "label": "uint128", "numberOfBytes": "16" },
+        "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+        "t_array(t_uint64)dyn_storage": { "base": "t_uint64", "encoding": "dynamic_array", "label": "uint64[]", "numberOfBytes": "32" },
+        "t_mapping(t_address,t_uint256)": { "encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256" },
+        "t_mapping(t_uint256,t_struct(Position)1_storage)": { "encoding": "mapping", "key": "t_uint256", "label": "mapping(uint256 => struct Pool.Position)", "numberOfBytes": "32", "value": "t_struct(Position)1_storage" },
+        "t_struct(Position)1_storage": {
+          "encoding": "inplace", "label": "struct Pool.Position", "numberOfBytes": "64",
+          "members": [
+            { "astId": 8, "contract": "Pool.sol:Pool", "label": "owner", "offset": 0, "slot": "0", "type": "t_address" },
+            { "astId": 9, "contract": "Pool.sol:Pool", "label": "liquidity", "offset": 0, "slot": "1", "type": "t_uint128" }
+          ]
+        }
+      }
+    }
+    "#;
+
+    #[test]
+    fn test_resolve_value_variables() {
+        let layout: StorageLayout = LAYOUT.parse().unwrap();
+
+        let total_supply = layout.resolve("totalSupply").unwrap();
+        assert_eq!(total_supply.slot, StorageKey::from(U256::from(9)));
+        assert_eq!(total_supply.slice, None);
+
+        let reserve0 = layout.resolve("reserve0").unwrap();
+        assert_eq!(reserve0.slot, StorageKey::from(U256::from(8)));
+        assert_eq!(
+            reserve0.slice,
+            Some(StorageSlice::new(0, 14, false).unwrap())
+        );
+
+        let reserve1 = layout.resolve("reserve1").unwrap();
+        assert_eq!(reserve1.slot, StorageKey::from(U256::from(8)));
+        assert_eq!(
+            reserve1.slice,
+            Some(StorageSlice::new(14, 14, false).unwrap())
+        );
+
+        let timestamp = layout.resolve("blockTimestampLast").unwrap();
+        assert_eq!(
+            timestamp.slice,
+            Some(StorageSlice::new(28, 4, false).unwrap())
+        );
+
+        let tick = layout.resolve("tick").unwrap();
+        assert_eq!(tick.slot, StorageKey::from(U256::from(12)));
+        assert_eq!(tick.slice, Some(StorageSlice::new(20, 3, true).unwrap()));
+    }
+
+    #[test]
+    fn test_resolve_mapping() {
+        let layout: StorageLayout = LAYOUT.parse().unwrap();
+        let holder = address!("7b2f05ce9ae365c3dbf30657e2dc6449989e83d6");
+
+        let balance = layout
+            .resolve("_balances[0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6]")
+            .unwrap();
+        let mut preimage = holder.into_word().to_vec();
+        preimage.extend_from_slice(&[0; 32]);
+        assert_eq!(balance.slot, keccak256(preimage));
+        assert_eq!(balance.slice, None);
+    }
+
+    #[test]
+    fn test_resolve_array_and_struct() {
+        let layout: StorageLayout = LAYOUT.parse().unwrap();
+
+        // 4 uint64 values are packed in a slot
+        let checkpoint = layout.resolve("checkpoints[5]").unwrap();
+        let start: U256 = keccak256(U256::from(10).to_be_bytes::<32>()).into();
+        assert_eq!(checkpoint.slot, StorageKey::from(start + U256::from(1)));
+        assert_eq!(
+            checkpoint.slice,
+            Some(StorageSlice::new(8, 8, false).unwrap())
+        );
+
+        let liquidity = layout.resolve("positions[1].liquidity").unwrap();
+        let mut preimage = U256::from(1).to_be_bytes::<32>().to_vec();
+        preimage.extend_from_slice(&U256::from(11).to_be_bytes::<32>());
+        let position: U256 = keccak256(preimage).into();
+        assert_eq!(liquidity.slot, StorageKey::from(position + U256::from(1)));
+        assert_eq!(
+            liquidity.slice,
+            Some(StorageSlice::new(0, 16, false).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_resolve_invalid_variables() {
+        let layout: StorageLayout = LAYOUT.parse().unwrap();
+        assert!(layout.resolve("unknown").is_err());
+        // mapping itself is not a value
+        assert!(layout.resolve("_balances").is_err());
+        // struct is not a value
+        assert!(layout.resolve("positions[1]").is_err());
+        assert!(layout.resolve("positions[1].unknown").is_err());
+        assert!(layout.resolve("reserve0[1]").is_err());
+        assert!(layout.resolve("_balances[0x7b2f").is_err());
+    }
+
+    #[test]
+    fn test_resolve_malformed_layout() {
+        // mapping without value type
+        let mut layout: StorageLayout = LAYOUT.parse().unwrap();
+        layout
+            .types
+            .get_mut("t_mapping(t_address,t_uint256)")
+            .unwrap()
+            .value = None;
+        assert!(layout
+            .resolve("_balances[0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6]")
+            .is_err());
+
+        // array of elements of 0 bytes
+        let mut layout: StorageLayout = LAYOUT.parse().unwrap();
+        layout.types.get_mut("t_uint64").unwrap().number_of_bytes = "0".to_string();
+        assert!(layout.resolve("checkpoints[5]").is_err());
+
+        // value type larger than a slot
+        let mut layout: StorageLayout = LAYOUT.parse().unwrap();
+        layout.types.get_mut("t_uint256").unwrap().number_of_bytes = "33".to_string();
+        assert!(layout.resolve("totalSupply").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Timestamp based block range of a [`BlockSampledDatalake`].
+//!
+//! A timestamp maps to the first block with a timestamp greater than or equal to it.
+//! The range is resolved to block numbers by the provider before compilation, against the latest
+//! block of the chain. The timestamp range is committed along with the resolved blocks,
+//! so the resolution can be reproduced.
+//!
+//! - Without a time step, the range is from the block of `from_timestamp`
+//!   to the last block with a timestamp less than or equal to `to_timestamp`.
+//! - With a time step, the block of every `from_timestamp + k * time_step` up to `to_timestamp`
+//!   is sampled, as an explicit block list.
+//!
+//! Timestamps after the latest block are not resolved, i.e. the range ends at the latest block.
+//!
+//! [`BlockSampledDatalake`]: super::BlockSampledDatalake
+
+use alloy::primitives::BlockNumber;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampRange {
+    /// Start of the range, in seconds (inclusive)
+    pub from_timestamp: u64,
+    /// End of the range, in seconds (inclusive)
+    pub to_timestamp: u64,
+    /// Time step between sampled blocks, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_step: Option<u64>,
+    /// Latest block of the chain the range is resolved against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_block: Option<BlockNumber>,
+}
+
+impl TimestampRange {
+    pub fn new(from_timestamp: u64, to_timestamp: u64, time_step: Option<u64>) -> Result<Self> {
+        if to_timestamp < from_timestamp {
+            bail!(
+                "Invalid timestamp range: {} to {}",
+                from_timestamp,
+                to_timestamp
+            );
+        }
+        if time_step == Some(0) {
+            bail!("Time step must be positive");
+        }
+        Ok(Self {
+            from_timestamp,
+            to_timestamp,
+            time_step,
+            latest_block: None,
+        })
+    }
+
+    /// Resolve the range against the latest block of the chain
+    pub fn resolve(&self, latest_block: BlockNumber) -> Self {
+        Self {
+            latest_block: Some(latest_block),
+            ..*self
+        }
+    }
+
+    /// Timestamps to resolve to blocks for a stepped range, up to the timestamp of the latest block
+    pub fn stepped_timestamps(&self, latest_timestamp: u64) -> Option<Vec<u64>> {
+        self.time_step.map(|time_step| {
+            (self.from_timestamp..=self.to_timestamp.min(latest_timestamp))
+                .step_by(time_step as usize)
+                .collect()
+        })
+    }
+
+    /// Blocks whose headers prove the resolution of the given blocks,
+    /// i.e. the block before each block of a timestamp, and the block after the end of the range
+    /// unless the range ends at the latest block.
+    pub fn boundary_blocks(
+        &self,
+        block_range_start: BlockNumber,
+        block_range_end: BlockNumber,
+        block_list: &[BlockNumber],
+    ) -> Vec<BlockNumber> {
+        match self.time_step {
+            Some(_) => block_list
+                .iter()
+                .filter_map(|block| block.checked_sub(1))
+                .collect(),
+            None => block_range_start
+                .checked_sub(1)
+                .into_iter()
+                .chain(Some(block_range_end + 1).filter(|_| {
+                    self.latest_block
+                        .map_or(true, |latest| block_range_end < latest)
+                }))
+                .collect(),
+        }
+    }
+
+    /// Serialize as the 8 bytes big endian timestamps, the latest block and the optional time step.
+    /// The range must be resolved.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let latest_block = match self.latest_block {
+            Some(latest_block) => latest_block,
+            None => bail!("Timestamp range is not resolved"),
+        };
+        let mut serialized = self.from_timestamp.to_be_bytes().to_vec();
+        serialized.extend_from_slice(&self.to_timestamp.to_be_bytes());
+        serialized.extend_from_slice(&latest_block.to_be_bytes());
+        if let Some(time_step) = self.time_step {
+            serialized.extend_from_slice(&time_step.to_be_bytes());
+        }
+        Ok(serialized)
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        let time_step = match serialized.len() {
+            24 => None,
+            32 => Some(u64::from_be_bytes(serialized[24..].try_into().unwrap())),
+            _ => bail!("Invalid timestamp range"),
+        };
+        let range = Self::new(
+            u64::from_be_bytes(serialized[..8].try_into().unwrap()),
+            u64::from_be_bytes(serialized[8..16].try_into().unwrap()),
+            time_step,
+        )?;
+        Ok(range.resolve(u64::from_be_bytes(serialized[16..24].try_into().unwrap())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_range() {
+        let range = TimestampRange::new(1704067200, 1706745600, Some(86400 * 7)).unwrap();
+        let timestamps = range.stepped_timestamps(u64::MAX).unwrap();
+        assert_eq!(
+            range
+                .stepped_timestamps(1704067200 + 86400 * 7)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(timestamps.len(), 5);
+        assert_eq!(timestamps[0], 1704067200);
+        assert_eq!(timestamps[4], 1704067200 + 4 * 86400 * 7);
+        assert_eq!(
+            range.boundary_blocks(0, 0, &[100, 200, 300]),
+            vec![99, 199, 299]
+        );
+
+        let range = TimestampRange::new(1704067200, 1706745600, None).unwrap();
+        assert!(range.stepped_timestamps(u64::MAX).is_none());
+        assert_eq!(range.boundary_blocks(100, 300, &[]), vec![99, 301]);
+        // no block after the latest block
+        assert_eq!(
+            range.resolve(301).boundary_blocks(100, 300, &[]),
+            vec![99, 301]
+        );
+        assert_eq!(range.resolve(300).boundary_blocks(100, 300, &[]), vec![99]);
+
+        assert!(TimestampRange::new(1706745600, 1704067200, None).is_err());
+        assert!(TimestampRange::new(1704067200, 1706745600, Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_range_serialize() {
+        let range = TimestampRange::new(1704067200, 1706745600, Some(604800)).unwrap();
+        assert!(range.serialize().is_err());
+
+        let resolved = range.resolve(5200000);
+        let serialized = resolved.serialize().unwrap();
+        assert_eq!(serialized.len(), 32);
+        assert_eq!(TimestampRange::deserialize(&serialized).unwrap(), resolved);
+
+        let resolved = TimestampRange::new(1704067200, u64::MAX, None)
+            .unwrap()
+            .resolve(5200000);
+        let serialized = resolved.serialize().unwrap();
+        assert_eq!(serialized.len(), 24);
+        assert_eq!(TimestampRange::deserialize(&serialized).unwrap(), resolved);
+
+        assert!(TimestampRange::deserialize(&serialized[..16]).is_err());
+    }
+}
@@ -8,10 +8,18 @@ pub const BLOCK_SAMPLED_DATALAKE_TYPE_ID: u8 = 0;
 /// Identifier for an [`TransactionsDatalake`] type.
 pub const TRANSACTIONS_IN_BLOCK_DATALAKE_TYPE_ID: u8 = 1;
 
+/// Identifier for an [`TransactionsInRangeDatalake`] type.
+pub const TRANSACTIONS_IN_RANGE_DATALAKE_TYPE_ID: u8 = 2;
+
+/// Identifier for an [`TransactionsByHashDatalake`] type.
+pub const TRANSACTIONS_BY_HASH_DATALAKE_TYPE_ID: u8 = 3;
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub enum DatalakeType {
     BlockSampled = 0,
     TransactionsInBlock = 1,
+    TransactionsInRange = 2,
+    TransactionsByHash = 3,
 }
 
 impl FromStr for DatalakeType {
@@ -21,6 +29,8 @@ impl FromStr for DatalakeType {
         match s {
             "BLOCK_SAMPLED" => Ok(DatalakeType::BlockSampled),
             "TRANSACTIONS_IN_BLOCK" => Ok(DatalakeType::TransactionsInBlock),
+            "TRANSACTIONS_IN_RANGE" => Ok(DatalakeType::TransactionsInRange),
+            "TRANSACTIONS_BY_HASH" => Ok(DatalakeType::TransactionsByHash),
             _ => bail!("Unknown datalake type"),
         }
     }
@@ -31,16 +41,23 @@ impl From<DatalakeType> for u8 {
         match value {
             DatalakeType::BlockSampled => BLOCK_SAMPLED_DATALAKE_TYPE_ID,
             DatalakeType::TransactionsInBlock => TRANSACTIONS_IN_BLOCK_DATALAKE_TYPE_ID,
+            DatalakeType::TransactionsInRange => TRANSACTIONS_IN_RANGE_DATALAKE_TYPE_ID,
+            DatalakeType::TransactionsByHash => TRANSACTIONS_BY_HASH_DATALAKE_TYPE_ID,
         }
     }
 }
 
 impl DatalakeType {
     pub fn variants() -> Vec<String> {
-        vec!["BLOCK_SAMPLED", "TRANSACTIONS_IN_BLOCK"]
-            .into_iter()
-            .map(String::from)
-            .collect()
+        vec![
+            "BLOCK_SAMPLED",
+            "TRANSACTIONS_IN_BLOCK",
+            "TRANSACTIONS_IN_RANGE",
+            "TRANSACTIONS_BY_HASH",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
     }
 
     pub fn to_u8(self) -> u8 {
@@ -51,6 +68,8 @@ impl DatalakeType {
         match value {
             BLOCK_SAMPLED_DATALAKE_TYPE_ID => Ok(DatalakeType::BlockSampled),
             TRANSACTIONS_IN_BLOCK_DATALAKE_TYPE_ID => Ok(DatalakeType::TransactionsInBlock),
+            TRANSACTIONS_IN_RANGE_DATALAKE_TYPE_ID => Ok(DatalakeType::TransactionsInRange),
+            TRANSACTIONS_BY_HASH_DATALAKE_TYPE_ID => Ok(DatalakeType::TransactionsByHash),
             _ => bail!("Invalid datalake type"),
         }
     }
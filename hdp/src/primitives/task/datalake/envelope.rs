@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    block_sampled::BlockSampledDatalake, transactions::TransactionsInBlockDatalake,
+    block_sampled::{BlockSampledCollection, BlockSampledDatalake},
+    transactions::{
+        TransactionsByHashDatalake, TransactionsInBlockDatalake, TransactionsInRangeDatalake,
+    },
     DatalakeCollection,
 };
 
@@ -13,6 +16,8 @@ pub type BatchedDatalakes = Vec<DatalakeEnvelope>;
 pub enum DatalakeEnvelope {
     BlockSampled(BlockSampledDatalake),
     TransactionsInBlock(TransactionsInBlockDatalake),
+    TransactionsInRange(TransactionsInRangeDatalake),
+    TransactionsByHash(TransactionsByHashDatalake),
 }
 
 impl DatalakeEnvelope {
@@ -22,13 +27,35 @@ impl DatalakeEnvelope {
             DatalakeEnvelope::TransactionsInBlock(datalake) => {
                 Box::new(datalake.sampled_property.clone())
             }
+            DatalakeEnvelope::TransactionsInRange(datalake) => {
+                Box::new(datalake.sampled_property.clone())
+            }
+            DatalakeEnvelope::TransactionsByHash(datalake) => {
+                Box::new(datalake.sampled_property.clone())
+            }
         }
     }
 
+    /// Size of the sampled values in bytes, see [`DatalakeCollection::value_bytes`]
+    pub fn value_bytes(&self) -> usize {
+        self.get_collection_type().value_bytes()
+    }
+
+    /// Whether the sampled values are two's complement int256, i.e. a signed packed storage value
+    pub fn has_signed_values(&self) -> bool {
+        matches!(
+            self,
+            DatalakeEnvelope::BlockSampled(datalake)
+                if matches!(datalake.sampled_property, BlockSampledCollection::Storage(_, _, Some(slice)) if slice.signed)
+        )
+    }
+
     pub fn get_chain_id(&self) -> crate::primitives::ChainId {
         match self {
             DatalakeEnvelope::BlockSampled(datalake) => datalake.chain_id,
             DatalakeEnvelope::TransactionsInBlock(datalake) => datalake.chain_id,
+            DatalakeEnvelope::TransactionsInRange(datalake) => datalake.chain_id,
+            DatalakeEnvelope::TransactionsByHash(datalake) => datalake.chain_id,
         }
     }
 }
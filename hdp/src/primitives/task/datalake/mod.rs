@@ -1,9 +1,11 @@
 use alloy::primitives::U256;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-use self::{compute::Computation, envelope::DatalakeEnvelope};
+use self::{
+    block_sampled::MultiBlockSampledDatalake, compute::Computation, envelope::DatalakeEnvelope,
+};
 
 pub mod block_sampled;
 pub mod compute;
@@ -21,6 +23,75 @@ impl DatalakeCompute {
     pub fn new(datalake: DatalakeEnvelope, compute: Computation) -> Self {
         Self { datalake, compute }
     }
+
+    /// Check that the computation takes the sampled values as they are.
+    /// Signed packed storage values must be aggregated with the `signed` context,
+    /// which the function must support.
+    pub fn validate(&self) -> Result<()> {
+        self.compute
+            .aggregate_fn_id
+            .validate_context(&self.compute.aggregate_fn_ctx)?;
+        if self.datalake.has_signed_values() && !self.compute.aggregate_fn_ctx.signed {
+            bail!(
+                "Signed storage values require the signed context, {} does not take them as signed",
+                self.compute.aggregate_fn_id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// [`MultiDatalakeCompute`] samples several properties over the same blocks,
+/// each property feeding its own computation.
+///
+/// It expands to one [`DatalakeCompute`] per property. A single computation applies to every property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "MultiDatalakeComputeRequest")]
+pub struct MultiDatalakeCompute {
+    pub datalake: MultiBlockSampledDatalake,
+    pub computes: Vec<Computation>,
+}
+
+impl MultiDatalakeCompute {
+    pub fn new(datalake: MultiBlockSampledDatalake, computes: Vec<Computation>) -> Result<Self> {
+        let properties_len = datalake.datalakes().len();
+        if computes.len() != 1 && computes.len() != properties_len {
+            bail!(
+                "Expected 1 or {} computations, got {}",
+                properties_len,
+                computes.len()
+            );
+        }
+        Ok(Self { datalake, computes })
+    }
+
+    /// Expand to a task per sampled property
+    pub fn into_datalake_computes(self) -> Vec<DatalakeCompute> {
+        let computes = self.computes;
+        self.datalake
+            .into_datalakes()
+            .into_iter()
+            .enumerate()
+            .map(|(i, datalake)| {
+                let compute = computes.get(i).unwrap_or(&computes[0]).clone();
+                DatalakeCompute::new(DatalakeEnvelope::BlockSampled(datalake), compute)
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct MultiDatalakeComputeRequest {
+    datalake: MultiBlockSampledDatalake,
+    computes: Vec<Computation>,
+}
+
+impl TryFrom<MultiDatalakeComputeRequest> for MultiDatalakeCompute {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MultiDatalakeComputeRequest) -> Result<Self> {
+        Self::new(value.datalake, value.computes)
+    }
 }
 
 pub trait DatalakeCollection {
@@ -29,6 +100,10 @@ pub trait DatalakeCollection {
     fn deserialize(encoded: &[u8]) -> Result<Self>
     where
         Self: Sized;
+    /// Size of the sampled values in bytes, e.g. 20 for addresses, 32 for values that are words
+    fn value_bytes(&self) -> usize {
+        32
+    }
 }
 
 pub trait DatalakeField: FromStr + Display {
@@ -36,5 +111,5 @@ pub trait DatalakeField: FromStr + Display {
     where
         Self: Sized;
     fn to_index(&self) -> u8;
-    fn decode_field_from_rlp(&self, rlp: &[u8]) -> U256;
+    fn decode_field_from_rlp(&self, rlp: &[u8]) -> Result<U256>;
 }
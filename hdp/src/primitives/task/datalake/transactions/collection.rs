@@ -5,22 +5,28 @@ use serde::{Deserialize, Serialize};
 
 use crate::primitives::task::datalake::{DatalakeCollection, DatalakeField};
 
-use super::{TransactionField, TransactionReceiptField};
+use super::{TransactionFeeField, TransactionField, TransactionReceiptField};
 
 pub enum TransactionsCollectionType {
     Transactions,
     TransactionReceipts,
+    TransactionFees,
 }
 
 impl TransactionsCollectionType {
     pub fn variants() -> Vec<String> {
-        vec!["TX".to_string(), "TX_RECEIPT".to_string()]
+        vec![
+            "TX".to_string(),
+            "TX_RECEIPT".to_string(),
+            "TX_FEE".to_string(),
+        ]
     }
 
     pub fn to_u8(&self) -> u8 {
         match self {
             TransactionsCollectionType::Transactions => 4,
             TransactionsCollectionType::TransactionReceipts => 5,
+            TransactionsCollectionType::TransactionFees => 6,
         }
     }
 }
@@ -32,16 +38,19 @@ impl FromStr for TransactionsCollectionType {
         match s.to_uppercase().as_str() {
             "TX" => Ok(TransactionsCollectionType::Transactions),
             "TX_RECEIPT" => Ok(TransactionsCollectionType::TransactionReceipts),
+            "TX_FEE" => Ok(TransactionsCollectionType::TransactionFees),
             _ => bail!("Unknown transactions collection type"),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(try_from = "String")]
+#[serde(try_from = "String", into = "String")]
 pub enum TransactionsCollection {
     Transactions(TransactionField),
     TranasactionReceipts(TransactionReceiptField),
+    /// Fee accounting fields derived from the transaction, its receipt and the block header
+    TransactionFees(TransactionFeeField),
 }
 
 impl DatalakeCollection for TransactionsCollection {
@@ -49,6 +58,7 @@ impl DatalakeCollection for TransactionsCollection {
         match self {
             TransactionsCollection::Transactions(ref field) => field.to_index(),
             TransactionsCollection::TranasactionReceipts(ref field) => field.to_index(),
+            TransactionsCollection::TransactionFees(ref field) => field.to_index(),
         }
     }
 
@@ -58,6 +68,9 @@ impl DatalakeCollection for TransactionsCollection {
             TransactionsCollection::TranasactionReceipts(ref field) => {
                 Ok([2, field.to_index()].to_vec())
             }
+            TransactionsCollection::TransactionFees(ref field) => {
+                Ok([3, field.to_index()].to_vec())
+            }
         }
     }
 
@@ -73,9 +86,22 @@ impl DatalakeCollection for TransactionsCollection {
             2 => Ok(TransactionsCollection::TranasactionReceipts(
                 TransactionReceiptField::from_index(bytes[1])?,
             )),
+            3 => Ok(TransactionsCollection::TransactionFees(
+                TransactionFeeField::from_index(bytes[1])?,
+            )),
             _ => Err(anyhow::Error::msg("Unknown transactions collection")),
         }
     }
+
+    fn value_bytes(&self) -> usize {
+        match self {
+            TransactionsCollection::Transactions(
+                TransactionField::To | TransactionField::Sender | TransactionField::CreatedAddress,
+            ) => 20,
+            TransactionsCollection::Transactions(TransactionField::Selector) => 4,
+            _ => 32,
+        }
+    }
 }
 
 impl FromStr for TransactionsCollection {
@@ -95,6 +121,9 @@ impl FromStr for TransactionsCollection {
             "TX_RECEIPT" => Ok(TransactionsCollection::TranasactionReceipts(
                 parts[1].to_uppercase().as_str().parse()?,
             )),
+            "TX_FEE" => Ok(TransactionsCollection::TransactionFees(
+                parts[1].to_uppercase().as_str().parse()?,
+            )),
             _ => bail!("Unknown transactions collection"),
         }
     }
@@ -108,6 +137,12 @@ impl TryFrom<String> for TransactionsCollection {
     }
 }
 
+impl From<TransactionsCollection> for String {
+    fn from(value: TransactionsCollection) -> Self {
+        value.to_string()
+    }
+}
+
 impl Display for TransactionsCollection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -115,6 +150,7 @@ impl Display for TransactionsCollection {
             TransactionsCollection::TranasactionReceipts(ref field) => {
                 write!(f, "TX_RECEIPT.{}", field)
             }
+            TransactionsCollection::TransactionFees(ref field) => write!(f, "TX_FEE.{}", field),
         }
     }
 }
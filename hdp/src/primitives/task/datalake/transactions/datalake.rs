@@ -4,21 +4,32 @@
 //!
 //! Example: `TransactionsInBlockDatalake { target_block: 100, sampled_property: "tx.to", increment: 1 }`
 //! represents all transactions in block 100 with a `tx.to` property sampled with an increment of 1.
+//!
+//! The transactions range can be relative to the number of transactions of the block,
+//! e.g. the last 10 transactions, see [`TxIndexRange`].
+//!
+//! [`TransactionsInRangeDatalake`] samples the transactions of every block in a block range the same way.
+//!
+//! [`TransactionsByHashDatalake`] samples a list of transactions given by their hashes,
+//! which are located in their blocks before compilation.
 
 use std::num::ParseIntError;
 use std::str::FromStr;
 
 use alloy::consensus::TxType;
-use alloy::primitives::U256;
-use anyhow::Result;
+use alloy::primitives::{BlockNumber, TxHash, TxIndex, U256};
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::primitives::{task::datalake::envelope::default_increment, ChainId};
 
-use super::TransactionsCollection;
+use super::{TransactionsCollection, TransactionsFilter, TxIndexBound, TxIndexRange};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(
+    rename_all = "camelCase",
+    try_from = "TransactionsInBlockDatalakeRequest"
+)]
 pub struct TransactionsInBlockDatalake {
     pub chain_id: ChainId,
     // target block number
@@ -34,6 +45,13 @@ pub struct TransactionsInBlockDatalake {
     pub included_types: IncludedTypes,
     // ex. "tx.to" , "tx.gas_price" or "tx_receipt.success", "tx_receipt.cumulative_gas_used"
     pub sampled_property: TransactionsCollection,
+    // filter on sender, recipient and function selector of Txs
+    #[serde(default, skip_serializing_if = "TransactionsFilter::is_empty")]
+    pub filter: TransactionsFilter,
+    // requested range relative to the number of transactions of the block,
+    // which start and end index are resolved from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_range: Option<TxIndexRange>,
 }
 
 impl TransactionsInBlockDatalake {
@@ -54,7 +72,270 @@ impl TransactionsInBlockDatalake {
             end_index,
             increment,
             included_types,
+            filter: TransactionsFilter::default(),
+            index_range: None,
+        }
+    }
+
+    /// Only sample the transactions matching the filter
+    pub fn with_filter(mut self, filter: TransactionsFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sample the transactions of a range relative to the number of transactions of the block.
+    /// Start and end index are set once the range is resolved.
+    /// A range without relative bounds only sets the start and end index.
+    pub fn with_index_range(mut self, index_range: TxIndexRange) -> Self {
+        if let Some((start_index, end_index)) = index_range.absolute() {
+            (self.start_index, self.end_index) = (start_index, end_index);
+            self.index_range = None;
+            return self;
+        }
+        (self.start_index, self.end_index) = index_range.indexes().unwrap_or_default();
+        self.index_range = Some(index_range);
+        self
+    }
+
+    /// Whether the start and end index are known, i.e. the index range, if any, has been resolved
+    pub fn is_resolved(&self) -> bool {
+        self.index_range
+            .map_or(true, |index_range| index_range.tx_count.is_some())
+    }
+}
+
+/// Requested form of [`TransactionsInBlockDatalake`].
+///
+/// Start and end index can be relative to the end of the block, e.g. `-10` or `"last"`.
+/// End index defaults to the end of the block.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionsInBlockDatalakeRequest {
+    chain_id: ChainId,
+    target_block: u64,
+    #[serde(default)]
+    start_index: TxIndexBound,
+    #[serde(default = "default_end_index")]
+    end_index: TxIndexBound,
+    #[serde(default = "default_increment")]
+    increment: u64,
+    included_types: IncludedTypes,
+    sampled_property: TransactionsCollection,
+    #[serde(default)]
+    filter: TransactionsFilter,
+    index_range: Option<TxIndexRange>,
+}
+
+fn default_end_index() -> TxIndexBound {
+    TxIndexBound::LAST
+}
+
+impl TryFrom<TransactionsInBlockDatalakeRequest> for TransactionsInBlockDatalake {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TransactionsInBlockDatalakeRequest) -> Result<Self> {
+        let requested_range = TxIndexRange::new(value.start_index, value.end_index);
+        let datalake = Self::new(
+            value.chain_id,
+            value.target_block,
+            value.sampled_property,
+            0,
+            0,
+            value.increment,
+            value.included_types,
+        )
+        .with_filter(value.filter);
+
+        if let Some(index_range) = value.index_range {
+            // serialized datalake, start and end index are resolved from its index range
+            let datalake = datalake.with_index_range(index_range);
+            if datalake.is_resolved()
+                && requested_range.absolute() != Some((datalake.start_index, datalake.end_index))
+            {
+                bail!("Start and end index do not match the resolved index range");
+            }
+            return Ok(datalake);
+        }
+
+        Ok(datalake.with_index_range(requested_range))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsInRangeDatalake {
+    pub chain_id: ChainId,
+    // start of the block range (inclusive)
+    pub block_range_start: u64,
+    // end of the block range (inclusive)
+    pub block_range_end: u64,
+    // increment of blocks, Defaults to 1 if not present.
+    #[serde(default = "default_increment")]
+    pub block_increment: u64,
+    // start index of transactions range in each block
+    pub start_index: u64,
+    // end index of transactions range in each block, not included in the range.
+    // bounded by the number of transactions of each block
+    pub end_index: u64,
+    // increment of transactions, Defaults to 1 if not present.
+    #[serde(default = "default_increment")]
+    pub increment: u64,
+    // filter out the specific type of Txs
+    pub included_types: IncludedTypes,
+    // ex. "tx.to" , "tx.gas_price" or "tx_receipt.success", "tx_receipt.cumulative_gas_used"
+    pub sampled_property: TransactionsCollection,
+    // filter on sender, recipient and function selector of Txs
+    #[serde(default, skip_serializing_if = "TransactionsFilter::is_empty")]
+    pub filter: TransactionsFilter,
+}
+
+impl TransactionsInRangeDatalake {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        chain_id: ChainId,
+        block_range_start: u64,
+        block_range_end: u64,
+        block_increment: u64,
+        sampled_property: TransactionsCollection,
+        start_index: u64,
+        end_index: u64,
+        increment: u64,
+        included_types: IncludedTypes,
+    ) -> Self {
+        Self {
+            chain_id,
+            block_range_start,
+            block_range_end,
+            block_increment,
+            sampled_property,
+            start_index,
+            end_index,
+            increment,
+            included_types,
+            filter: TransactionsFilter::default(),
+        }
+    }
+
+    /// Only sample the transactions matching the filter
+    pub fn with_filter(mut self, filter: TransactionsFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Target blocks of the datalake
+    pub fn blocks(&self) -> impl Iterator<Item = u64> {
+        (self.block_range_start..=self.block_range_end).step_by(self.block_increment as usize)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsByHashDatalake {
+    pub chain_id: ChainId,
+    // hashes of the sampled transactions, in the order of the aggregation
+    pub tx_hashes: Vec<TxHash>,
+    // position of each transaction, resolved from its hash
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positions: Vec<TxPosition>,
+    // ex. "tx.to" , "tx.gas_price" or "tx_receipt.success", "tx_receipt.cumulative_gas_used"
+    pub sampled_property: TransactionsCollection,
+}
+
+/// Block number and index of a transaction in the block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPosition {
+    pub block_number: BlockNumber,
+    pub tx_index: TxIndex,
+}
+
+impl TxPosition {
+    pub fn new(block_number: BlockNumber, tx_index: TxIndex) -> Self {
+        Self {
+            block_number,
+            tx_index,
+        }
+    }
+}
+
+impl TransactionsByHashDatalake {
+    pub fn new(
+        chain_id: ChainId,
+        tx_hashes: Vec<TxHash>,
+        sampled_property: TransactionsCollection,
+    ) -> Result<Self> {
+        if tx_hashes.is_empty() {
+            bail!("Transaction hashes are empty");
+        }
+
+        Ok(Self {
+            chain_id,
+            tx_hashes,
+            positions: vec![],
+            sampled_property,
+        })
+    }
+
+    /// Set the positions of the transactions, resolved from their hashes
+    pub fn with_positions(mut self, positions: Vec<TxPosition>) -> Result<Self> {
+        if positions.len() != self.tx_hashes.len() {
+            bail!(
+                "Expected {} transaction positions, got {}",
+                self.tx_hashes.len(),
+                positions.len()
+            );
+        }
+        self.positions = positions;
+        Ok(self)
+    }
+
+    /// Whether the positions of the transactions are known
+    pub fn is_resolved(&self) -> bool {
+        self.positions.len() == self.tx_hashes.len()
+    }
+
+    /// Blocks of the transactions, in ascending order
+    pub fn blocks(&self) -> Vec<BlockNumber> {
+        let mut blocks: Vec<BlockNumber> = self
+            .positions
+            .iter()
+            .map(|position| position.block_number)
+            .collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+        blocks
+    }
+
+    /// Serialize the resolved transactions as the 8 bytes big endian block number and index,
+    /// followed by the hash of each transaction.
+    pub fn serialize_transactions(&self) -> Result<Vec<u8>> {
+        if !self.is_resolved() {
+            bail!("Transaction hashes are not resolved");
+        }
+        let mut serialized = Vec::with_capacity(self.tx_hashes.len() * 48);
+        for (tx_hash, position) in self.tx_hashes.iter().zip(&self.positions) {
+            serialized.extend_from_slice(&position.block_number.to_be_bytes());
+            serialized.extend_from_slice(&position.tx_index.to_be_bytes());
+            serialized.extend_from_slice(tx_hash.as_slice());
+        }
+        Ok(serialized)
+    }
+
+    /// Deserialize the transactions into their hashes and positions
+    pub fn deserialize_transactions(serialized: &[u8]) -> Result<(Vec<TxHash>, Vec<TxPosition>)> {
+        if serialized.is_empty() || serialized.len() % 48 != 0 {
+            bail!("Invalid serialized transactions");
         }
+        Ok(serialized
+            .chunks(48)
+            .map(|chunk| {
+                let position = TxPosition::new(
+                    u64::from_be_bytes(chunk[..8].try_into().unwrap()),
+                    u64::from_be_bytes(chunk[8..16].try_into().unwrap()),
+                );
+                (TxHash::from_slice(&chunk[16..]), position)
+            })
+            .unzip())
     }
 }
 
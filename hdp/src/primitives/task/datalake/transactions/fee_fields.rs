@@ -0,0 +1,385 @@
+use std::{fmt::Display, str::FromStr};
+
+use alloy::{
+    eips::eip4844::{calc_blob_gasprice, DATA_GAS_PER_BLOB},
+    primitives::U256,
+};
+use alloy_rlp::Decodable;
+use anyhow::{bail, Context, Result};
+use eth_trie_proofs::{tx::ConsensusTx, tx_receipt::ConsensusTxReceipt};
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::block::header::Header;
+
+/// Fee accounting fields of a transaction.
+///
+/// Unlike [`super::TransactionField`] and [`super::TransactionReceiptField`], these fields
+/// are derived from several proven objects: the transaction, its receipt, the receipt of
+/// the previous transaction in the block and the block header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionFeeField {
+    // Gas price actually paid, capped by `max_fee_per_gas` for EIP-1559 transactions
+    EffectiveGasPrice,
+    // Part of the effective gas price paid on top of the base fee
+    PriorityFeePerGas,
+    // Difference of cumulative gas used with the previous transaction
+    GasUsed,
+    // Gas used times the effective gas price, plus the blob fee
+    TotalFee,
+    // Blob gas used times the blob gas price. 0 for non EIP-4844 transactions
+    BlobFee,
+}
+
+impl TransactionFeeField {
+    pub fn variants() -> Vec<String> {
+        vec![
+            "EFFECTIVE_GAS_PRICE".to_string(),
+            "PRIORITY_FEE_PER_GAS".to_string(),
+            "GAS_USED".to_string(),
+            "TOTAL_FEE".to_string(),
+            "BLOB_FEE".to_string(),
+        ]
+    }
+
+    pub fn from_index(index: u8) -> Result<Self> {
+        match index {
+            0 => Ok(TransactionFeeField::EffectiveGasPrice),
+            1 => Ok(TransactionFeeField::PriorityFeePerGas),
+            2 => Ok(TransactionFeeField::GasUsed),
+            3 => Ok(TransactionFeeField::TotalFee),
+            4 => Ok(TransactionFeeField::BlobFee),
+            _ => bail!("Invalid transaction fee field index"),
+        }
+    }
+
+    pub fn to_index(&self) -> u8 {
+        match self {
+            TransactionFeeField::EffectiveGasPrice => 0,
+            TransactionFeeField::PriorityFeePerGas => 1,
+            TransactionFeeField::GasUsed => 2,
+            TransactionFeeField::TotalFee => 3,
+            TransactionFeeField::BlobFee => 4,
+        }
+    }
+
+    /// Whether the receipt of the previous transaction is needed to derive the field
+    pub fn needs_previous_receipt(&self) -> bool {
+        matches!(
+            self,
+            TransactionFeeField::GasUsed | TransactionFeeField::TotalFee
+        )
+    }
+
+    /// Derive the field from the rlp encoded header, transaction and receipts.
+    /// `previous_receipt` is `None` for the first transaction of the block.
+    pub fn decode_field_from_rlp(
+        &self,
+        mut header: &[u8],
+        tx: &[u8],
+        receipt: &[u8],
+        previous_receipt: Option<&[u8]>,
+    ) -> Result<U256> {
+        let header = <Header>::decode(&mut header).context("failed to decode header")?;
+        let raw_tx = ConsensusTx::rlp_decode(tx).context("failed to decode transaction")?;
+        let value = match self {
+            TransactionFeeField::EffectiveGasPrice => effective_gas_price(&header, &raw_tx)?,
+            TransactionFeeField::PriorityFeePerGas => {
+                let base_fee = U256::from(header.base_fee_per_gas.unwrap_or_default());
+                effective_gas_price(&header, &raw_tx)?
+                    .checked_sub(base_fee)
+                    .context("gas price is below the base fee")?
+            }
+            TransactionFeeField::GasUsed => gas_used(receipt, previous_receipt)?,
+            TransactionFeeField::TotalFee => {
+                let gas_fee = gas_used(receipt, previous_receipt)?
+                    .checked_mul(effective_gas_price(&header, &raw_tx)?)
+                    .context("total fee overflows u256")?;
+                gas_fee
+                    .checked_add(blob_fee(&header, &raw_tx)?)
+                    .context("total fee overflows u256")?
+            }
+            TransactionFeeField::BlobFee => blob_fee(&header, &raw_tx)?,
+        };
+        Ok(value)
+    }
+}
+
+fn effective_gas_price(header: &Header, raw_tx: &ConsensusTx) -> Result<U256> {
+    match (raw_tx.max_fee_per_gas(), raw_tx.max_priority_fee_per_gas()) {
+        (Some(max_fee), Some(max_priority_fee)) => {
+            let base_fee = header
+                .base_fee_per_gas
+                .context("base fee per gas does not exist")? as u128;
+            Ok(U256::from(
+                max_fee.min(base_fee.saturating_add(max_priority_fee)),
+            ))
+        }
+        _ => Ok(U256::from(
+            raw_tx.gas_price().context("gas price does not exist")?,
+        )),
+    }
+}
+
+fn gas_used(receipt: &[u8], previous_receipt: Option<&[u8]>) -> Result<U256> {
+    let cumulative_gas_used = ConsensusTxReceipt::rlp_decode(receipt)
+        .context("failed to decode transaction receipt")?
+        .cumulative_gas_used();
+    let previous_cumulative_gas_used = match previous_receipt {
+        Some(previous_receipt) => ConsensusTxReceipt::rlp_decode(previous_receipt)
+            .context("failed to decode previous transaction receipt")?
+            .cumulative_gas_used(),
+        None => 0,
+    };
+    let gas_used = cumulative_gas_used
+        .checked_sub(previous_cumulative_gas_used)
+        .context("cumulative gas used is below the one of the previous receipt")?;
+    Ok(U256::from(gas_used))
+}
+
+fn blob_fee(header: &Header, raw_tx: &ConsensusTx) -> Result<U256> {
+    match raw_tx.blob_versioned_hashes() {
+        Some(blob_versioned_hashes) => {
+            let excess_blob_gas = header
+                .excess_blob_gas
+                .context("excess blob gas does not exist")?;
+            let blob_gas_used = blob_versioned_hashes.len() as u64 * DATA_GAS_PER_BLOB;
+            Ok(U256::from(blob_gas_used) * U256::from(calc_blob_gasprice(excess_blob_gas)))
+        }
+        None => Ok(U256::ZERO),
+    }
+}
+
+impl FromStr for TransactionFeeField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "EFFECTIVE_GAS_PRICE" => Ok(TransactionFeeField::EffectiveGasPrice),
+            "PRIORITY_FEE_PER_GAS" => Ok(TransactionFeeField::PriorityFeePerGas),
+            "GAS_USED" => Ok(TransactionFeeField::GasUsed),
+            "TOTAL_FEE" => Ok(TransactionFeeField::TotalFee),
+            "BLOB_FEE" => Ok(TransactionFeeField::BlobFee),
+            _ => bail!("Unknown transaction fee field"),
+        }
+    }
+}
+
+impl Display for TransactionFeeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionFeeField::EffectiveGasPrice => write!(f, "EFFECTIVE_GAS_PRICE"),
+            TransactionFeeField::PriorityFeePerGas => write!(f, "PRIORITY_FEE_PER_GAS"),
+            TransactionFeeField::GasUsed => write!(f, "GAS_USED"),
+            TransactionFeeField::TotalFee => write!(f, "TOTAL_FEE"),
+            TransactionFeeField::BlobFee => write!(f, "BLOB_FEE"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{
+            Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom, SignableTransaction,
+            TxEip1559, TxEip4844, TxEip4844Variant, TxEnvelope, TxLegacy,
+        },
+        primitives::{Address, Bloom, Bytes, Signature, TxKind, B256},
+    };
+
+    use super::*;
+
+    fn encoded_header(base_fee_per_gas: u64, excess_blob_gas: u64) -> Vec<u8> {
+        Header::new(
+            B256::ZERO,
+            B256::ZERO,
+            Address::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+            Bloom::ZERO,
+            U256::ZERO,
+            20000000,
+            30000000,
+            15000000,
+            1700000000,
+            Bytes::new(),
+            B256::ZERO,
+            0,
+            Some(base_fee_per_gas),
+            Some(B256::ZERO),
+            Some(0),
+            Some(excess_blob_gas),
+            Some(B256::ZERO),
+        )
+        .rlp_encode()
+    }
+
+    fn encoded_receipt(cumulative_gas_used: u128) -> Vec<u8> {
+        let receipt = Receipt {
+            status: Eip658Value::Eip658(true),
+            cumulative_gas_used,
+            logs: vec![],
+        };
+        ConsensusTxReceipt(ReceiptEnvelope::Eip1559(ReceiptWithBloom::from(receipt))).rlp_encode()
+    }
+
+    #[test]
+    fn test_eip1559_fee_fields() {
+        let tx = TxEip1559 {
+            chain_id: 1,
+            nonce: 0,
+            gas_limit: 100000,
+            max_fee_per_gas: 30,
+            max_priority_fee_per_gas: 3,
+            to: TxKind::Create,
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input: Bytes::new(),
+        };
+        let tx = ConsensusTx(TxEnvelope::Eip1559(
+            tx.into_signed(Signature::test_signature()),
+        ))
+        .rlp_encode();
+        let receipt = encoded_receipt(71000);
+        let previous_receipt = encoded_receipt(50000);
+
+        // base fee + priority fee is below the max fee
+        let header = encoded_header(20, 0);
+        let decode = |field: TransactionFeeField, previous_receipt: Option<&[u8]>| {
+            field
+                .decode_field_from_rlp(&header, &tx, &receipt, previous_receipt)
+                .unwrap()
+        };
+        assert_eq!(
+            decode(TransactionFeeField::EffectiveGasPrice, None),
+            U256::from(23)
+        );
+        assert_eq!(
+            decode(TransactionFeeField::PriorityFeePerGas, None),
+            U256::from(3)
+        );
+        assert_eq!(
+            decode(TransactionFeeField::GasUsed, Some(&previous_receipt)),
+            U256::from(21000)
+        );
+        assert_eq!(
+            decode(TransactionFeeField::GasUsed, None),
+            U256::from(71000)
+        );
+        assert_eq!(
+            decode(TransactionFeeField::TotalFee, Some(&previous_receipt)),
+            U256::from(21000 * 23)
+        );
+        assert_eq!(decode(TransactionFeeField::BlobFee, None), U256::ZERO);
+
+        // effective gas price is capped by the max fee
+        let header = encoded_header(29, 0);
+        assert_eq!(
+            TransactionFeeField::EffectiveGasPrice
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(30)
+        );
+        assert_eq!(
+            TransactionFeeField::PriorityFeePerGas
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(1)
+        );
+
+        // receipts out of order are an error rather than an underflow
+        assert!(TransactionFeeField::GasUsed
+            .decode_field_from_rlp(&header, &tx, &previous_receipt, Some(&receipt))
+            .is_err());
+    }
+
+    #[test]
+    fn test_blob_fee_field() {
+        let tx = TxEip4844 {
+            chain_id: 1,
+            nonce: 0,
+            gas_limit: 21000,
+            max_fee_per_gas: 30,
+            max_priority_fee_per_gas: 1,
+            to: Address::ZERO,
+            value: U256::ZERO,
+            access_list: Default::default(),
+            blob_versioned_hashes: vec![B256::ZERO, B256::ZERO],
+            max_fee_per_blob_gas: 10,
+            input: Bytes::new(),
+        };
+        let tx = ConsensusTx(TxEnvelope::Eip4844(
+            TxEip4844Variant::from(tx).into_signed(Signature::test_signature()),
+        ))
+        .rlp_encode();
+        let header = encoded_header(20, 0);
+        let receipt = encoded_receipt(21000);
+
+        // blob gas price is 1 wei without excess blob gas
+        let blob_fee = U256::from(2 * DATA_GAS_PER_BLOB);
+        assert_eq!(
+            TransactionFeeField::BlobFee
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            blob_fee
+        );
+        assert_eq!(
+            TransactionFeeField::TotalFee
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(21000 * 21) + blob_fee
+        );
+    }
+
+    #[test]
+    fn test_legacy_fee_fields() {
+        let tx = TxLegacy {
+            chain_id: Some(1),
+            nonce: 0,
+            gas_price: 25,
+            gas_limit: 21000,
+            to: TxKind::Create,
+            value: U256::ZERO,
+            input: Bytes::new(),
+        };
+        let tx = ConsensusTx(TxEnvelope::Legacy(
+            tx.into_signed(Signature::test_signature().with_chain_id(1)),
+        ))
+        .rlp_encode();
+        let header = encoded_header(20, 0);
+        let receipt = encoded_receipt(21000);
+
+        assert_eq!(
+            TransactionFeeField::EffectiveGasPrice
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(25)
+        );
+        assert_eq!(
+            TransactionFeeField::PriorityFeePerGas
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(5)
+        );
+        assert_eq!(
+            TransactionFeeField::TotalFee
+                .decode_field_from_rlp(&header, &tx, &receipt, None)
+                .unwrap(),
+            U256::from(21000 * 25)
+        );
+
+        // a gas price below the base fee is an error rather than an underflow
+        let header = encoded_header(30, 0);
+        assert!(TransactionFeeField::PriorityFeePerGas
+            .decode_field_from_rlp(&header, &tx, &receipt, None)
+            .is_err());
+
+        for field in TransactionFeeField::variants() {
+            let field = TransactionFeeField::from_str(&field).unwrap();
+            assert_eq!(
+                TransactionFeeField::from_index(field.to_index()).unwrap(),
+                field
+            );
+        }
+    }
+}
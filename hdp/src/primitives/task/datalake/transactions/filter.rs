@@ -0,0 +1,240 @@
+use std::{fmt::Display, str::FromStr};
+
+use alloy::primitives::{Address, FixedBytes};
+use anyhow::{bail, Result};
+use eth_trie_proofs::tx::ConsensusTx;
+use serde::{Deserialize, Serialize};
+
+const SENDER_FLAG: u8 = 1;
+const TO_FLAG: u8 = 1 << 1;
+const SELECTOR_FLAG: u8 = 1 << 2;
+
+/// Optional filters on the transactions of a transactions datalake.
+///
+/// A transaction is sampled only if it matches every filter that is set.
+/// Contract creations never match a `to` filter, and transactions with
+/// less than 4 bytes of input never match a `selector` filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsFilter {
+    /// Recovered sender of the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<Address>,
+    /// Recipient of the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// First 4 bytes of the transaction input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<FixedBytes<4>>,
+}
+
+impl TransactionsFilter {
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_none() && self.to.is_none() && self.selector.is_none()
+    }
+
+    /// Check if the rlp encoded transaction matches the filter.
+    /// Fails if the transaction cannot be decoded.
+    pub fn matches(&self, encoded_tx: &[u8]) -> Result<bool> {
+        if self.is_empty() {
+            return Ok(true);
+        }
+
+        let raw_tx = ConsensusTx::rlp_decode(encoded_tx)
+            .map_err(|e| anyhow::anyhow!("Failed to decode transaction: {:?}", e))?;
+        if let Some(sender) = self.sender {
+            if raw_tx.sender().ok() != Some(sender) {
+                return Ok(false);
+            }
+        }
+        if let Some(to) = self.to {
+            if raw_tx.to().to() != Some(&to) {
+                return Ok(false);
+            }
+        }
+        if let Some(selector) = self.selector {
+            let input = raw_tx.input();
+            if input.len() < 4 || input[..4] != selector[..] {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Serialize the filter as a flag byte followed by the filters that are set,
+    /// in the order of sender, to and selector.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut flags = 0;
+        let mut serialized = vec![0];
+        if let Some(sender) = self.sender {
+            flags |= SENDER_FLAG;
+            serialized.extend_from_slice(sender.as_slice());
+        }
+        if let Some(to) = self.to {
+            flags |= TO_FLAG;
+            serialized.extend_from_slice(to.as_slice());
+        }
+        if let Some(selector) = self.selector {
+            flags |= SELECTOR_FLAG;
+            serialized.extend_from_slice(selector.as_slice());
+        }
+        serialized[0] = flags;
+        serialized
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        if serialized.is_empty() {
+            bail!("Invalid transactions filter");
+        }
+        let flags = serialized[0];
+        if flags & !(SENDER_FLAG | TO_FLAG | SELECTOR_FLAG) != 0 {
+            bail!("Unknown transactions filter flags: {}", flags);
+        }
+
+        let mut filter = Self::default();
+        let mut cursor = 1;
+        let mut take = |len: usize| -> Result<&[u8]> {
+            if serialized.len() < cursor + len {
+                bail!("Invalid transactions filter");
+            }
+            cursor += len;
+            Ok(&serialized[cursor - len..cursor])
+        };
+        if flags & SENDER_FLAG != 0 {
+            filter.sender = Some(Address::from_slice(take(20)?));
+        }
+        if flags & TO_FLAG != 0 {
+            filter.to = Some(Address::from_slice(take(20)?));
+        }
+        if flags & SELECTOR_FLAG != 0 {
+            filter.selector = Some(FixedBytes::from_slice(take(4)?));
+        }
+        if cursor != serialized.len() {
+            bail!("Invalid transactions filter");
+        }
+        Ok(filter)
+    }
+}
+
+impl FromStr for TransactionsFilter {
+    type Err = anyhow::Error;
+
+    /// Parse filter from comma separated "key=value" pairs,
+    /// e.g. "to=0x...,selector=0xa9059cbb"
+    fn from_str(s: &str) -> Result<Self> {
+        let mut filter = Self::default();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid transactions filter format"))?;
+            match key.trim().to_lowercase().as_str() {
+                "sender" | "from" => filter.sender = Some(value.trim().parse()?),
+                "to" => filter.to = Some(value.trim().parse()?),
+                "selector" => filter.selector = Some(value.trim().parse()?),
+                _ => bail!("Unknown transactions filter: {}", key),
+            }
+        }
+        Ok(filter)
+    }
+}
+
+impl Display for TransactionsFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut pairs = Vec::new();
+        if let Some(sender) = self.sender {
+            pairs.push(format!("sender={}", sender));
+        }
+        if let Some(to) = self.to {
+            pairs.push(format!("to={}", to));
+        }
+        if let Some(selector) = self.selector {
+            pairs.push(format!("selector={}", selector));
+        }
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEnvelope, TxLegacy},
+        primitives::{address, bytes, fixed_bytes, Signature, TxKind, U256},
+    };
+
+    use super::*;
+
+    fn encoded_tx(to: TxKind, input: alloy::primitives::Bytes) -> (Vec<u8>, Address) {
+        let tx = TxLegacy {
+            chain_id: Some(1),
+            nonce: 0,
+            gas_price: 1,
+            gas_limit: 21000,
+            to,
+            value: U256::from(1),
+            input,
+        };
+        let envelope =
+            TxEnvelope::Legacy(tx.into_signed(Signature::test_signature().with_chain_id(1)));
+        let sender = envelope.recover_signer().unwrap();
+        (ConsensusTx(envelope).rlp_encode(), sender)
+    }
+
+    #[test]
+    fn test_filter_serialize() {
+        let filter = TransactionsFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(filter.serialize(), [0]);
+        assert_eq!(TransactionsFilter::deserialize(&[0]).unwrap(), filter);
+
+        let filter: TransactionsFilter =
+            "to=0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6,selector=0xa9059cbb"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            filter.to,
+            Some(address!("7b2f05ce9ae365c3dbf30657e2dc6449989e83d6"))
+        );
+        assert_eq!(filter.selector, Some(fixed_bytes!("a9059cbb")));
+        let serialized = filter.serialize();
+        assert_eq!(serialized.len(), 25);
+        assert_eq!(serialized[0], TO_FLAG | SELECTOR_FLAG);
+        assert_eq!(
+            TransactionsFilter::deserialize(&serialized).unwrap(),
+            filter
+        );
+        assert_eq!(
+            filter.to_string().parse::<TransactionsFilter>().unwrap(),
+            filter
+        );
+
+        assert!(TransactionsFilter::deserialize(&serialized[..24]).is_err());
+        assert!(TransactionsFilter::deserialize(&[8]).is_err());
+        assert!("value=1".parse::<TransactionsFilter>().is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let contract = address!("7b2f05ce9ae365c3dbf30657e2dc6449989e83d6");
+        let (transfer, sender) =
+            encoded_tx(TxKind::Call(contract), bytes!("a9059cbb0000000000000000"));
+        let (creation, _) = encoded_tx(TxKind::Create, bytes!("a9059cbb"));
+        let (short_input, _) = encoded_tx(TxKind::Call(contract), bytes!("a905"));
+
+        let filter = TransactionsFilter {
+            sender: Some(sender),
+            to: Some(contract),
+            selector: Some(fixed_bytes!("a9059cbb")),
+        };
+        assert!(filter.matches(&transfer).unwrap());
+        assert!(!filter.matches(&creation).unwrap());
+        assert!(!filter.matches(&short_input).unwrap());
+
+        let filter = TransactionsFilter {
+            sender: Some(contract),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&transfer).unwrap());
+        assert!(TransactionsFilter::default().matches(&creation).unwrap());
+        assert!(filter.matches(&[0xc0]).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Transaction index ranges relative to the number of transactions of a block.
+//!
+//! A bound is either an index from the start of the block, or an offset from the end of the block:
+//! - `5` is the transaction at index 5.
+//! - `-10` is 10 transactions before the end of the block.
+//! - `last` (or `all`) is the end of the block, so `0..last` samples every transaction.
+//!
+//! Relative bounds are resolved against the number of transactions of the block before compilation.
+//! The number of transactions is committed along with the bounds, and proven against the transactions trie.
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "TxIndexBoundRepr", into = "TxIndexBoundRepr")]
+pub enum TxIndexBound {
+    /// Index from the start of the block
+    FromStart(u64),
+    /// Number of transactions before the end of the block
+    FromEnd(u64),
+}
+
+impl TxIndexBound {
+    /// End of the block, i.e. the number of transactions
+    pub const LAST: Self = TxIndexBound::FromEnd(0);
+
+    pub fn is_relative(&self) -> bool {
+        matches!(self, TxIndexBound::FromEnd(_))
+    }
+
+    /// Resolve the bound to an index, given the number of transactions of the block
+    pub fn resolve(&self, tx_count: u64) -> u64 {
+        match self {
+            TxIndexBound::FromStart(index) => *index,
+            TxIndexBound::FromEnd(offset) => tx_count.saturating_sub(*offset),
+        }
+    }
+
+    /// Serialize as a flag byte, 0 from start and 1 from end, followed by the 8 bytes big endian value
+    pub fn serialize(&self) -> [u8; 9] {
+        let (flag, value) = match self {
+            TxIndexBound::FromStart(index) => (0, index),
+            TxIndexBound::FromEnd(offset) => (1, offset),
+        };
+        let mut serialized = [0u8; 9];
+        serialized[0] = flag;
+        serialized[1..].copy_from_slice(&value.to_be_bytes());
+        serialized
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        if serialized.len() != 9 {
+            bail!("Invalid transaction index bound");
+        }
+        let value = u64::from_be_bytes(serialized[1..].try_into().unwrap());
+        match serialized[0] {
+            0 => Ok(TxIndexBound::FromStart(value)),
+            1 => Ok(TxIndexBound::FromEnd(value)),
+            flag => bail!("Unknown transaction index bound flag: {}", flag),
+        }
+    }
+}
+
+impl Default for TxIndexBound {
+    fn default() -> Self {
+        TxIndexBound::FromStart(0)
+    }
+}
+
+impl FromStr for TxIndexBound {
+    type Err = anyhow::Error;
+
+    /// Parse bound from an index, a negative offset from the end, or "last" / "all"
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("last") || s.eq_ignore_ascii_case("all") {
+            return Ok(TxIndexBound::LAST);
+        }
+        match s.strip_prefix('-') {
+            Some(offset) => Ok(TxIndexBound::FromEnd(offset.parse()?)),
+            None => Ok(TxIndexBound::FromStart(s.parse()?)),
+        }
+    }
+}
+
+impl Display for TxIndexBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TxIndexBound::FromStart(index) => write!(f, "{}", index),
+            TxIndexBound::FromEnd(0) => write!(f, "last"),
+            TxIndexBound::FromEnd(offset) => write!(f, "-{}", offset),
+        }
+    }
+}
+
+/// JSON form of [`TxIndexBound`], either a number or a string
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TxIndexBoundRepr {
+    Index(u64),
+    Offset(i64),
+    Str(String),
+}
+
+impl TryFrom<TxIndexBoundRepr> for TxIndexBound {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TxIndexBoundRepr) -> Result<Self> {
+        match value {
+            TxIndexBoundRepr::Index(index) => Ok(TxIndexBound::FromStart(index)),
+            TxIndexBoundRepr::Offset(offset) => Ok(TxIndexBound::FromEnd(offset.unsigned_abs())),
+            TxIndexBoundRepr::Str(s) => s.parse(),
+        }
+    }
+}
+
+impl From<TxIndexBound> for TxIndexBoundRepr {
+    fn from(value: TxIndexBound) -> Self {
+        match value {
+            TxIndexBound::FromStart(index) => TxIndexBoundRepr::Index(index),
+            bound => TxIndexBoundRepr::Str(bound.to_string()),
+        }
+    }
+}
+
+/// Requested range of transaction indexes, with relative bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxIndexRange {
+    /// Start of the range (inclusive)
+    pub start: TxIndexBound,
+    /// End of the range (exclusive)
+    pub end: TxIndexBound,
+    /// Number of transactions of the block the range is resolved against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_count: Option<u64>,
+}
+
+impl TxIndexRange {
+    pub fn new(start: TxIndexBound, end: TxIndexBound) -> Self {
+        Self {
+            start,
+            end,
+            tx_count: None,
+        }
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.start.is_relative() || self.end.is_relative()
+    }
+
+    /// Start and end indexes if both bounds are from the start of the block
+    pub fn absolute(&self) -> Option<(u64, u64)> {
+        match (self.start, self.end) {
+            (TxIndexBound::FromStart(start), TxIndexBound::FromStart(end)) => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    /// Resolve the range against the number of transactions of the block
+    pub fn resolve(&self, tx_count: u64) -> Self {
+        Self {
+            tx_count: Some(tx_count),
+            ..*self
+        }
+    }
+
+    /// Resolved start and end indexes, `None` if the range is not resolved yet
+    pub fn indexes(&self) -> Option<(u64, u64)> {
+        self.tx_count.map(|tx_count| {
+            let start = self.start.resolve(tx_count);
+            (start, self.end.resolve(tx_count).max(start))
+        })
+    }
+
+    /// Serialize as the start and end bounds followed by the 8 bytes big endian number of transactions.
+    /// The range must be resolved.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let tx_count = match self.tx_count {
+            Some(tx_count) => tx_count,
+            None => bail!("Transaction index range is not resolved"),
+        };
+        let mut serialized = self.start.serialize().to_vec();
+        serialized.extend_from_slice(&self.end.serialize());
+        serialized.extend_from_slice(&tx_count.to_be_bytes());
+        Ok(serialized)
+    }
+
+    pub fn deserialize(serialized: &[u8]) -> Result<Self> {
+        if serialized.len() != 26 {
+            bail!("Invalid transaction index range");
+        }
+        Ok(Self {
+            start: TxIndexBound::deserialize(&serialized[..9])?,
+            end: TxIndexBound::deserialize(&serialized[9..18])?,
+            tx_count: Some(u64::from_be_bytes(serialized[18..].try_into().unwrap())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_index_bound() {
+        assert_eq!(
+            "5".parse::<TxIndexBound>().unwrap(),
+            TxIndexBound::FromStart(5)
+        );
+        assert_eq!(
+            "-10".parse::<TxIndexBound>().unwrap(),
+            TxIndexBound::FromEnd(10)
+        );
+        assert_eq!("LAST".parse::<TxIndexBound>().unwrap(), TxIndexBound::LAST);
+        assert_eq!("all".parse::<TxIndexBound>().unwrap(), TxIndexBound::LAST);
+        assert!("-".parse::<TxIndexBound>().is_err());
+        assert_eq!(TxIndexBound::FromEnd(10).to_string(), "-10");
+        assert_eq!(TxIndexBound::LAST.to_string(), "last");
+
+        assert_eq!(TxIndexBound::FromStart(5).resolve(93), 5);
+        assert_eq!(TxIndexBound::FromEnd(10).resolve(93), 83);
+        assert_eq!(TxIndexBound::FromEnd(100).resolve(93), 0);
+        assert_eq!(TxIndexBound::LAST.resolve(93), 93);
+
+        let bounds: Vec<TxIndexBound> = serde_json::from_str(r#"[5, -10, "last", "-3"]"#).unwrap();
+        assert_eq!(
+            bounds,
+            vec![
+                TxIndexBound::FromStart(5),
+                TxIndexBound::FromEnd(10),
+                TxIndexBound::LAST,
+                TxIndexBound::FromEnd(3)
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&bounds).unwrap(),
+            r#"[5,"-10","last","-3"]"#
+        );
+    }
+
+    #[test]
+    fn test_tx_index_range() {
+        let range = TxIndexRange::new(TxIndexBound::FromEnd(10), TxIndexBound::LAST);
+        assert!(range.is_relative());
+        assert_eq!(range.absolute(), None);
+        assert_eq!(range.indexes(), None);
+        assert!(range.serialize().is_err());
+
+        let resolved = range.resolve(93);
+        assert_eq!(resolved.indexes(), Some((83, 93)));
+        let serialized = resolved.serialize().unwrap();
+        assert_eq!(serialized.len(), 26);
+        assert_eq!(TxIndexRange::deserialize(&serialized).unwrap(), resolved);
+
+        // end never precedes start
+        let range = TxIndexRange::new(TxIndexBound::FromStart(50), TxIndexBound::FromEnd(60));
+        assert_eq!(range.resolve(93).indexes(), Some((50, 50)));
+
+        let range = TxIndexRange::new(TxIndexBound::FromStart(0), TxIndexBound::FromStart(10));
+        assert!(!range.is_relative());
+        assert_eq!(range.absolute(), Some((0, 10)));
+    }
+}
@@ -1,10 +1,16 @@
 pub mod collection;
 pub mod datalake;
+pub mod fee_fields;
+pub mod filter;
+pub mod index_range;
 pub mod rlp_fields;
 
 // Export all types
 pub use collection::*;
 pub use datalake::*;
+pub use fee_fields::*;
+pub use filter::*;
+pub use index_range::*;
 pub use rlp_fields::*;
 
 #[cfg(test)]
@@ -13,12 +19,16 @@ mod tests {
     use std::str::FromStr;
 
     use crate::primitives::{
-        solidity_types::traits::DatalakeCodecs, task::datalake::DatalakeCollection, ChainId,
+        solidity_types::traits::DatalakeCodecs,
+        task::datalake::{envelope::DatalakeEnvelope, DatalakeCollection, DatalakeField},
+        ChainId,
     };
     use alloy::{
+        consensus::{SignableTransaction, TxEip1559, TxEnvelope},
         hex,
-        primitives::{B256, U256},
+        primitives::{address, bytes, Signature, TxKind, B256, U256},
     };
+    use eth_trie_proofs::tx::ConsensusTx;
 
     use super::*;
 
@@ -99,6 +109,347 @@ mod tests {
         assert_eq!(decoded, transaction_datalake);
     }
 
+    #[test]
+    fn test_transactions_in_range_datalake() {
+        let sampled_property =
+            TransactionsCollection::Transactions(TransactionField::MaxPriorityFeePerGas);
+        let transaction_datalake = TransactionsInRangeDatalake::new(
+            ChainId::EthereumSepolia,
+            1000000,
+            1000010,
+            5,
+            sampled_property,
+            0,
+            100,
+            1,
+            IncludedTypes::from(&[0, 0, 1, 1]),
+        );
+        assert_eq!(
+            transaction_datalake.blocks().collect::<Vec<_>>(),
+            vec![1000000, 1000005, 1000010]
+        );
+
+        let encoded = transaction_datalake.encode().unwrap();
+        assert_eq!(encoded.len(), 32 * 12);
+        // datalake type, block range and block increment
+        assert_eq!(encoded[31], 2);
+        assert_eq!(U256::from_be_slice(&encoded[64..96]), U256::from(1000000));
+        assert_eq!(U256::from_be_slice(&encoded[96..128]), U256::from(1000010));
+        assert_eq!(U256::from_be_slice(&encoded[128..160]), U256::from(5));
+
+        let decoded = TransactionsInRangeDatalake::decode(&encoded).unwrap();
+        assert_eq!(decoded, transaction_datalake);
+        assert!(TransactionsInBlockDatalake::decode(&encoded).is_err());
+
+        let envelope = DatalakeEnvelope::decode(&encoded).unwrap();
+        assert_eq!(
+            envelope,
+            DatalakeEnvelope::TransactionsInRange(transaction_datalake.clone())
+        );
+        assert_eq!(envelope.commit(), transaction_datalake.commit());
+    }
+
+    #[test]
+    fn test_transactions_datalake_with_filter() {
+        let filter: TransactionsFilter =
+            "to=0x7b2f05ce9ae365c3dbf30657e2dc6449989e83d6,selector=0xa9059cbb"
+                .parse()
+                .unwrap();
+        let transaction_datalake = TransactionsInBlockDatalake::new(
+            ChainId::EthereumSepolia,
+            1000000,
+            TransactionsCollection::Transactions(TransactionField::Value),
+            0,
+            10,
+            1,
+            IncludedTypes::from(&[1, 1, 1, 1]),
+        );
+        let filtered_datalake = transaction_datalake.clone().with_filter(filter.clone());
+
+        // filter is part of the encoding and the commitment
+        let encoded = filtered_datalake.encode().unwrap();
+        assert_ne!(encoded, transaction_datalake.encode().unwrap());
+        assert_ne!(filtered_datalake.commit(), transaction_datalake.commit());
+        assert_eq!(
+            TransactionsInBlockDatalake::decode(&encoded).unwrap(),
+            filtered_datalake
+        );
+        assert_eq!(
+            TransactionsInBlockDatalake::decode(&transaction_datalake.encode().unwrap()).unwrap(),
+            transaction_datalake
+        );
+
+        let range_datalake = TransactionsInRangeDatalake::new(
+            ChainId::EthereumSepolia,
+            1000000,
+            1000010,
+            1,
+            TransactionsCollection::TranasactionReceipts(TransactionReceiptField::Success),
+            0,
+            10,
+            1,
+            IncludedTypes::from(&[1, 1, 1, 1]),
+        )
+        .with_filter(filter);
+        let encoded = range_datalake.encode().unwrap();
+        assert_eq!(
+            TransactionsInRangeDatalake::decode(&encoded).unwrap(),
+            range_datalake
+        );
+
+        let json = serde_json::to_string(&filtered_datalake).unwrap();
+        assert!(json.contains("\"selector\":\"0xa9059cbb\""));
+        let json = serde_json::to_string(&transaction_datalake).unwrap();
+        assert!(!json.contains("filter"));
+    }
+
+    #[test]
+    fn test_transactions_datalake_with_index_range() {
+        let transaction_datalake = TransactionsInBlockDatalake::new(
+            ChainId::EthereumSepolia,
+            1000000,
+            TransactionsCollection::Transactions(TransactionField::Value),
+            0,
+            10,
+            1,
+            IncludedTypes::from(&[1, 1, 1, 1]),
+        );
+
+        // last 10 transactions of the block
+        let mut json = serde_json::to_value(&transaction_datalake).unwrap();
+        json["startIndex"] = serde_json::json!(-10);
+        json["endIndex"] = serde_json::json!("last");
+        let relative_datalake: TransactionsInBlockDatalake = serde_json::from_value(json).unwrap();
+        let index_range = TxIndexRange::new(TxIndexBound::FromEnd(10), TxIndexBound::LAST);
+        assert_eq!(relative_datalake.index_range, Some(index_range));
+        assert!(!relative_datalake.is_resolved());
+        assert!(relative_datalake.encode().is_err());
+
+        // end index defaults to the end of the block
+        let mut json = serde_json::to_value(&transaction_datalake).unwrap();
+        json.as_object_mut().unwrap().remove("endIndex");
+        let all_datalake: TransactionsInBlockDatalake = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            all_datalake.index_range,
+            Some(TxIndexRange::new(
+                TxIndexBound::FromStart(0),
+                TxIndexBound::LAST
+            ))
+        );
+
+        let resolved_datalake = relative_datalake.with_index_range(index_range.resolve(93));
+        assert!(resolved_datalake.is_resolved());
+        assert_eq!(resolved_datalake.start_index, 83);
+        assert_eq!(resolved_datalake.end_index, 93);
+
+        // resolved index range is part of the encoding and the commitment
+        let encoded = resolved_datalake.encode().unwrap();
+        assert_eq!(
+            TransactionsInBlockDatalake::decode(&encoded).unwrap(),
+            resolved_datalake
+        );
+        let absolute_datalake = TransactionsInBlockDatalake {
+            start_index: 83,
+            end_index: 93,
+            ..transaction_datalake.clone()
+        };
+        assert_ne!(resolved_datalake.commit(), absolute_datalake.commit());
+        let filtered_datalake = resolved_datalake
+            .clone()
+            .with_filter("selector=0xa9059cbb".parse().unwrap());
+        assert_eq!(
+            TransactionsInBlockDatalake::decode(&filtered_datalake.encode().unwrap()).unwrap(),
+            filtered_datalake
+        );
+
+        let json = serde_json::to_string(&resolved_datalake).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransactionsInBlockDatalake>(&json).unwrap(),
+            resolved_datalake
+        );
+        // absolute range is not recorded as an index range
+        let json = serde_json::to_string(&transaction_datalake).unwrap();
+        assert!(!json.contains("indexRange"));
+        assert_eq!(
+            serde_json::from_str::<TransactionsInBlockDatalake>(&json).unwrap(),
+            transaction_datalake
+        );
+    }
+
+    #[test]
+    fn test_transactions_by_hash_datalake() {
+        let tx_hashes = vec![B256::repeat_byte(0x11), B256::repeat_byte(0x22)];
+        let transaction_datalake = TransactionsByHashDatalake::new(
+            ChainId::EthereumSepolia,
+            tx_hashes.clone(),
+            TransactionsCollection::Transactions(TransactionField::Value),
+        )
+        .unwrap();
+        assert!(!transaction_datalake.is_resolved());
+        assert!(transaction_datalake.encode().is_err());
+
+        let parsed: TransactionsByHashDatalake = serde_json::from_str(
+            r#"{
+                "chainId": "ETHEREUM_SEPOLIA",
+                "txHashes": [
+                    "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "0x2222222222222222222222222222222222222222222222222222222222222222"
+                ],
+                "sampledProperty": "tx.value"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, transaction_datalake);
+
+        let resolved_datalake = transaction_datalake
+            .clone()
+            .with_positions(vec![
+                TxPosition::new(6000100, 12),
+                TxPosition::new(6000000, 3),
+            ])
+            .unwrap();
+        assert!(resolved_datalake.is_resolved());
+        assert_eq!(resolved_datalake.blocks(), vec![6000000, 6000100]);
+
+        // positions are committed along with the hashes
+        let encoded = resolved_datalake.encode().unwrap();
+        assert_eq!(
+            TransactionsByHashDatalake::decode(&encoded).unwrap(),
+            resolved_datalake
+        );
+        let envelope = DatalakeEnvelope::decode(&encoded).unwrap();
+        assert_eq!(
+            envelope,
+            DatalakeEnvelope::TransactionsByHash(resolved_datalake.clone())
+        );
+        assert_ne!(
+            resolved_datalake.commit(),
+            transaction_datalake
+                .clone()
+                .with_positions(vec![
+                    TxPosition::new(6000100, 12),
+                    TxPosition::new(6000000, 4),
+                ])
+                .unwrap()
+                .commit()
+        );
+
+        let json = serde_json::to_string(&resolved_datalake).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransactionsByHashDatalake>(&json).unwrap(),
+            resolved_datalake
+        );
+
+        assert!(transaction_datalake
+            .with_positions(vec![TxPosition::new(6000000, 3)])
+            .is_err());
+        assert!(TransactionsByHashDatalake::new(
+            ChainId::EthereumSepolia,
+            vec![],
+            TransactionsCollection::Transactions(TransactionField::Value),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_derived_transaction_fields() {
+        let tx = TxEip1559 {
+            chain_id: 11155111,
+            nonce: 7,
+            gas_limit: 100000,
+            max_fee_per_gas: 2,
+            max_priority_fee_per_gas: 1,
+            to: TxKind::Create,
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input: bytes!("6080604052"),
+        };
+        let envelope = TxEnvelope::Eip1559(tx.into_signed(Signature::test_signature()));
+        let sender = envelope.recover_signer().unwrap();
+        let tx_hash = *envelope.tx_hash();
+        let encoded = ConsensusTx(envelope).rlp_encode();
+
+        assert_eq!(
+            TransactionField::Sender
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from_be_slice(sender.as_slice())
+        );
+        assert_eq!(
+            TransactionField::Hash
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from_be_bytes(tx_hash.0)
+        );
+        assert_eq!(
+            TransactionField::Type
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(
+            TransactionField::Selector
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from(0x60806040u64)
+        );
+        assert_eq!(
+            TransactionField::CreatedAddress
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from_be_slice(sender.create(7).as_slice())
+        );
+
+        for field in ["SENDER", "HASH", "TYPE", "SELECTOR", "CREATED_ADDRESS"] {
+            let field = TransactionField::from_str(field).unwrap();
+            assert_eq!(
+                TransactionField::from_index(field.to_index()).unwrap(),
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_call_transaction_fields() {
+        let to = address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4");
+        let tx = TxEip1559 {
+            chain_id: 11155111,
+            nonce: 7,
+            gas_limit: 100000,
+            max_fee_per_gas: 2,
+            max_priority_fee_per_gas: 1,
+            to: TxKind::Call(to),
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input: bytes!("a9059cbb"),
+        };
+        let encoded = ConsensusTx(TxEnvelope::Eip1559(
+            tx.into_signed(Signature::test_signature()),
+        ))
+        .rlp_encode();
+
+        assert_eq!(
+            TransactionField::To
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::from_be_slice(to.as_slice())
+        );
+        // call transactions do not create a contract
+        assert_eq!(
+            TransactionField::CreatedAddress
+                .decode_field_from_rlp(&encoded)
+                .unwrap(),
+            U256::ZERO
+        );
+        // fields absent from the transaction type are errors
+        assert!(TransactionField::GasPrice
+            .decode_field_from_rlp(&encoded)
+            .is_err());
+        assert!(TransactionField::Nonce
+            .decode_field_from_rlp(&[0xc0])
+            .is_err());
+    }
+
     #[test]
     fn test_tx_collection_serialize() {
         let tx_collection = TransactionsCollection::Transactions(TransactionField::Nonce);
@@ -113,6 +464,17 @@ mod tests {
         let tx_collection = TransactionsCollection::Transactions(TransactionField::AccessList);
         let serialized = tx_collection.serialize().unwrap();
         assert_eq!(serialized, [1, 10]);
+        assert_eq!(tx_collection.value_bytes(), 32);
+
+        // addresses and selectors are aggregated at their native size
+        assert_eq!(
+            TransactionsCollection::Transactions(TransactionField::To).value_bytes(),
+            20
+        );
+        assert_eq!(
+            TransactionsCollection::Transactions(TransactionField::Selector).value_bytes(),
+            4
+        );
     }
 
     #[test]
@@ -1,7 +1,7 @@
 use std::{fmt::Display, str::FromStr};
 
 use alloy::{consensus::Eip658Value, primitives::U256};
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use eth_trie_proofs::{tx::ConsensusTx, tx_receipt::ConsensusTxReceipt};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +32,18 @@ pub enum TransactionField {
     BlobVersionedHashes,
     // Only for EIP-4844 transactions
     MaxFeePerBlobGas,
+
+    // ===== Derived fields =====
+    // Sender recovered from the signature
+    Sender,
+    // Hash of the transaction
+    Hash,
+    // Type of the transaction
+    Type,
+    // First 4 bytes of the input, 0 if the input is shorter
+    Selector,
+    // Only for contract creation transactions
+    CreatedAddress,
 }
 
 impl TransactionField {
@@ -52,6 +64,11 @@ impl TransactionField {
             "MAX_PRIORITY_FEE_PER_GAS".to_string(),
             "BLOB_VERSIONED_HASHES".to_string(),
             "MAX_FEE_PER_BLOB_GAS".to_string(),
+            "SENDER".to_string(),
+            "HASH".to_string(),
+            "TYPE".to_string(),
+            "SELECTOR".to_string(),
+            "CREATED_ADDRESS".to_string(),
         ]
     }
 
@@ -90,6 +107,11 @@ impl DatalakeField for TransactionField {
             12 => Ok(TransactionField::MaxPriorityFeePerGas),
             13 => Ok(TransactionField::BlobVersionedHashes),
             14 => Ok(TransactionField::MaxFeePerBlobGas),
+            15 => Ok(TransactionField::Sender),
+            16 => Ok(TransactionField::Hash),
+            17 => Ok(TransactionField::Type),
+            18 => Ok(TransactionField::Selector),
+            19 => Ok(TransactionField::CreatedAddress),
             _ => bail!("Invalid transaction field index"),
         }
     }
@@ -111,52 +133,88 @@ impl DatalakeField for TransactionField {
             TransactionField::MaxPriorityFeePerGas => 12,
             TransactionField::BlobVersionedHashes => 13,
             TransactionField::MaxFeePerBlobGas => 14,
+            TransactionField::Sender => 15,
+            TransactionField::Hash => 16,
+            TransactionField::Type => 17,
+            TransactionField::Selector => 18,
+            TransactionField::CreatedAddress => 19,
         }
     }
 
-    fn decode_field_from_rlp(&self, rlp: &[u8]) -> U256 {
-        let raw_tx = ConsensusTx::rlp_decode(rlp).unwrap();
-        match self {
+    /// Decodes the field from an EIP-2718 encoded transaction.
+    ///
+    /// `CreatedAddress` is `0` for call transactions, every other field that is
+    /// absent from the transaction type is an error.
+    fn decode_field_from_rlp(&self, rlp: &[u8]) -> Result<U256> {
+        let raw_tx = ConsensusTx::rlp_decode(rlp).context("failed to decode transaction")?;
+        let value = match self {
             TransactionField::Nonce => U256::from(raw_tx.nonce()),
             TransactionField::GasPrice => {
-                U256::from(raw_tx.gas_price().expect("gas price does not exist"))
+                U256::from(raw_tx.gas_price().context("gas price does not exist")?)
             }
             TransactionField::GasLimit => U256::from(raw_tx.gas_limit()),
-            TransactionField::To => U256::from_str_radix(
-                &raw_tx.to().to().expect("to does not exist").to_string(),
-                16,
-            )
-            .unwrap(),
+            TransactionField::To => U256::from_be_slice(
+                raw_tx
+                    .to()
+                    .to()
+                    .context("to does not exist for contract creation")?
+                    .as_slice(),
+            ),
             TransactionField::Value => U256::from(raw_tx.value()),
             TransactionField::Input => U256::from_be_slice(raw_tx.input()),
             TransactionField::V => U256::from(raw_tx.v()),
             TransactionField::R => U256::from(raw_tx.r()),
             TransactionField::S => U256::from(raw_tx.s()),
             TransactionField::ChainId => {
-                U256::from(raw_tx.chain_id().expect("chain id does not exist"))
+                U256::from(raw_tx.chain_id().context("chain id does not exist")?)
             }
             // TODO:  string should be properly rlp encoded
-            TransactionField::AccessList => todo!("access list cannot parse into u256"),
+            TransactionField::AccessList => bail!("access list cannot parse into u256"),
             TransactionField::MaxFeePerGas => U256::from(
                 raw_tx
                     .max_fee_per_gas()
-                    .expect("max fee per gas does not exist"),
+                    .context("max fee per gas does not exist")?,
             ),
             TransactionField::MaxPriorityFeePerGas => U256::from(
                 raw_tx
                     .max_priority_fee_per_gas()
-                    .expect("max priority fee per gas does not exist"),
+                    .context("max priority fee per gas does not exist")?,
             ),
             TransactionField::BlobVersionedHashes => raw_tx
                 .blob_versioned_hashes()
-                .expect("blob versioned hashes does not exist")[0]
+                .and_then(|hashes| hashes.first().copied())
+                .context("blob versioned hashes does not exist")?
                 .into(),
             TransactionField::MaxFeePerBlobGas => U256::from(
                 raw_tx
                     .max_fee_per_blob_gas()
-                    .expect("max fee per blob gas does not exist"),
+                    .context("max fee per blob gas does not exist")?,
             ),
-        }
+            TransactionField::Sender => U256::from_be_slice(
+                raw_tx
+                    .sender()
+                    .context("sender cannot be recovered")?
+                    .as_slice(),
+            ),
+            TransactionField::Hash => (*raw_tx.0.tx_hash()).into(),
+            TransactionField::Type => U256::from(raw_tx.0.tx_type() as u8),
+            TransactionField::Selector => {
+                let input = raw_tx.input();
+                if input.len() < 4 {
+                    U256::ZERO
+                } else {
+                    U256::from_be_slice(&input[..4])
+                }
+            }
+            TransactionField::CreatedAddress => {
+                if raw_tx.to().is_call() {
+                    return Ok(U256::ZERO);
+                }
+                let sender = raw_tx.sender().context("sender cannot be recovered")?;
+                U256::from_be_slice(sender.create(raw_tx.nonce()).as_slice())
+            }
+        };
+        Ok(value)
     }
 }
 
@@ -180,6 +238,11 @@ impl FromStr for TransactionField {
             "MAX_PRIORITY_FEE_PER_GAS" => Ok(TransactionField::MaxPriorityFeePerGas),
             "BLOB_VERSIONED_HASHES" => Ok(TransactionField::BlobVersionedHashes),
             "MAX_FEE_PER_BLOB_GAS" => Ok(TransactionField::MaxFeePerBlobGas),
+            "SENDER" => Ok(TransactionField::Sender),
+            "HASH" => Ok(TransactionField::Hash),
+            "TYPE" => Ok(TransactionField::Type),
+            "SELECTOR" => Ok(TransactionField::Selector),
+            "CREATED_ADDRESS" => Ok(TransactionField::CreatedAddress),
             _ => bail!("Unknown transaction datalake field"),
         }
     }
@@ -203,6 +266,11 @@ impl Display for TransactionField {
             TransactionField::MaxPriorityFeePerGas => write!(f, "MAX_PRIORITY_FEE_PER_GAS"),
             TransactionField::BlobVersionedHashes => write!(f, "BLOB_VERSIONED_HASHES"),
             TransactionField::MaxFeePerBlobGas => write!(f, "MAX_FEE_PER_BLOB_GAS"),
+            TransactionField::Sender => write!(f, "SENDER"),
+            TransactionField::Hash => write!(f, "HASH"),
+            TransactionField::Type => write!(f, "TYPE"),
+            TransactionField::Selector => write!(f, "SELECTOR"),
+            TransactionField::CreatedAddress => write!(f, "CREATED_ADDRESS"),
         }
     }
 }
@@ -271,10 +339,11 @@ impl DatalakeField for TransactionReceiptField {
         }
     }
 
-    fn decode_field_from_rlp(&self, rlp: &[u8]) -> U256 {
-        let raw_tx_receipt = ConsensusTxReceipt::rlp_decode(rlp).unwrap();
+    fn decode_field_from_rlp(&self, rlp: &[u8]) -> Result<U256> {
+        let raw_tx_receipt =
+            ConsensusTxReceipt::rlp_decode(rlp).context("failed to decode transaction receipt")?;
 
-        match self {
+        let value = match self {
             TransactionReceiptField::Success => match raw_tx_receipt.status() {
                 Eip658Value::Eip658(bool) => U256::from(bool as u8),
                 Eip658Value::PostState(state) => state.into(),
@@ -285,6 +354,7 @@ impl DatalakeField for TransactionReceiptField {
             // TODO: string should be properly rlp encoded
             TransactionReceiptField::Logs => U256::from(raw_tx_receipt.logs().len()),
             TransactionReceiptField::Bloom => U256::from(raw_tx_receipt.bloom().len()),
-        }
+        };
+        Ok(value)
     }
 }
@@ -12,7 +12,7 @@ pub mod module;
 /// This structure is used to provide the task to the pre-processor
 #[derive(Clone, Debug)]
 pub enum TaskEnvelope {
-    DatalakeCompute(DatalakeCompute),
+    DatalakeCompute(Box<DatalakeCompute>),
     Module(ExtendedModule),
 }
 
@@ -40,7 +40,7 @@ impl TaskEnvelope {
             .into_iter()
             .filter_map(|task| {
                 if let TaskEnvelope::DatalakeCompute(datalake_task) = task {
-                    Some(datalake_task)
+                    Some(*datalake_task)
                 } else {
                     None
                 }
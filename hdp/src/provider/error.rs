@@ -1,5 +1,8 @@
+use alloy::primitives::{Address, BlockNumber, StorageKey, TxHash};
 use thiserror::Error;
 
+use crate::primitives::task::datalake::block_sampled::TimestampRange;
+
 use crate::provider::indexer::IndexerError;
 
 use super::evm::rpc::RpcProviderError;
@@ -33,4 +36,56 @@ pub enum ProviderError {
 
     #[error("Fetch key error: {0}")]
     FetchKeyError(String),
+
+    /// Error when the fetched code does not match the proven code hash
+    #[error("Code hash mismatch for {0} at block {1}")]
+    CodeHashMismatch(Address, BlockNumber),
+
+    /// Error when the random sampling seed is not the hash of the seed block
+    #[error("Sampling seed does not match the hash of block {0}")]
+    SamplingSeedMismatch(BlockNumber),
+
+    /// Error when no block has been produced at or after the timestamp yet
+    #[error("No block at or after timestamp {0}")]
+    TimestampOutOfRange(u64),
+
+    /// Error when no block is in the timestamp range
+    #[error("No block in timestamp range {0:?}")]
+    EmptyTimestampRange(TimestampRange),
+
+    /// Error when the number of transactions of the block does not match the resolved index range
+    #[error("Transaction count mismatch at block {0}: resolved: {1}, actual: {2}")]
+    TxCountMismatch(BlockNumber, u64, u64),
+
+    /// Error when the transaction at the resolved position does not have the requested hash
+    #[error("Transaction hash mismatch: {0}")]
+    TxHashMismatch(TxHash),
+
+    /// Error when a fetched object cannot be decoded
+    #[error("Decoding error: {0}")]
+    DecodeError(String),
+
+    /// Error when the transaction of a fetched receipt was not fetched
+    #[error("Transaction {1} of block {0} not found")]
+    MissingTransaction(BlockNumber, u64),
+
+    /// Error when the receipt of a sampled transaction was not fetched
+    #[error("Receipt of transaction {1} of block {0} not found")]
+    MissingReceipt(BlockNumber, u64),
+
+    /// Error when the header of a sampled block was not fetched
+    #[error("Header of block {0} not found")]
+    MissingHeader(BlockNumber),
+
+    /// Error when the account proof of a sampled block was not fetched
+    #[error("Account proof of {0} at block {1} not found")]
+    MissingAccountProof(Address, BlockNumber),
+
+    /// Error when the storage proof of a sampled block was not fetched
+    #[error("Storage proof of {0} at slot {1} at block {2} not found")]
+    MissingStorageProof(Address, StorageKey, BlockNumber),
+
+    /// Error when the resolved positions do not match the transaction hashes
+    #[error("Failed to resolve transaction positions: {0}")]
+    UnresolvedPositions(String),
 }
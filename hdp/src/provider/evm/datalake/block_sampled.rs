@@ -2,138 +2,413 @@ use crate::{
     primitives::{
         block::account::Account,
         processed_types::{
-            account::ProcessedAccount, header::ProcessedHeader, mpt::ProcessedMPTProof,
-            storage::ProcessedStorage,
+            account::ProcessedAccount, bytecode::ProcessedBytecode, header::ProcessedHeader,
+            mpt::ProcessedMPTProof, storage::ProcessedStorage,
+            transaction_count::ProcessedTransactionCount,
         },
         task::datalake::{
-            block_sampled::{BlockSampledCollection, BlockSampledDatalake},
+            block_sampled::{
+                BlockSampledCollection, BlockSampledDatalake, HeaderField,
+                MultiBlockSampledDatalake, RandomSampling,
+            },
             DatalakeField,
         },
     },
     provider::{error::ProviderError, evm::provider::EvmProvider, types::FetchedDatalake},
 };
-use std::collections::HashSet;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
-use alloy::primitives::{Bytes, U256};
+use alloy::{
+    primitives::{keccak256, Address, BlockNumber, Bytes, StorageKey, B256, U256},
+    rpc::types::EIP1186AccountProofResponse,
+};
 use anyhow::Result;
 
+/// Account proofs of the sampled blocks
+type AccountProofs = HashMap<BlockNumber, EIP1186AccountProofResponse>;
+
 impl EvmProvider {
+    /// Resolves the timestamp range of the datalake to blocks.
+    /// A timestamp resolves to the first block with a timestamp greater than or equal to it,
+    /// and the range ends at the latest block at the latest.
+    ///
+    /// Return:
+    /// - Datalake with the resolved blocks, along with the timestamp range
+    pub async fn resolve_timestamp_range(
+        &self,
+        datalake: &BlockSampledDatalake,
+    ) -> Result<BlockSampledDatalake, ProviderError> {
+        let timestamp_range = match &datalake.timestamp_range {
+            Some(timestamp_range) if !datalake.is_resolved() => *timestamp_range,
+            _ => return Ok(datalake.clone()),
+        };
+        let latest_block = self.rpc_provider.get_block_number().await?;
+        let latest_timestamp = self.rpc_provider.get_block_timestamp(latest_block).await?;
+        let timestamp_range = timestamp_range.resolve(latest_block);
+
+        let resolved = match timestamp_range.stepped_timestamps(latest_timestamp) {
+            Some(timestamps) => {
+                let mut block_list: Vec<BlockNumber> = vec![];
+                let mut from_block = 0;
+                for timestamp in timestamps {
+                    let block = self
+                        .get_first_block_at_or_after(timestamp, from_block, latest_block)
+                        .await?;
+                    // consecutive timestamps within a block resolve to the same block
+                    if block_list.last() != Some(&block) {
+                        block_list.push(block);
+                    }
+                    from_block = block;
+                }
+                BlockSampledDatalake::from_block_list(
+                    datalake.chain_id,
+                    block_list,
+                    datalake.sampled_property.clone(),
+                )
+                .map_err(|_| ProviderError::EmptyTimestampRange(timestamp_range))?
+            }
+            None => {
+                let block_range_start = self
+                    .get_first_block_at_or_after(timestamp_range.from_timestamp, 0, latest_block)
+                    .await?;
+                let block_range_end = if timestamp_range.to_timestamp >= latest_timestamp {
+                    latest_block
+                } else {
+                    // the end of the range is before the latest block, so the next timestamp is too
+                    self.get_first_block_at_or_after(
+                        timestamp_range.to_timestamp + 1,
+                        block_range_start,
+                        latest_block,
+                    )
+                    .await?
+                    .checked_sub(1)
+                    .ok_or(ProviderError::EmptyTimestampRange(timestamp_range))?
+                };
+                if block_range_end < block_range_start {
+                    return Err(ProviderError::EmptyTimestampRange(timestamp_range));
+                }
+                BlockSampledDatalake::new(
+                    datalake.chain_id,
+                    block_range_start,
+                    block_range_end,
+                    datalake.increment,
+                    datalake.sampled_property.clone(),
+                )
+            }
+        };
+
+        Ok(BlockSampledDatalake {
+            timestamp_range: Some(timestamp_range),
+            ..resolved
+        })
+    }
+
+    /// Binary search of the first block with a timestamp greater than or equal to the given one,
+    /// between the given blocks (inclusive).
+    async fn get_first_block_at_or_after(
+        &self,
+        timestamp: u64,
+        mut low: BlockNumber,
+        mut high: BlockNumber,
+    ) -> Result<BlockNumber, ProviderError> {
+        if self.rpc_provider.get_block_timestamp(high).await? < timestamp {
+            return Err(ProviderError::TimestampOutOfRange(timestamp));
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.rpc_provider.get_block_timestamp(mid).await? < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
     pub(crate) async fn fetch_block_sampled(
         &self,
         datalake: &BlockSampledDatalake,
     ) -> Result<FetchedDatalake, ProviderError> {
-        let mut aggregation_set: Vec<U256> = Vec::new();
+        let mut fetched = self
+            .fetch_multi_block_sampled(&MultiBlockSampledDatalake::from(datalake.clone()))
+            .await?;
+        Ok(fetched.remove(0))
+    }
 
-        let (mmr_metas, headers_proofs) = self
-            .get_range_of_header_proofs(
-                datalake.block_range_start,
-                datalake.block_range_end,
-                datalake.increment,
+    /// Fetches the properties of the datalake over its blocks.
+    /// Headers are fetched once, and account and storage proofs once per account and slot.
+    ///
+    /// Return:
+    /// - Fetched values and proofs of each property, in order
+    pub(crate) async fn fetch_multi_block_sampled(
+        &self,
+        datalake: &MultiBlockSampledDatalake,
+    ) -> Result<Vec<FetchedDatalake>, ProviderError> {
+        let blocks_datalake = &datalake.datalakes()[0];
+        let blocks = datalake.blocks();
+        let (mut mmr_metas, headers_proofs) = if blocks_datalake.is_full_range() {
+            self.get_range_of_header_proofs(
+                blocks_datalake.block_range_start,
+                blocks_datalake.block_range_end,
+                blocks_datalake.increment,
             )
-            .await?;
-        let mut headers: HashSet<ProcessedHeader> = HashSet::new();
-        let mut accounts: HashSet<ProcessedAccount> = HashSet::new();
-        let mut storages: HashSet<ProcessedStorage> = HashSet::new();
-        let block_range = (datalake.block_range_start..=datalake.block_range_end)
-            .step_by(datalake.increment as usize);
-
-        match &datalake.sampled_property {
-            BlockSampledCollection::Header(property) => {
-                for block in block_range {
-                    let fetched_block = headers_proofs.get(&block).unwrap();
-                    let value = property.decode_field_from_rlp(&Bytes::from(
-                        fetched_block.rlp_block_header.clone(),
-                    ));
-                    headers.insert(ProcessedHeader::new(
-                        fetched_block.rlp_block_header.clone(),
-                        fetched_block.element_index,
-                        fetched_block.siblings_hashes.clone(),
-                    ));
-                    aggregation_set.push(value);
-                }
+            .await?
+        } else {
+            self.get_header_proofs_of_blocks(blocks.clone()).await?
+        };
+        let mut shared_headers: HashSet<ProcessedHeader> = HashSet::new();
+        // headers of all sampled blocks are included, so the timestamps are proven for any property
+        let timestamps: Vec<u64> = blocks
+            .iter()
+            .map(|block| {
+                HeaderField::Timestamp
+                    .decode_field_from_rlp(&Bytes::from(
+                        headers_proofs
+                            .get(block)
+                            .ok_or(ProviderError::MissingHeader(*block))?
+                            .rlp_block_header
+                            .clone(),
+                    ))
+                    .map(|timestamp| timestamp.to::<u64>())
+                    .map_err(|e| ProviderError::DecodeError(e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        // seed of the random sampling is proven to be the hash of the seed block
+        if let Some(RandomSampling {
+            seed,
+            seed_block: Some(seed_block),
+            ..
+        }) = blocks_datalake.random_sampling
+        {
+            let (seed_mmr_metas, seed_header_proofs) =
+                self.get_header_proofs_of_blocks(vec![seed_block]).await?;
+            let fetched_block = seed_header_proofs
+                .get(&seed_block)
+                .ok_or(ProviderError::MissingHeader(seed_block))?;
+            let seed_header = ProcessedHeader::new(
+                fetched_block.rlp_block_header.clone(),
+                fetched_block.element_index,
+                fetched_block.siblings_hashes.clone(),
+            );
+            if keccak256(&seed_header.rlp) != seed {
+                return Err(ProviderError::SamplingSeedMismatch(seed_block));
             }
-            BlockSampledCollection::Account(address, property) => {
-                let accounts_and_proofs_result = self
-                    .get_range_of_account_proofs(
-                        datalake.block_range_start,
-                        datalake.block_range_end,
-                        datalake.increment,
-                        *address,
-                    )
-                    .await?;
+            shared_headers.insert(seed_header);
+            mmr_metas.extend(seed_mmr_metas);
+        }
 
-                let mut account_proofs: Vec<ProcessedMPTProof> = vec![];
+        // headers around the resolved blocks are proven, to check the resolution of the timestamps
+        if let Some(timestamp_range) = &blocks_datalake.timestamp_range {
+            let boundary_blocks = timestamp_range.boundary_blocks(
+                blocks_datalake.block_range_start,
+                blocks_datalake.block_range_end,
+                &blocks_datalake.block_list,
+            );
+            let (boundary_mmr_metas, boundary_header_proofs) =
+                self.get_header_proofs_of_blocks(boundary_blocks).await?;
+            shared_headers.extend(boundary_header_proofs.into_values().map(|fetched_block| {
+                ProcessedHeader::new(
+                    fetched_block.rlp_block_header,
+                    fetched_block.element_index,
+                    fetched_block.siblings_hashes,
+                )
+            }));
+            mmr_metas.extend(boundary_mmr_metas);
+        }
 
-                for block in block_range {
-                    let fetched_block = headers_proofs.get(&block).unwrap().clone();
-                    let account_proof = accounts_and_proofs_result.get(&block).unwrap().clone();
-                    let account = Account::from(&account_proof).rlp_encode();
+        let mut account_proofs_cache: HashMap<Address, AccountProofs> = HashMap::new();
+        let mut storage_proofs_cache: HashMap<(Address, StorageKey), AccountProofs> =
+            HashMap::new();
+        let mut fetched_properties = Vec::with_capacity(datalake.datalakes().len());
 
-                    let value = property.decode_field_from_rlp(&account);
-                    headers.insert(ProcessedHeader::new(
-                        fetched_block.rlp_block_header.clone(),
-                        fetched_block.element_index,
-                        fetched_block.siblings_hashes.clone(),
-                    ));
+        for sampled_property in datalake.sampled_properties() {
+            let mut aggregation_set: Vec<U256> = Vec::new();
+            let mut headers: HashSet<ProcessedHeader> = shared_headers.clone();
+            let mut accounts: HashSet<ProcessedAccount> = HashSet::new();
+            let mut storages: HashSet<ProcessedStorage> = HashSet::new();
+            let mut transaction_counts: HashSet<ProcessedTransactionCount> = HashSet::new();
+            let mut bytecodes: HashSet<ProcessedBytecode> = HashSet::new();
 
-                    let account_proof = ProcessedMPTProof {
-                        block_number: block,
-                        proof: account_proof.account_proof,
+            match sampled_property {
+                BlockSampledCollection::Header(property) => {
+                    for block in blocks.iter().copied() {
+                        let fetched_block = headers_proofs
+                            .get(&block)
+                            .ok_or(ProviderError::MissingHeader(block))?;
+                        let value = if *property == HeaderField::TxCount {
+                            // tx count is proven against the transactions trie of the block
+                            let tx_count = self.get_tx_count_proof_from_block(block).await?;
+                            let value = U256::from(tx_count.count);
+                            transaction_counts.insert(tx_count);
+                            value
+                        } else {
+                            property
+                                .decode_field_from_rlp(&Bytes::from(
+                                    fetched_block.rlp_block_header.clone(),
+                                ))
+                                .map_err(|e| ProviderError::DecodeError(e.to_string()))?
+                        };
+                        headers.insert(ProcessedHeader::new(
+                            fetched_block.rlp_block_header.clone(),
+                            fetched_block.element_index,
+                            fetched_block.siblings_hashes.clone(),
+                        ));
+                        aggregation_set.push(value);
+                    }
+                }
+                BlockSampledCollection::Account(address, property) => {
+                    let accounts_and_proofs_result = match account_proofs_cache.entry(*address) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(
+                            self.get_account_proofs_of_blocks(blocks.clone(), *address)
+                                .await?,
+                        ),
                     };
 
-                    account_proofs.push(account_proof);
-                    aggregation_set.push(value);
+                    let mut account_proofs: Vec<ProcessedMPTProof> = vec![];
+
+                    for block in blocks.iter().copied() {
+                        let fetched_block = headers_proofs
+                            .get(&block)
+                            .ok_or(ProviderError::MissingHeader(block))?;
+                        let account_proof = accounts_and_proofs_result
+                            .get(&block)
+                            .ok_or(ProviderError::MissingAccountProof(*address, block))?
+                            .clone();
+                        let account = Account::from(&account_proof).rlp_encode();
+
+                        let value = property
+                            .decode_field_from_rlp(&account)
+                            .map_err(|e| ProviderError::DecodeError(e.to_string()))?;
+                        headers.insert(ProcessedHeader::new(
+                            fetched_block.rlp_block_header.clone(),
+                            fetched_block.element_index,
+                            fetched_block.siblings_hashes.clone(),
+                        ));
+
+                        let account_proof = ProcessedMPTProof {
+                            block_number: block,
+                            proof: account_proof.account_proof,
+                        };
+
+                        account_proofs.push(account_proof);
+                        aggregation_set.push(value);
+                    }
+
+                    accounts.insert(ProcessedAccount::new(*address, account_proofs));
                 }
+                BlockSampledCollection::Storage(address, slot, slice) => {
+                    // packed values of the same slot share the storage proofs
+                    let storages_and_proofs_result =
+                        match storage_proofs_cache.entry((*address, *slot)) {
+                            Entry::Occupied(entry) => entry.into_mut(),
+                            Entry::Vacant(entry) => entry.insert(
+                                self.get_storage_proofs_of_blocks(blocks.clone(), *address, *slot)
+                                    .await?,
+                            ),
+                        };
 
-                accounts.insert(ProcessedAccount::new(*address, account_proofs));
-            }
-            BlockSampledCollection::Storage(address, slot) => {
-                let storages_and_proofs_result = self
-                    .get_range_of_storage_proofs(
-                        datalake.block_range_start,
-                        datalake.block_range_end,
-                        datalake.increment,
-                        *address,
-                        *slot,
-                    )
-                    .await?;
+                    let mut storage_proofs: Vec<ProcessedMPTProof> = vec![];
+                    let mut account_proofs: Vec<ProcessedMPTProof> = vec![];
 
-                let mut storage_proofs: Vec<ProcessedMPTProof> = vec![];
-                let mut account_proofs: Vec<ProcessedMPTProof> = vec![];
+                    for i in blocks.iter().copied() {
+                        let fetched_block = headers_proofs
+                            .get(&i)
+                            .ok_or(ProviderError::MissingHeader(i))?;
+                        let storage_proof = storages_and_proofs_result
+                            .get(&i)
+                            .ok_or(ProviderError::MissingStorageProof(*address, *slot, i))?
+                            .clone();
+                        let slot_proof = storage_proof
+                            .storage_proof
+                            .first()
+                            .ok_or(ProviderError::MissingStorageProof(*address, *slot, i))?;
 
-                for i in block_range {
-                    let fetched_block = headers_proofs.get(&i).unwrap().clone();
-                    let storage_proof = storages_and_proofs_result.get(&i).unwrap().clone();
+                        headers.insert(ProcessedHeader::new(
+                            fetched_block.rlp_block_header.clone(),
+                            fetched_block.element_index,
+                            fetched_block.siblings_hashes.clone(),
+                        ));
 
-                    headers.insert(ProcessedHeader::new(
-                        fetched_block.rlp_block_header.clone(),
-                        fetched_block.element_index,
-                        fetched_block.siblings_hashes.clone(),
-                    ));
+                        account_proofs.push(ProcessedMPTProof::new(i, storage_proof.account_proof));
 
-                    account_proofs.push(ProcessedMPTProof::new(i, storage_proof.account_proof));
+                        storage_proofs.push(ProcessedMPTProof::new(i, slot_proof.proof.clone()));
 
-                    storage_proofs.push(ProcessedMPTProof::new(
-                        i,
-                        storage_proof.storage_proof[0].proof.clone(),
-                    ));
+                        // packed value is extracted from the slot
+                        let value = slot_proof.value;
+                        aggregation_set.push(slice.map_or(value, |slice| slice.extract(value)));
+                    }
 
-                    aggregation_set.push(storage_proof.storage_proof[0].value);
+                    storages.insert(ProcessedStorage::new(*address, *slot, storage_proofs));
+                    accounts.insert(ProcessedAccount::new(*address, account_proofs));
                 }
+                BlockSampledCollection::Code(address, property) => {
+                    let accounts_and_proofs_result = match account_proofs_cache.entry(*address) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(
+                            self.get_account_proofs_of_blocks(blocks.clone(), *address)
+                                .await?,
+                        ),
+                    };
+
+                    let mut account_proofs: Vec<ProcessedMPTProof> = vec![];
+                    // code only changes on upgrades, so it is fetched once per code hash
+                    let mut codes: HashMap<B256, Bytes> = HashMap::new();
 
-                storages.insert(ProcessedStorage::new(*address, *slot, storage_proofs));
-                accounts.insert(ProcessedAccount::new(*address, account_proofs));
+                    for block in blocks.iter().copied() {
+                        let fetched_block = headers_proofs
+                            .get(&block)
+                            .ok_or(ProviderError::MissingHeader(block))?;
+                        let account_proof = accounts_and_proofs_result
+                            .get(&block)
+                            .ok_or(ProviderError::MissingAccountProof(*address, block))?
+                            .clone();
+                        let code_hash = account_proof.code_hash;
+
+                        let code = match codes.entry(code_hash) {
+                            Entry::Occupied(entry) => entry.into_mut(),
+                            Entry::Vacant(entry) => entry.insert(
+                                self.get_code_with_code_hash(block, *address, code_hash)
+                                    .await?,
+                            ),
+                        };
+                        let value = property.decode_field_from_code(code);
+
+                        headers.insert(ProcessedHeader::new(
+                            fetched_block.rlp_block_header.clone(),
+                            fetched_block.element_index,
+                            fetched_block.siblings_hashes.clone(),
+                        ));
+                        account_proofs
+                            .push(ProcessedMPTProof::new(block, account_proof.account_proof));
+                        aggregation_set.push(value);
+                    }
+
+                    accounts.insert(ProcessedAccount::new(*address, account_proofs));
+                    // empty code is implied by the code hash of the account
+                    bytecodes.extend(
+                        codes
+                            .into_values()
+                            .filter(|code| !code.is_empty())
+                            .map(ProcessedBytecode::new),
+                    );
+                }
             }
+
+            fetched_properties.push(FetchedDatalake {
+                values: aggregation_set,
+                timestamps: timestamps.clone(),
+                headers,
+                accounts,
+                storages,
+                bytecodes,
+                transaction_counts,
+                mmr_metas: mmr_metas.clone(),
+                ..Default::default()
+            });
         }
 
-        Ok(FetchedDatalake {
-            values: aggregation_set,
-            headers,
-            accounts,
-            storages,
-            transactions: HashSet::new(),
-            transaction_receipts: HashSet::new(),
-            mmr_metas,
-        })
+        Ok(fetched_properties)
     }
 }
@@ -1,21 +1,74 @@
 use crate::{
     primitives::{
+        block::header::MMRProofFromNewIndexer,
         processed_types::{
             header::ProcessedHeader, receipt::ProcessedReceipt, transaction::ProcessedTransaction,
+            transaction_count::ProcessedTransactionCount,
         },
         task::datalake::{
-            transactions::{TransactionsCollection, TransactionsInBlockDatalake},
+            transactions::{
+                IncludedTypes, TransactionFeeField, TransactionsByHashDatalake,
+                TransactionsCollection, TransactionsFilter, TransactionsInBlockDatalake,
+                TransactionsInRangeDatalake, TxPosition,
+            },
             DatalakeField,
         },
     },
-    provider::{error::ProviderError, evm::provider::EvmProvider, types::FetchedDatalake},
+    provider::{
+        error::ProviderError,
+        evm::provider::EvmProvider,
+        types::{FetchedDatalake, FetchedTransactionProof, FetchedTransactionReceiptProof},
+    },
 };
-use alloy::primitives::U256;
+use alloy::primitives::{keccak256, BlockNumber, Bytes, TxHash, TxIndex, U256};
 use anyhow::Result;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 impl EvmProvider {
+    /// Resolve the relative index range of the datalake against the number of transactions of the block
+    pub async fn resolve_tx_index_range(
+        &self,
+        datalake: &TransactionsInBlockDatalake,
+    ) -> Result<TransactionsInBlockDatalake, ProviderError> {
+        let index_range = match datalake.index_range {
+            Some(index_range) => index_range,
+            None => return Ok(datalake.clone()),
+        };
+        let tx_count = self
+            .get_tx_count_proof_from_block(datalake.target_block)
+            .await?;
+        Ok(datalake
+            .clone()
+            .with_index_range(index_range.resolve(tx_count.count)))
+    }
+
+    /// Locate the transactions with the given hashes, i.e. their block number and index
+    pub async fn get_tx_positions(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<TxPosition>, ProviderError> {
+        let mut positions = Vec::with_capacity(tx_hashes.len());
+        for tx_hash in tx_hashes {
+            let (block_number, tx_index) =
+                self.rpc_provider.get_transaction_position(*tx_hash).await?;
+            positions.push(TxPosition::new(block_number, tx_index));
+        }
+        Ok(positions)
+    }
+
+    /// Resolve the positions of the transactions of the datalake from their hashes
+    pub async fn resolve_tx_hashes(
+        &self,
+        datalake: &TransactionsByHashDatalake,
+    ) -> Result<TransactionsByHashDatalake, ProviderError> {
+        let positions = self.get_tx_positions(&datalake.tx_hashes).await?;
+        datalake
+            .clone()
+            .with_positions(positions)
+            .map_err(|e| ProviderError::UnresolvedPositions(e.to_string()))
+    }
+
     pub async fn fetch_transactions(
         &self,
         datalake: &TransactionsInBlockDatalake,
@@ -41,6 +94,22 @@ impl EvmProvider {
             fetched_block.siblings_hashes.clone(),
         ));
 
+        // resolved index range is proven with the number of transactions of the block
+        let mut transaction_counts: HashSet<ProcessedTransactionCount> = HashSet::new();
+        if let Some(resolved_tx_count) = datalake.index_range.and_then(|range| range.tx_count) {
+            let tx_count = self
+                .get_tx_count_proof_from_block(datalake.target_block)
+                .await?;
+            if tx_count.count != resolved_tx_count {
+                return Err(ProviderError::TxCountMismatch(
+                    datalake.target_block,
+                    resolved_tx_count,
+                    tx_count.count,
+                ));
+            }
+            transaction_counts.insert(tx_count);
+        }
+
         match &datalake.sampled_property {
             TransactionsCollection::Transactions(property) => {
                 for tx in self
@@ -59,13 +128,30 @@ impl EvmProvider {
                     ));
 
                     // depends on datalake.included_types filter the value to be included in the aggregation set
-                    if datalake.included_types.is_included(tx.tx_type) {
-                        let value = property.decode_field_from_rlp(&tx.encoded_transaction);
+                    if datalake.included_types.is_included(tx.tx_type)
+                        && matches_filter(&datalake.filter, &tx.encoded_transaction)?
+                    {
+                        let value = decode_field(property, &tx.encoded_transaction)?;
                         aggregation_set.push(value);
                     }
                 }
             }
             TransactionsCollection::TranasactionReceipts(property) => {
+                // filter is checked against the transaction of the receipt, which is proven as well
+                let filtered_txs = if datalake.filter.is_empty() {
+                    None
+                } else {
+                    Some(transactions_by_position(
+                        self.get_tx_with_proof_from_block(
+                            datalake.target_block,
+                            datalake.start_index,
+                            datalake.end_index,
+                            datalake.increment,
+                        )
+                        .await?,
+                    ))
+                };
+
                 for tx_receipt in self
                     .get_tx_receipt_with_proof_from_block(
                         datalake.target_block,
@@ -81,13 +167,364 @@ impl EvmProvider {
                         tx_receipt.receipt_proof,
                     ));
 
+                    let matches_filter = match &filtered_txs {
+                        Some(txs) => {
+                            let tx = txs
+                                .get(&(tx_receipt.block_number, tx_receipt.tx_index))
+                                .ok_or(ProviderError::MissingTransaction(
+                                    tx_receipt.block_number,
+                                    tx_receipt.tx_index,
+                                ))?;
+                            transactions.insert(ProcessedTransaction::new(
+                                tx.tx_index,
+                                tx.block_number,
+                                tx.transaction_proof.clone(),
+                            ));
+                            matches_filter(&datalake.filter, &tx.encoded_transaction)?
+                        }
+                        None => true,
+                    };
+
+                    // depends on datalake.included_types filter the value to be included in the aggregation set
+                    if datalake.included_types.is_included(tx_receipt.tx_type) && matches_filter {
+                        let value = decode_field(property, &tx_receipt.encoded_receipt)?;
+                        aggregation_set.push(value);
+                    }
+                }
+            }
+            TransactionsCollection::TransactionFees(property) => {
+                let txs = self
+                    .get_tx_with_proof_from_block(
+                        datalake.target_block,
+                        datalake.start_index,
+                        datalake.end_index,
+                        datalake.increment,
+                    )
+                    .await?;
+                // receipts of the previous transactions are needed for the gas used
+                let tx_receipts = self
+                    .get_tx_receipt_with_proof_from_block(
+                        datalake.target_block,
+                        datalake.start_index.saturating_sub(1),
+                        datalake.end_index,
+                        1,
+                    )
+                    .await?;
+                aggregation_set = sample_transaction_fees(
+                    property,
+                    &headers_proofs,
+                    txs,
+                    tx_receipts,
+                    &datalake.included_types,
+                    &datalake.filter,
+                    &mut transactions,
+                    &mut transaction_receipts,
+                )?;
+            }
+        }
+
+        Ok(FetchedDatalake {
+            values: aggregation_set,
+            headers,
+            accounts: HashSet::new(),
+            storages: HashSet::new(),
+            transactions,
+            transaction_receipts,
+            bytecodes: HashSet::new(),
+            transaction_counts,
+            mmr_metas,
+            ..Default::default()
+        })
+    }
+
+    pub async fn fetch_transactions_in_range(
+        &self,
+        datalake: &TransactionsInRangeDatalake,
+    ) -> Result<FetchedDatalake, ProviderError> {
+        let mut aggregation_set: Vec<U256> = Vec::new();
+
+        let (mmr_metas, headers_proofs) = self
+            .get_range_of_header_proofs(
+                datalake.block_range_start,
+                datalake.block_range_end,
+                datalake.block_increment,
+            )
+            .await?;
+
+        let mut headers: HashSet<ProcessedHeader> = HashSet::new();
+        let mut transactions: HashSet<ProcessedTransaction> = HashSet::new();
+        let mut transaction_receipts: HashSet<ProcessedReceipt> = HashSet::new();
+
+        for block in datalake.blocks() {
+            let fetched_block = headers_proofs
+                .get(&block)
+                .ok_or(ProviderError::MissingHeader(block))?;
+            headers.insert(ProcessedHeader::new(
+                fetched_block.rlp_block_header.clone(),
+                fetched_block.element_index,
+                fetched_block.siblings_hashes.clone(),
+            ));
+        }
+
+        match &datalake.sampled_property {
+            TransactionsCollection::Transactions(property) => {
+                for tx in self
+                    .get_tx_with_proof_from_block_range(
+                        datalake.block_range_start,
+                        datalake.block_range_end,
+                        datalake.block_increment,
+                        datalake.start_index,
+                        datalake.end_index,
+                        datalake.increment,
+                    )
+                    .await?
+                {
+                    transactions.insert(ProcessedTransaction::new(
+                        tx.tx_index,
+                        tx.block_number,
+                        tx.transaction_proof,
+                    ));
+
+                    // depends on datalake.included_types filter the value to be included in the aggregation set
+                    if datalake.included_types.is_included(tx.tx_type)
+                        && matches_filter(&datalake.filter, &tx.encoded_transaction)?
+                    {
+                        let value = decode_field(property, &tx.encoded_transaction)?;
+                        aggregation_set.push(value);
+                    }
+                }
+            }
+            TransactionsCollection::TranasactionReceipts(property) => {
+                // filter is checked against the transaction of the receipt, which is proven as well
+                let filtered_txs = if datalake.filter.is_empty() {
+                    None
+                } else {
+                    Some(transactions_by_position(
+                        self.get_tx_with_proof_from_block_range(
+                            datalake.block_range_start,
+                            datalake.block_range_end,
+                            datalake.block_increment,
+                            datalake.start_index,
+                            datalake.end_index,
+                            datalake.increment,
+                        )
+                        .await?,
+                    ))
+                };
+
+                for tx_receipt in self
+                    .get_tx_receipt_with_proof_from_block_range(
+                        datalake.block_range_start,
+                        datalake.block_range_end,
+                        datalake.block_increment,
+                        datalake.start_index,
+                        datalake.end_index,
+                        datalake.increment,
+                    )
+                    .await?
+                {
+                    transaction_receipts.insert(ProcessedReceipt::new(
+                        tx_receipt.tx_index,
+                        tx_receipt.block_number,
+                        tx_receipt.receipt_proof,
+                    ));
+
+                    let matches_filter = match &filtered_txs {
+                        Some(txs) => {
+                            let tx = txs
+                                .get(&(tx_receipt.block_number, tx_receipt.tx_index))
+                                .ok_or(ProviderError::MissingTransaction(
+                                    tx_receipt.block_number,
+                                    tx_receipt.tx_index,
+                                ))?;
+                            transactions.insert(ProcessedTransaction::new(
+                                tx.tx_index,
+                                tx.block_number,
+                                tx.transaction_proof.clone(),
+                            ));
+                            matches_filter(&datalake.filter, &tx.encoded_transaction)?
+                        }
+                        None => true,
+                    };
+
                     // depends on datalake.included_types filter the value to be included in the aggregation set
-                    if datalake.included_types.is_included(tx_receipt.tx_type) {
-                        let value = property.decode_field_from_rlp(&tx_receipt.encoded_receipt);
+                    if datalake.included_types.is_included(tx_receipt.tx_type) && matches_filter {
+                        let value = decode_field(property, &tx_receipt.encoded_receipt)?;
                         aggregation_set.push(value);
                     }
                 }
             }
+            TransactionsCollection::TransactionFees(property) => {
+                let txs = self
+                    .get_tx_with_proof_from_block_range(
+                        datalake.block_range_start,
+                        datalake.block_range_end,
+                        datalake.block_increment,
+                        datalake.start_index,
+                        datalake.end_index,
+                        datalake.increment,
+                    )
+                    .await?;
+                // receipts of the previous transactions are needed for the gas used
+                let tx_receipts = self
+                    .get_tx_receipt_with_proof_from_block_range(
+                        datalake.block_range_start,
+                        datalake.block_range_end,
+                        datalake.block_increment,
+                        datalake.start_index.saturating_sub(1),
+                        datalake.end_index,
+                        1,
+                    )
+                    .await?;
+                aggregation_set = sample_transaction_fees(
+                    property,
+                    &headers_proofs,
+                    txs,
+                    tx_receipts,
+                    &datalake.included_types,
+                    &datalake.filter,
+                    &mut transactions,
+                    &mut transaction_receipts,
+                )?;
+            }
+        }
+
+        Ok(FetchedDatalake {
+            values: aggregation_set,
+            headers,
+            accounts: HashSet::new(),
+            storages: HashSet::new(),
+            transactions,
+            transaction_receipts,
+            bytecodes: HashSet::new(),
+            mmr_metas,
+            ..Default::default()
+        })
+    }
+
+    pub async fn fetch_transactions_by_hash(
+        &self,
+        datalake: &TransactionsByHashDatalake,
+    ) -> Result<FetchedDatalake, ProviderError> {
+        let mut aggregation_set: Vec<U256> = Vec::new();
+
+        let (mmr_metas, headers_proofs) =
+            self.get_header_proofs_of_blocks(datalake.blocks()).await?;
+
+        let mut headers: HashSet<ProcessedHeader> = HashSet::new();
+        let mut transactions: HashSet<ProcessedTransaction> = HashSet::new();
+        let mut transaction_receipts: HashSet<ProcessedReceipt> = HashSet::new();
+
+        for block in datalake.blocks() {
+            let fetched_block = headers_proofs
+                .get(&block)
+                .ok_or(ProviderError::MissingHeader(block))?;
+            headers.insert(ProcessedHeader::new(
+                fetched_block.rlp_block_header.clone(),
+                fetched_block.element_index,
+                fetched_block.siblings_hashes.clone(),
+            ));
+        }
+
+        // positions are grouped by block, so the tries of each block are built once
+        let mut tx_indexes: BTreeMap<BlockNumber, BTreeSet<TxIndex>> = BTreeMap::new();
+        let mut receipt_indexes: BTreeMap<BlockNumber, BTreeSet<TxIndex>> = BTreeMap::new();
+        for position in &datalake.positions {
+            tx_indexes
+                .entry(position.block_number)
+                .or_default()
+                .insert(position.tx_index);
+            let receipts = receipt_indexes.entry(position.block_number).or_default();
+            match &datalake.sampled_property {
+                TransactionsCollection::Transactions(_) => {}
+                TransactionsCollection::TranasactionReceipts(_) => {
+                    receipts.insert(position.tx_index);
+                }
+                TransactionsCollection::TransactionFees(property) => {
+                    if property.needs_previous_receipt() {
+                        receipts.extend(position.tx_index.checked_sub(1));
+                    }
+                    receipts.insert(position.tx_index);
+                }
+            }
+        }
+
+        let mut txs = HashMap::new();
+        for (block, indexes) in tx_indexes {
+            txs.extend(transactions_by_position(
+                self.get_tx_with_proof_at_indexes(block, indexes).await?,
+            ));
+        }
+        let mut tx_receipts = vec![];
+        for (block, indexes) in receipt_indexes {
+            if !indexes.is_empty() {
+                tx_receipts.extend(
+                    self.get_tx_receipt_with_proof_at_indexes(block, indexes)
+                        .await?,
+                );
+            }
+        }
+
+        // the transactions are always proven, as they bind the positions to the hashes
+        let mut sampled_txs = Vec::with_capacity(datalake.positions.len());
+        for (tx_hash, position) in datalake.tx_hashes.iter().zip(&datalake.positions) {
+            let tx = txs.get(&(position.block_number, position.tx_index)).ok_or(
+                ProviderError::MissingTransaction(position.block_number, position.tx_index),
+            )?;
+            if keccak256(&tx.encoded_transaction) != *tx_hash {
+                return Err(ProviderError::TxHashMismatch(*tx_hash));
+            }
+            sampled_txs.push(tx.clone());
+        }
+
+        match &datalake.sampled_property {
+            TransactionsCollection::Transactions(property) => {
+                for tx in sampled_txs {
+                    aggregation_set.push(decode_field(property, &tx.encoded_transaction)?);
+                    transactions.insert(ProcessedTransaction::new(
+                        tx.tx_index,
+                        tx.block_number,
+                        tx.transaction_proof,
+                    ));
+                }
+            }
+            TransactionsCollection::TranasactionReceipts(property) => {
+                let tx_receipts: HashMap<(BlockNumber, TxIndex), FetchedTransactionReceiptProof> =
+                    tx_receipts
+                        .into_iter()
+                        .map(|tx_receipt| {
+                            ((tx_receipt.block_number, tx_receipt.tx_index), tx_receipt)
+                        })
+                        .collect();
+                for tx in sampled_txs {
+                    let tx_receipt = tx_receipts.get(&(tx.block_number, tx.tx_index)).ok_or(
+                        ProviderError::MissingTransaction(tx.block_number, tx.tx_index),
+                    )?;
+                    aggregation_set.push(decode_field(property, &tx_receipt.encoded_receipt)?);
+                    transactions.insert(ProcessedTransaction::new(
+                        tx.tx_index,
+                        tx.block_number,
+                        tx.transaction_proof,
+                    ));
+                    transaction_receipts.insert(ProcessedReceipt::new(
+                        tx_receipt.tx_index,
+                        tx_receipt.block_number,
+                        tx_receipt.receipt_proof.clone(),
+                    ));
+                }
+            }
+            TransactionsCollection::TransactionFees(property) => {
+                aggregation_set = sample_transaction_fees(
+                    property,
+                    &headers_proofs,
+                    sampled_txs,
+                    tx_receipts,
+                    &IncludedTypes::from(&[1, 1, 1, 1]),
+                    &TransactionsFilter::default(),
+                    &mut transactions,
+                    &mut transaction_receipts,
+                )?;
+            }
         }
 
         Ok(FetchedDatalake {
@@ -97,7 +534,102 @@ impl EvmProvider {
             storages: HashSet::new(),
             transactions,
             transaction_receipts,
+            bytecodes: HashSet::new(),
             mmr_metas,
+            ..Default::default()
         })
     }
 }
+
+/// Derive the fee fields of the sampled transactions.
+/// Receipts are expected to contain the receipt of the transaction preceding each sampled transaction.
+#[allow(clippy::too_many_arguments)]
+fn sample_transaction_fees(
+    property: &TransactionFeeField,
+    headers_proofs: &HashMap<BlockNumber, MMRProofFromNewIndexer>,
+    txs: Vec<FetchedTransactionProof>,
+    tx_receipts: Vec<FetchedTransactionReceiptProof>,
+    included_types: &IncludedTypes,
+    filter: &TransactionsFilter,
+    transactions: &mut HashSet<ProcessedTransaction>,
+    transaction_receipts: &mut HashSet<ProcessedReceipt>,
+) -> Result<Vec<U256>, ProviderError> {
+    let mut aggregation_set: Vec<U256> = Vec::new();
+    let tx_receipts: HashMap<(BlockNumber, TxIndex), FetchedTransactionReceiptProof> = tx_receipts
+        .into_iter()
+        .map(|tx_receipt| ((tx_receipt.block_number, tx_receipt.tx_index), tx_receipt))
+        .collect();
+
+    for tx in txs {
+        let receipt_at = |tx_index: TxIndex| {
+            tx_receipts
+                .get(&(tx.block_number, tx_index))
+                .ok_or(ProviderError::MissingReceipt(tx.block_number, tx_index))
+        };
+        let tx_receipt = receipt_at(tx.tx_index)?;
+        let previous_receipt = if property.needs_previous_receipt() && tx.tx_index > 0 {
+            Some(receipt_at(tx.tx_index - 1)?)
+        } else {
+            None
+        };
+
+        transactions.insert(ProcessedTransaction::new(
+            tx.tx_index,
+            tx.block_number,
+            tx.transaction_proof,
+        ));
+        for receipt in std::iter::once(tx_receipt).chain(previous_receipt) {
+            transaction_receipts.insert(ProcessedReceipt::new(
+                receipt.tx_index,
+                receipt.block_number,
+                receipt.receipt_proof.clone(),
+            ));
+        }
+
+        // depends on datalake.included_types filter the value to be included in the aggregation set
+        if included_types.is_included(tx.tx_type)
+            && matches_filter(filter, &tx.encoded_transaction)?
+        {
+            let header: Bytes = headers_proofs
+                .get(&tx.block_number)
+                .ok_or(ProviderError::MissingHeader(tx.block_number))?
+                .rlp_block_header
+                .clone()
+                .into();
+            let value = property
+                .decode_field_from_rlp(
+                    &header,
+                    &tx.encoded_transaction,
+                    &tx_receipt.encoded_receipt,
+                    previous_receipt.map(|receipt| receipt.encoded_receipt.as_slice()),
+                )
+                .map_err(|e| ProviderError::DecodeError(e.to_string()))?;
+            aggregation_set.push(value);
+        }
+    }
+
+    Ok(aggregation_set)
+}
+
+/// Check the filter against the rlp encoded transaction
+fn matches_filter(filter: &TransactionsFilter, encoded_tx: &[u8]) -> Result<bool, ProviderError> {
+    filter
+        .matches(encoded_tx)
+        .map_err(|e| ProviderError::DecodeError(e.to_string()))
+}
+
+/// Decode the sampled property from the rlp encoded transaction or receipt
+fn decode_field(property: &impl DatalakeField, encoded: &[u8]) -> Result<U256, ProviderError> {
+    property
+        .decode_field_from_rlp(encoded)
+        .map_err(|e| ProviderError::DecodeError(e.to_string()))
+}
+
+/// Index the transactions by their position, to find the transaction of each receipt
+fn transactions_by_position(
+    txs: Vec<FetchedTransactionProof>,
+) -> HashMap<(BlockNumber, TxIndex), FetchedTransactionProof> {
+    txs.into_iter()
+        .map(|tx| ((tx.block_number, tx.tx_index), tx))
+        .collect()
+}
@@ -59,6 +59,8 @@ impl EvmProvider {
             storages: storages.into_iter().collect(),
             transactions,
             transaction_receipts,
+            bytecodes: vec![],
+            transaction_counts: vec![],
         })
     }
 
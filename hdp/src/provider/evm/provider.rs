@@ -1,16 +1,24 @@
 use crate::{
     primitives::{
-        block::header::MMRProofFromNewIndexer, processed_types::mmr::MMRMeta,
+        block::header::MMRProofFromNewIndexer,
+        processed_types::{
+            mmr::MMRMeta, transaction::ProcessedTransaction,
+            transaction_count::ProcessedTransactionCount,
+        },
         task::datalake::envelope::DatalakeEnvelope,
     },
     provider::{
         config::ProviderConfig,
         error::ProviderError,
-        traits::{AsyncResult, FetchProofsFromKeysResult, FetchProofsResult, ProofProvider},
+        traits::{
+            AsyncResult, FetchMultiProofsResult, FetchProofsFromKeysResult, FetchProofsResult,
+            ProofProvider,
+        },
     },
 };
 use alloy::{
-    primitives::{Address, BlockNumber, Bytes, StorageKey, TxIndex},
+    consensus::constants::{EMPTY_TRANSACTIONS, KECCAK_EMPTY},
+    primitives::{keccak256, Address, BlockNumber, Bytes, StorageKey, TxIndex, B256},
     rpc::types::EIP1186AccountProofResponse,
     transports::{RpcError, TransportErrorKind},
 };
@@ -124,6 +132,56 @@ impl EvmProvider {
         }
     }
 
+    /// Fetches the header proofs for the given blocks.
+    /// Unlike [`Self::get_range_of_header_proofs`], only the given blocks are returned,
+    /// so the blocks can be sparse.
+    ///
+    /// Return:
+    /// - MMR meta
+    /// - Header proofs mapped by block number
+    pub async fn get_header_proofs_of_blocks(
+        &self,
+        blocks: Vec<BlockNumber>,
+    ) -> HeaderProofsResult {
+        let start_fetch = Instant::now();
+
+        let target_blocks: HashSet<BlockNumber> = blocks.iter().copied().collect();
+        let target_blocks_batch = self._chunk_vec_blocks_for_indexer(blocks);
+
+        let mut fetched_headers_proofs_with_blocks_map = HashMap::new();
+        let mut mmrs = HashSet::new();
+
+        for target_blocks_chunk in target_blocks_batch {
+            let (start_block, end_block) = (
+                target_blocks_chunk[0],
+                target_blocks_chunk[target_blocks_chunk.len() - 1],
+            );
+
+            let indexer_response = self
+                .header_provider
+                .get_headers_proof(start_block, end_block)
+                .await?;
+
+            fetched_headers_proofs_with_blocks_map.extend(
+                indexer_response
+                    .headers
+                    .into_iter()
+                    .filter(|(block_number, _)| target_blocks.contains(block_number)),
+            );
+            let fetched_mmr = indexer_response.mmr_meta;
+            let mmr_meta = MMRMeta::from_indexer(fetched_mmr, self.header_provider.chain_id);
+            mmrs.insert(mmr_meta);
+        }
+
+        let duration = start_fetch.elapsed();
+        info!("time taken (Headers Proofs Fetch): {:?}", duration);
+        if !mmrs.is_empty() {
+            Ok((mmrs, fetched_headers_proofs_with_blocks_map))
+        } else {
+            Err(ProviderError::MmrNotFound)
+        }
+    }
+
     /// Fetches the account proofs for the given block range.
     /// The account proofs are fetched from the RPC provider.
     ///
@@ -135,11 +193,26 @@ impl EvmProvider {
         to_block: BlockNumber,
         increment: u64,
         address: Address,
+    ) -> AccountProofsResult {
+        let blocks = (from_block..=to_block)
+            .step_by(increment as usize)
+            .collect();
+        self.get_account_proofs_of_blocks(blocks, address).await
+    }
+
+    /// Fetches the account proofs for the given blocks.
+    /// The account proofs are fetched from the RPC provider.
+    ///
+    /// Return:
+    /// - Account proofs mapped by block number
+    pub async fn get_account_proofs_of_blocks(
+        &self,
+        blocks: Vec<BlockNumber>,
+        address: Address,
     ) -> AccountProofsResult {
         let start_fetch = Instant::now();
 
-        let target_blocks_batch: Vec<Vec<BlockNumber>> =
-            self._chunk_block_range(from_block, to_block, increment);
+        let target_blocks_batch: Vec<Vec<BlockNumber>> = self._chunk_vec_blocks_for_mpt(blocks);
 
         let mut fetched_accounts_proofs_with_blocks_map = HashMap::new();
         for target_blocks in target_blocks_batch {
@@ -168,11 +241,28 @@ impl EvmProvider {
         increment: u64,
         address: Address,
         storage_slot: StorageKey,
+    ) -> StorageProofsResult {
+        let blocks = (from_block..=to_block)
+            .step_by(increment as usize)
+            .collect();
+        self.get_storage_proofs_of_blocks(blocks, address, storage_slot)
+            .await
+    }
+
+    /// Fetches the storage proofs for the given blocks.
+    /// The storage proofs are fetched from the RPC provider.
+    ///
+    /// Return:
+    /// - Storage proofs mapped by block number
+    pub async fn get_storage_proofs_of_blocks(
+        &self,
+        blocks: Vec<BlockNumber>,
+        address: Address,
+        storage_slot: StorageKey,
     ) -> StorageProofsResult {
         let start_fetch = Instant::now();
 
-        let target_blocks_batch: Vec<Vec<BlockNumber>> =
-            self._chunk_block_range(from_block, to_block, increment);
+        let target_blocks_batch: Vec<Vec<BlockNumber>> = self._chunk_vec_blocks_for_mpt(blocks);
 
         let mut processed_accounts = HashMap::new();
         for target_blocks in target_blocks_batch {
@@ -189,6 +279,28 @@ impl EvmProvider {
         Ok(processed_accounts)
     }
 
+    /// Fetches the code of the account at the given block, and checks it against the code hash.
+    /// Accounts without code are resolved without a request.
+    ///
+    /// Return:
+    /// - Code of the account
+    pub async fn get_code_with_code_hash(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        code_hash: B256,
+    ) -> Result<Bytes, ProviderError> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytes::new());
+        }
+
+        let code = self.rpc_provider.get_code(block_number, address).await?;
+        if keccak256(&code) != code_hash {
+            return Err(ProviderError::CodeHashMismatch(address, block_number));
+        }
+        Ok(code)
+    }
+
     /// Fetches the encoded transaction with proof from the MPT trie for the given block number.
     /// The transaction is fetched from the MPT trie and the proof is generated from the MPT trie.
     ///
@@ -203,7 +315,188 @@ impl EvmProvider {
     ) -> TxProofsResult {
         let start_fetch = Instant::now();
 
+        let mut tx_trie_provider = self.build_tx_trie(target_block).await?;
+        let fetched_transaction_proofs = Self::get_tx_proofs_from_trie(
+            &mut tx_trie_provider,
+            target_block,
+            start_index,
+            end_index,
+            incremental,
+        )?;
+
+        let duration = start_fetch.elapsed();
+        info!("time taken (Transactions Proofs Fetch): {:?}", duration);
+
+        Ok(fetched_transaction_proofs)
+    }
+
+    /// Fetches the encoded transactions with proof from the MPT trie for every block in the given range.
+    /// In each block, the end index is bounded by the number of transactions in the block.
+    ///
+    /// Return:
+    /// - Transaction proofs of all the blocks
+    pub async fn get_tx_with_proof_from_block_range(
+        &self,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+        block_increment: u64,
+        start_index: TxIndex,
+        end_index: TxIndex,
+        incremental: u64,
+    ) -> TxProofsResult {
+        let start_fetch = Instant::now();
+
+        let mut fetched_transaction_proofs = vec![];
+        for target_block in (from_block..=to_block).step_by(block_increment as usize) {
+            let mut tx_trie_provider = self.build_tx_trie(target_block).await?;
+            let tx_length = tx_trie_provider.get_elements()?.len() as u64;
+            fetched_transaction_proofs.extend(Self::get_tx_proofs_from_trie(
+                &mut tx_trie_provider,
+                target_block,
+                start_index,
+                end_index.min(tx_length),
+                incremental,
+            )?);
+        }
+
+        let duration = start_fetch.elapsed();
+        info!("time taken (Transactions Proofs Fetch): {:?}", duration);
+
+        Ok(fetched_transaction_proofs)
+    }
+
+    /// Fetches the transactions with proof at the given indexes of the block, building the trie once.
+    ///
+    /// Return:
+    /// - Transaction proofs, in the order of the indexes
+    pub async fn get_tx_with_proof_at_indexes(
+        &self,
+        target_block: BlockNumber,
+        tx_indexes: impl IntoIterator<Item = TxIndex>,
+    ) -> TxProofsResult {
+        let mut tx_trie_provider = self.build_tx_trie(target_block).await?;
         let mut fetched_transaction_proofs = vec![];
+        for tx_index in tx_indexes {
+            fetched_transaction_proofs.extend(Self::get_tx_proofs_from_trie(
+                &mut tx_trie_provider,
+                target_block,
+                tx_index,
+                tx_index + 1,
+                1,
+            )?);
+        }
+        Ok(fetched_transaction_proofs)
+    }
+
+    /// Fetches the transaction receipts with proof at the given indexes of the block, building the trie once.
+    ///
+    /// Return:
+    /// - Transaction receipts proofs, in the order of the indexes
+    pub async fn get_tx_receipt_with_proof_at_indexes(
+        &self,
+        target_block: BlockNumber,
+        tx_indexes: impl IntoIterator<Item = TxIndex>,
+    ) -> TxReceiptProofsResult {
+        let mut tx_receipt_trie_provider = self.build_tx_receipt_trie(target_block).await?;
+        let mut fetched_transaction_receipts_proofs = vec![];
+        for tx_index in tx_indexes {
+            fetched_transaction_receipts_proofs.extend(Self::get_tx_receipt_proofs_from_trie(
+                &mut tx_receipt_trie_provider,
+                target_block,
+                tx_index,
+                tx_index + 1,
+                1,
+            )?);
+        }
+        Ok(fetched_transaction_receipts_proofs)
+    }
+
+    /// Fetches the transaction receipts with proof from the MPT trie for the given block number.
+    /// The transaction receipts are fetched from the MPT trie and the proof is generated from the MPT trie.
+    ///
+    /// Return:
+    /// - Transaction receipts proofs mapped by block number
+    pub async fn get_tx_receipt_with_proof_from_block(
+        &self,
+        target_block: BlockNumber,
+        start_index: TxIndex,
+        end_index: TxIndex,
+        incremental: u64,
+    ) -> TxReceiptProofsResult {
+        let start_fetch = Instant::now();
+
+        let mut tx_receipt_trie_provider = self.build_tx_receipt_trie(target_block).await?;
+        let fetched_transaction_receipts_proofs = Self::get_tx_receipt_proofs_from_trie(
+            &mut tx_receipt_trie_provider,
+            target_block,
+            start_index,
+            end_index,
+            incremental,
+        )?;
+
+        let duration = start_fetch.elapsed();
+        info!(
+            "time taken (Transaction Receipts Proofs Fetch): {:?}",
+            duration
+        );
+
+        Ok(fetched_transaction_receipts_proofs)
+    }
+
+    /// Fetches the transaction receipts with proof from the MPT trie for every block in the given range.
+    /// In each block, the end index is bounded by the number of transactions in the block.
+    ///
+    /// Return:
+    /// - Transaction receipts proofs of all the blocks
+    pub async fn get_tx_receipt_with_proof_from_block_range(
+        &self,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+        block_increment: u64,
+        start_index: TxIndex,
+        end_index: TxIndex,
+        incremental: u64,
+    ) -> TxReceiptProofsResult {
+        let start_fetch = Instant::now();
+
+        let mut fetched_transaction_receipts_proofs = vec![];
+        for target_block in (from_block..=to_block).step_by(block_increment as usize) {
+            let mut tx_receipt_trie_provider = self.build_tx_receipt_trie(target_block).await?;
+            let tx_receipt_length = tx_receipt_trie_provider.get_elements()?.len() as u64;
+            fetched_transaction_receipts_proofs.extend(Self::get_tx_receipt_proofs_from_trie(
+                &mut tx_receipt_trie_provider,
+                target_block,
+                start_index,
+                end_index.min(tx_receipt_length),
+                incremental,
+            )?);
+        }
+
+        let duration = start_fetch.elapsed();
+        info!(
+            "time taken (Transaction Receipts Proofs Fetch): {:?}",
+            duration
+        );
+
+        Ok(fetched_transaction_receipts_proofs)
+    }
+
+    /// Fetches the number of transactions in the given block, along with its proof.
+    ///
+    /// Return:
+    /// - Proof of the number of transactions, see [`ProcessedTransactionCount`]
+    pub async fn get_tx_count_proof_from_block(
+        &self,
+        target_block: BlockNumber,
+    ) -> Result<ProcessedTransactionCount, ProviderError> {
+        let mut tx_trie_provider = self.build_tx_trie(target_block).await?;
+        Self::get_tx_count_proof_from_trie(&mut tx_trie_provider, target_block)
+    }
+
+    async fn build_tx_trie(
+        &self,
+        target_block: BlockNumber,
+    ) -> Result<TxsMptHandler, ProviderError> {
         let mut tx_trie_provider = TxsMptHandler::new(self.tx_provider_url.clone()).unwrap();
 
         loop {
@@ -224,6 +517,92 @@ impl EvmProvider {
             }
         }
 
+        Ok(tx_trie_provider)
+    }
+
+    async fn build_tx_receipt_trie(
+        &self,
+        target_block: BlockNumber,
+    ) -> Result<TxReceiptsMptHandler, ProviderError> {
+        let mut tx_receipt_trie_provider = TxReceiptsMptHandler::new(self.tx_provider_url.clone())?;
+
+        loop {
+            let trie_response = tx_receipt_trie_provider
+                .build_tx_receipts_tree_from_block(target_block)
+                .await;
+
+            match trie_response {
+                Ok(_) => break,
+                Err(EthTrieError::RPC(RpcError::Transport(TransportErrorKind::HttpError(
+                    http_error,
+                )))) if http_error.status == 429 => {
+                    // retry if 429 error
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+                Err(e) => return Err(ProviderError::EthTrieError(e)),
+            }
+        }
+
+        Ok(tx_receipt_trie_provider)
+    }
+
+    /// Proves the number of transactions `n` of the trie with the inclusion proof of the
+    /// transaction at index `n - 1` and the non-inclusion proof of the index `n`.
+    /// The trie of an empty block must have the empty root, the proofs are then empty.
+    fn get_tx_count_proof_from_trie(
+        tx_trie_provider: &mut TxsMptHandler,
+        target_block: BlockNumber,
+    ) -> Result<ProcessedTransactionCount, ProviderError> {
+        let tx_count = tx_trie_provider.get_elements()?.len() as u64;
+        let transactions_root = tx_trie_provider.get_root()?;
+
+        let last_transaction = match tx_count.checked_sub(1) {
+            Some(last_index) => Some(ProcessedTransaction::new(
+                last_index,
+                target_block,
+                Self::get_tx_trie_proof(tx_trie_provider, last_index)?,
+            )),
+            None if transactions_root == EMPTY_TRANSACTIONS => None,
+            None => {
+                return Err(ProviderError::EthTrieError(EthTrieError::UnexpectedRoot));
+            }
+        };
+        let non_inclusion = ProcessedTransaction::new(
+            tx_count,
+            target_block,
+            Self::get_tx_trie_proof(tx_trie_provider, tx_count)?,
+        );
+
+        Ok(ProcessedTransactionCount::new(
+            target_block,
+            tx_count,
+            transactions_root,
+            last_transaction,
+            non_inclusion,
+        ))
+    }
+
+    /// Proof of the given index in the transactions trie, of inclusion or non-inclusion
+    fn get_tx_trie_proof(
+        tx_trie_provider: &mut TxsMptHandler,
+        tx_index: TxIndex,
+    ) -> Result<Vec<Bytes>, ProviderError> {
+        Ok(tx_trie_provider
+            .get_proof(tx_index)?
+            .into_iter()
+            .map(Bytes::from)
+            .collect())
+    }
+
+    fn get_tx_proofs_from_trie(
+        tx_trie_provider: &mut TxsMptHandler,
+        target_block: BlockNumber,
+        start_index: TxIndex,
+        end_index: TxIndex,
+        incremental: u64,
+    ) -> TxProofsResult {
+        let mut fetched_transaction_proofs = vec![];
         let fetched_transactions = tx_trie_provider.get_elements()?;
         let tx_length = fetched_transactions.len() as u64;
         let target_tx_index_range = (start_index..end_index).step_by(incremental as usize);
@@ -250,47 +629,17 @@ impl EvmProvider {
             ));
         }
 
-        let duration = start_fetch.elapsed();
-        info!("time taken (Transactions Proofs Fetch): {:?}", duration);
-
         Ok(fetched_transaction_proofs)
     }
 
-    /// Fetches the transaction receipts with proof from the MPT trie for the given block number.
-    /// The transaction receipts are fetched from the MPT trie and the proof is generated from the MPT trie.
-    ///
-    /// Return:
-    /// - Transaction receipts proofs mapped by block number
-    pub async fn get_tx_receipt_with_proof_from_block(
-        &self,
+    fn get_tx_receipt_proofs_from_trie(
+        tx_receipt_trie_provider: &mut TxReceiptsMptHandler,
         target_block: BlockNumber,
         start_index: TxIndex,
         end_index: TxIndex,
         incremental: u64,
     ) -> TxReceiptProofsResult {
-        let start_fetch = Instant::now();
-
         let mut fetched_transaction_receipts_proofs = vec![];
-        let mut tx_receipt_trie_provider = TxReceiptsMptHandler::new(self.tx_provider_url.clone())?;
-
-        loop {
-            let trie_response = tx_receipt_trie_provider
-                .build_tx_receipts_tree_from_block(target_block)
-                .await;
-
-            match trie_response {
-                Ok(_) => break,
-                Err(EthTrieError::RPC(RpcError::Transport(TransportErrorKind::HttpError(
-                    http_error,
-                )))) if http_error.status == 429 => {
-                    // retry if 429 error
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    continue;
-                }
-                Err(e) => return Err(ProviderError::EthTrieError(e)),
-            }
-        }
-
         let fetched_transaction_receipts = tx_receipt_trie_provider.get_elements()?;
         let tx_receipt_length = fetched_transaction_receipts.len() as u64;
         let target_tx_index_range = (start_index..end_index).step_by(incremental as usize);
@@ -320,12 +669,6 @@ impl EvmProvider {
             ));
         }
 
-        let duration = start_fetch.elapsed();
-        info!(
-            "time taken (Transaction Receipts Proofs Fetch): {:?}",
-            duration
-        );
-
         Ok(fetched_transaction_receipts_proofs)
     }
 
@@ -401,10 +744,23 @@ impl ProofProvider for EvmProvider {
                 DatalakeEnvelope::TransactionsInBlock(datalake) => {
                     self.fetch_transactions(datalake).await
                 }
+                DatalakeEnvelope::TransactionsInRange(datalake) => {
+                    self.fetch_transactions_in_range(datalake).await
+                }
+                DatalakeEnvelope::TransactionsByHash(datalake) => {
+                    self.fetch_transactions_by_hash(datalake).await
+                }
             }
         })
     }
 
+    fn fetch_multi_proofs<'a>(
+        &'a self,
+        datalake: &'a crate::primitives::task::datalake::block_sampled::MultiBlockSampledDatalake,
+    ) -> AsyncResult<FetchMultiProofsResult> {
+        Box::pin(async move { self.fetch_multi_block_sampled(datalake).await })
+    }
+
     fn fetch_proofs_from_keys(
         &self,
         keys: crate::provider::key::CategorizedFetchKeys,
@@ -417,13 +773,105 @@ impl ProofProvider for EvmProvider {
 #[cfg(feature = "test_utils")]
 mod tests {
     use super::*;
-    use alloy::primitives::address;
-    use alloy::primitives::B256;
+    use crate::primitives::{
+        task::datalake::transactions::{
+            IncludedTypes, TransactionField, TransactionsCollection, TransactionsInBlockDatalake,
+            TxIndexBound, TxIndexRange,
+        },
+        utils::tx_index_to_tx_key,
+        ChainId,
+    };
+    use alloy::consensus::{SignableTransaction, TxEnvelope, TxLegacy};
+    use alloy::primitives::{address, Signature, TxKind, B256, U256};
     use dotenv::dotenv;
-    use std::sync::Once;
+    use eth_trie::{EthTrie, MemoryDB, Trie};
+    use eth_trie_proofs::tx::ConsensusTx;
+    use ethereum_types::H256;
+    use std::sync::{Arc, Once};
 
     static INIT: Once = Once::new();
 
+    fn build_tx_trie_of(txs: Vec<ConsensusTx>) -> TxsMptHandler {
+        let mut trie = EthTrie::new(Arc::new(MemoryDB::new(true)));
+        for (index, tx) in txs.iter().enumerate() {
+            trie.insert(&alloy_rlp::encode(U256::from(index)), &tx.rlp_encode())
+                .unwrap();
+        }
+        let root = B256::from_slice(trie.root_hash().unwrap().as_bytes());
+
+        let mut tx_trie_provider =
+            TxsMptHandler::new(Url::parse("http://localhost:8545").unwrap()).unwrap();
+        tx_trie_provider.build_trie(txs, root).unwrap();
+        tx_trie_provider
+    }
+
+    fn verify_tx_trie_proof(root: B256, tx_index: u64, proof: &[Bytes]) -> Option<Vec<u8>> {
+        EthTrie::new(Arc::new(MemoryDB::new(true)))
+            .verify_proof(
+                H256::from_slice(root.as_slice()),
+                &alloy_rlp::encode(U256::from(tx_index)),
+                proof.iter().map(|node| node.to_vec()).collect(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tx_count_proof() {
+        let txs: Vec<ConsensusTx> = (0..3)
+            .map(|nonce| {
+                let tx = TxLegacy {
+                    chain_id: Some(1),
+                    nonce,
+                    gas_price: 1,
+                    gas_limit: 21000,
+                    to: TxKind::Call(Address::ZERO),
+                    ..Default::default()
+                };
+                ConsensusTx(TxEnvelope::Legacy(
+                    tx.into_signed(Signature::test_signature()),
+                ))
+            })
+            .collect();
+        let last_tx = txs[2].rlp_encode();
+        let mut tx_trie_provider = build_tx_trie_of(txs);
+
+        let tx_count =
+            EvmProvider::get_tx_count_proof_from_trie(&mut tx_trie_provider, 100).unwrap();
+        assert_eq!(tx_count.block_number, 100);
+        assert_eq!(tx_count.count, 3);
+        assert_eq!(
+            tx_count.transactions_root,
+            tx_trie_provider.get_root().unwrap()
+        );
+
+        // the transaction at index 2 exists, and no transaction exists at index 3
+        let last_transaction = tx_count.last_transaction.unwrap();
+        assert_eq!(last_transaction.key, tx_index_to_tx_key(2));
+        assert_eq!(
+            verify_tx_trie_proof(tx_count.transactions_root, 2, &last_transaction.proof),
+            Some(last_tx)
+        );
+        assert_eq!(tx_count.non_inclusion.key, tx_index_to_tx_key(3));
+        assert_eq!(
+            verify_tx_trie_proof(tx_count.transactions_root, 3, &tx_count.non_inclusion.proof),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tx_count_proof_of_empty_block() {
+        let mut tx_trie_provider = build_tx_trie_of(vec![]);
+
+        let tx_count =
+            EvmProvider::get_tx_count_proof_from_trie(&mut tx_trie_provider, 100).unwrap();
+        assert_eq!(tx_count.count, 0);
+        assert_eq!(tx_count.transactions_root, EMPTY_TRANSACTIONS);
+        assert_eq!(tx_count.last_transaction, None);
+        assert_eq!(tx_count.non_inclusion.key, tx_index_to_tx_key(0));
+        // the empty root proves that the trie has no transaction
+        assert!(tx_count.non_inclusion.proof.is_empty());
+    }
+
     fn initialize() {
         INIT.call_once(|| {
             dotenv().ok();
@@ -593,6 +1041,42 @@ mod tests {
         assert_eq!(result4.unwrap().len(), 75);
     }
 
+    #[tokio::test]
+    #[cfg(feature = "test_utils")]
+    async fn test_fetch_transactions_with_resolved_index_range() {
+        initialize();
+        let provider = EvmProvider::default();
+        let datalake = TransactionsInBlockDatalake::new(
+            ChainId::EthereumSepolia,
+            6127485,
+            TransactionsCollection::Transactions(TransactionField::Nonce),
+            0,
+            0,
+            1,
+            IncludedTypes::from(&[1, 1, 1, 1]),
+        )
+        .with_index_range(TxIndexRange::new(
+            TxIndexBound::FromEnd(3),
+            TxIndexBound::LAST,
+        ));
+        let datalake = provider.resolve_tx_index_range(&datalake).await.unwrap();
+        assert_eq!((datalake.start_index, datalake.end_index), (90, 93));
+
+        let fetched = provider.fetch_transactions(&datalake).await.unwrap();
+        assert_eq!(fetched.values.len(), 3);
+        // the resolved range is proven with the number of transactions of the block
+        assert_eq!(fetched.transaction_counts.len(), 1);
+        let tx_count = fetched.transaction_counts.into_iter().next().unwrap();
+        assert_eq!(tx_count.block_number, 6127485);
+        assert_eq!(tx_count.count, 93);
+        assert_eq!(
+            tx_count.last_transaction.unwrap().key,
+            tx_index_to_tx_key(92)
+        );
+        assert_eq!(tx_count.non_inclusion.key, tx_index_to_tx_key(93));
+        assert!(!tx_count.non_inclusion.proof.is_empty());
+    }
+
     #[tokio::test]
     #[cfg(feature = "test_utils")]
     async fn test_error_get_tx_with_proof_from_block() {
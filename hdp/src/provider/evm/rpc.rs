@@ -5,7 +5,7 @@ use std::{
 };
 
 use alloy::{
-    primitives::{Address, BlockNumber, StorageKey},
+    primitives::{Address, BlockNumber, Bytes, StorageKey, TxHash, TxIndex},
     providers::{Provider, RootProvider},
     rpc::types::EIP1186AccountProofResponse,
     transports::{
@@ -33,6 +33,15 @@ pub enum RpcProviderError {
             alloy::rpc::types::EIP1186AccountProofResponse,
         )>,
     ),
+
+    #[error("Failed to fetch from rpc: {0}")]
+    RpcError(#[from] RpcError<TransportErrorKind>),
+
+    #[error("Block {0} not found")]
+    BlockNotFound(BlockNumber),
+
+    #[error("Transaction {0} not found or pending")]
+    TransactionNotFound(TxHash),
 }
 
 /// RPC provider for fetching data from Ethereum RPC
@@ -89,6 +98,53 @@ impl RpcProvider {
             .await
     }
 
+    /// Get code of the account at the given block
+    pub async fn get_code(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+    ) -> Result<Bytes, RpcProviderError> {
+        Ok(self
+            .provider
+            .get_code_at(address)
+            .block_id(block_number.into())
+            .await?)
+    }
+
+    /// Get the latest block number
+    pub async fn get_block_number(&self) -> Result<BlockNumber, RpcProviderError> {
+        Ok(self.provider.get_block_number().await?)
+    }
+
+    /// Get the block number and index of the mined transaction with the given hash
+    pub async fn get_transaction_position(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<(BlockNumber, TxIndex), RpcProviderError> {
+        let tx = self
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await?
+            .ok_or(RpcProviderError::TransactionNotFound(tx_hash))?;
+        match (tx.block_number, tx.transaction_index) {
+            (Some(block_number), Some(tx_index)) => Ok((block_number, tx_index)),
+            _ => Err(RpcProviderError::TransactionNotFound(tx_hash)),
+        }
+    }
+
+    /// Get the timestamp of the given block
+    pub async fn get_block_timestamp(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<u64, RpcProviderError> {
+        let block = self
+            .provider
+            .get_block_by_number(block_number.into(), false)
+            .await?
+            .ok_or(RpcProviderError::BlockNotFound(block_number))?;
+        Ok(block.header.timestamp)
+    }
+
     /// Generalized function to get proofs (account or storage) in given vector of blocks
     async fn get_proofs(
         &self,
@@ -10,6 +10,7 @@ use super::key::CategorizedFetchKeys;
 use super::types::FetchedDatalake;
 
 pub type FetchProofsResult = Result<FetchedDatalake, ProviderError>;
+pub type FetchMultiProofsResult = Result<Vec<FetchedDatalake>, ProviderError>;
 pub type FetchProofsFromKeysResult = Result<ProcessedBlockProofs, ProviderError>;
 
 pub type AsyncResult<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -17,6 +18,7 @@ pub type AsyncResult<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 /// Trait for generic proof provider.
 ///
 /// - `fetch_proofs` is used to fetch proofs from datalake.
+/// - `fetch_multi_proofs` is used to fetch proofs of several properties sampled over the same blocks, sharing the common proofs.
 /// - `fetch_proofs_from_keys` is used to fetch proofs from the provider based on the keys. Used in module.
 pub trait ProofProvider: Send + Sync {
     fn fetch_proofs<'a>(
@@ -24,6 +26,11 @@ pub trait ProofProvider: Send + Sync {
         datalake: &'a crate::primitives::task::datalake::DatalakeCompute,
     ) -> AsyncResult<FetchProofsResult>;
 
+    fn fetch_multi_proofs<'a>(
+        &'a self,
+        datalake: &'a crate::primitives::task::datalake::block_sampled::MultiBlockSampledDatalake,
+    ) -> AsyncResult<FetchMultiProofsResult>;
+
     fn fetch_proofs_from_keys(
         &self,
         keys: CategorizedFetchKeys,
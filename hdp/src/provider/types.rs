@@ -6,8 +6,9 @@
 use std::collections::HashSet;
 
 use crate::primitives::processed_types::{
-    account::ProcessedAccount, header::ProcessedHeader, mmr::MMRMeta, receipt::ProcessedReceipt,
-    storage::ProcessedStorage, transaction::ProcessedTransaction,
+    account::ProcessedAccount, bytecode::ProcessedBytecode, header::ProcessedHeader, mmr::MMRMeta,
+    receipt::ProcessedReceipt, storage::ProcessedStorage, transaction::ProcessedTransaction,
+    transaction_count::ProcessedTransactionCount,
 };
 
 use alloy::primitives::U256;
@@ -70,9 +71,12 @@ impl FetchedTransactionReceiptProof {
     }
 }
 
+#[derive(Default)]
 pub struct FetchedDatalake {
     /// Targeted datalake's compiled results
     pub values: Vec<U256>,
+    /// Timestamps of the blocks of the values, for block sampled datalakes
+    pub timestamps: Vec<u64>,
     /// Headers related to the datalake
     pub headers: HashSet<ProcessedHeader>,
     /// Accounts related to the datalake
@@ -83,6 +87,10 @@ pub struct FetchedDatalake {
     pub transactions: HashSet<ProcessedTransaction>,
     /// Transaction receipts related to the datalake
     pub transaction_receipts: HashSet<ProcessedReceipt>,
+    /// Bytecodes related to the datalake
+    pub bytecodes: HashSet<ProcessedBytecode>,
+    /// Transaction counts related to the datalake
+    pub transaction_counts: HashSet<ProcessedTransactionCount>,
     /// MMR meta data related to the headers
     pub mmr_metas: HashSet<MMRMeta>,
 }